@@ -0,0 +1,268 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Measures how many events per second FSMs process for common interactions.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use interacto::dom::{
+    Event, EventType, KeyEventType, KeyboardEvent, Modifiers, MouseEvent, MouseEventType, Touch,
+    TouchEvent, TouchEventType,
+};
+use interacto::fsm::FSM;
+use interacto::r#impl::fsm::{
+    ConcurrentAndFSM, FSMImpl, KeyTransition, MouseTransition, TimeoutTransition, TouchTransition,
+    TypedAction,
+};
+
+struct BenchMouseEvent {
+    event_type: MouseEventType,
+    button: i16,
+}
+
+impl Event for BenchMouseEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Mouse(self.event_type)
+    }
+
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl MouseEvent for BenchMouseEvent {
+    fn button(&self) -> i16 {
+        self.button
+    }
+}
+
+struct BenchKeyEvent {
+    event_type: KeyEventType,
+    key: String,
+}
+
+impl Event for BenchKeyEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Key(self.event_type)
+    }
+
+    fn as_keyboard_event(&self) -> Option<&(dyn KeyboardEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl KeyboardEvent for BenchKeyEvent {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn code(&self) -> &str {
+        &self.key
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        Modifiers::NONE
+    }
+}
+
+struct BenchTouchEvent {
+    event_type: TouchEventType,
+    touches: Vec<Touch>,
+}
+
+impl Event for BenchTouchEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Touch(self.event_type)
+    }
+
+    fn as_touch_event(&self) -> Option<&(dyn TouchEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl TouchEvent for BenchTouchEvent {
+    fn changed_touches(&self) -> &[Touch] {
+        &self.touches
+    }
+}
+
+fn mouse(event_type: MouseEventType) -> Box<dyn Event> {
+    Box::new(BenchMouseEvent {
+        event_type,
+        button: 0,
+    })
+}
+
+fn key(event_type: KeyEventType, key: &str) -> Box<dyn Event> {
+    Box::new(BenchKeyEvent {
+        event_type,
+        key: key.to_string(),
+    })
+}
+
+fn touch(event_type: TouchEventType, identifier: i32) -> Box<dyn Event> {
+    Box::new(BenchTouchEvent {
+        event_type,
+        touches: vec![Touch::new(identifier)],
+    })
+}
+
+/// A drag-and-drop: press, move (starts the interaction), release.
+/// Escape or a release without move cancels it.
+fn dnd(moves: Rc<Cell<u64>>) -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    let dragged = fsm.add_std_state("dragged", true);
+    let released = fsm.add_terminal_state("released", false);
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    let on_move = move || -> Option<TypedAction<dyn MouseEvent>> {
+        let moves = moves.clone();
+        Some(Box::new(move |_| {
+            moves.set(moves.get() + 1);
+            Ok(())
+        }))
+    };
+    fsm.add_transition(Box::new(
+        MouseTransition::new(init, pressed, [MouseEventType::Mousedown], None, None).with_button(0),
+    ));
+    fsm.add_transition(Box::new(MouseTransition::new(
+        pressed,
+        cancelled,
+        [MouseEventType::Mouseup],
+        None,
+        None,
+    )));
+    fsm.add_transition(Box::new(MouseTransition::new(
+        pressed,
+        dragged,
+        [MouseEventType::Mousemove],
+        on_move(),
+        None,
+    )));
+    fsm.add_transition(Box::new(
+        KeyTransition::new(dragged, cancelled, [KeyEventType::Keydown], None, None)
+            .with_key("Escape"),
+    ));
+    fsm.add_transition(Box::new(MouseTransition::new(
+        dragged,
+        released,
+        [MouseEventType::Mouseup],
+        None,
+        None,
+    )));
+    fsm.add_transition(Box::new(MouseTransition::new(
+        dragged,
+        dragged,
+        [MouseEventType::Mousemove],
+        on_move(),
+        None,
+    )));
+    fsm
+}
+
+/// Keys typed until Enter is released, or until one second without typing.
+fn keys_typed() -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let typed = fsm.add_std_state("typed", false);
+    let ended = fsm.add_terminal_state("ended", false);
+    fsm.add_transition(Box::new(KeyTransition::new(
+        init,
+        typed,
+        [KeyEventType::Keyup],
+        None,
+        None,
+    )));
+    fsm.add_transition(Box::new(
+        KeyTransition::new(typed, ended, [KeyEventType::Keyup], None, None).with_key("Enter"),
+    ));
+    fsm.add_transition(Box::new(KeyTransition::new(
+        typed,
+        typed,
+        [KeyEventType::Keyup],
+        None,
+        None,
+    )));
+    fsm.add_transition(Box::new(TimeoutTransition::with_delay(
+        typed, ended, 1000, None,
+    )));
+    fsm
+}
+
+/// The drag of the touch point that has the given identifier.
+fn touch_dnd(identifier: i32) -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let touched = fsm.add_std_state("touched", false);
+    let moved = fsm.add_std_state("moved", false);
+    let released = fsm.add_terminal_state("released", false);
+    let transition = |src, tgt, event_type| {
+        Box::new(TouchTransition::new(src, tgt, [event_type], None, None).with_touch_id(identifier))
+    };
+    fsm.add_transition(transition(init, touched, TouchEventType::Touchstart));
+    fsm.add_transition(transition(touched, moved, TouchEventType::Touchmove));
+    fsm.add_transition(transition(moved, moved, TouchEventType::Touchmove));
+    fsm.add_transition(transition(moved, released, TouchEventType::Touchend));
+    fsm
+}
+
+fn bench_events(c: &mut Criterion, name: &str, mut fsm: impl FSM, events: &[Box<dyn Event>]) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(events.len() as u64));
+    group.bench_function("process", |b| {
+        b.iter(|| {
+            for event in events {
+                black_box(fsm.process(event.as_ref()));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_dnd(c: &mut Criterion) {
+    let events: Vec<Box<dyn Event>> = std::iter::once(mouse(MouseEventType::Mousedown))
+        .chain((0..1000).map(|_| mouse(MouseEventType::Mousemove)))
+        .chain(std::iter::once(mouse(MouseEventType::Mouseup)))
+        .collect();
+    bench_events(c, "dnd", dnd(Rc::new(Cell::new(0))), &events);
+}
+
+fn bench_key_typing(c: &mut Criterion) {
+    let events: Vec<Box<dyn Event>> = "the quick brown fox jumps over the lazy dog"
+        .chars()
+        .map(|c| c.to_string())
+        .chain(std::iter::once("Enter".to_string()))
+        .flat_map(|k| [key(KeyEventType::Keydown, &k), key(KeyEventType::Keyup, &k)])
+        .collect();
+    bench_events(c, "key_typing", keys_typed(), &events);
+}
+
+fn bench_multi_touch(c: &mut Criterion) {
+    let ids = 0..3;
+    let events: Vec<Box<dyn Event>> = ids
+        .clone()
+        .map(|id| touch(TouchEventType::Touchstart, id))
+        .chain((0..300).map(|i| touch(TouchEventType::Touchmove, i % 3)))
+        .chain(ids.clone().map(|id| touch(TouchEventType::Touchend, id)))
+        .collect();
+    let fsm = ConcurrentAndFSM::new(ids.map(touch_dnd).collect());
+    bench_events(c, "multi_touch", fsm, &events);
+}
+
+criterion_group!(benches, bench_dnd, bench_key_typing, bench_multi_touch);
+criterion_main!(benches);
@@ -0,0 +1,108 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Applies arbitrary sequences of operations to a command and checks that its status
+//! follows the `CmdStatus` machine: a command executes only when created or executed,
+//! creates its memento once, and cannot leave the `Flushed` status.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use interacto::command::{Cmd, CmdStatus, Command, CustomCmd};
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Execute,
+    Done,
+    Flush,
+    Cancel,
+    SetCanExecute(bool),
+}
+
+#[derive(Default)]
+struct CountingCmd {
+    can_execute: bool,
+    mementos: u32,
+    executions: u32,
+}
+
+impl CustomCmd for CountingCmd {
+    fn create_memento(&mut self) {
+        self.mementos += 1;
+    }
+
+    fn can_execute(&self) -> bool {
+        self.can_execute
+    }
+
+    fn execution(&mut self) {
+        self.executions += 1;
+    }
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut cmd = Cmd::new(CountingCmd {
+        can_execute: true,
+        ..CountingCmd::default()
+    });
+    let mut executions = 0;
+
+    for op in ops {
+        let before = cmd.get_status();
+        match op {
+            Op::Execute => {
+                let executable = matches!(before, CmdStatus::Created | CmdStatus::Executed)
+                    && cmd.child.can_execute;
+                assert_eq!(cmd.execute(), executable);
+                if executable {
+                    executions += 1;
+                    assert_eq!(cmd.get_status(), CmdStatus::Executed);
+                } else {
+                    assert_eq!(cmd.get_status(), before);
+                }
+            }
+            Op::Done => {
+                cmd.done();
+                let expected = match before {
+                    CmdStatus::Created | CmdStatus::Executed => CmdStatus::Done,
+                    status => status,
+                };
+                assert_eq!(cmd.get_status(), expected);
+            }
+            Op::Flush => {
+                cmd.flush();
+                assert_eq!(cmd.get_status(), CmdStatus::Flushed);
+            }
+            Op::Cancel => {
+                cmd.cancel();
+                let expected = match before {
+                    CmdStatus::Flushed => CmdStatus::Flushed,
+                    _ => CmdStatus::Cancelled,
+                };
+                assert_eq!(cmd.get_status(), expected);
+            }
+            Op::SetCanExecute(can_execute) => cmd.child.can_execute = can_execute,
+        }
+
+        if before == CmdStatus::Flushed {
+            assert_eq!(cmd.get_status(), CmdStatus::Flushed);
+        }
+        assert_eq!(cmd.child.executions, executions);
+        assert_eq!(cmd.child.mementos, executions.min(1));
+        assert_eq!(cmd.is_done(), cmd.get_status() == CmdStatus::Done);
+        assert_eq!(cmd.had_effect(), cmd.is_done());
+    }
+});
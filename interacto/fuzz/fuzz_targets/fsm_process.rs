@@ -0,0 +1,180 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Builds FSMs from the fuzz input and makes them process well-formed event sequences:
+//! processing must not panic, and the FSMs must follow the invariants checked by
+//! `check_fsm_invariants`, whatever their states, transitions, guards, errors and timeouts.
+
+#![no_main]
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use interacto::dom::{Event, EventType, KeyEventType, MouseEventType, TouchEventType};
+use interacto::fsm::{ErrorPolicy, StateId, FSM};
+use interacto::r#impl::clock::VirtualClock;
+use interacto::r#impl::fsm::{
+    FSMImpl, TimeoutTransition, TransitionAction, TransitionBase, TransitionGuard,
+};
+use interacto::testing::{check_fsm_invariants, EventSequence};
+
+const EVENT_TYPES: [EventType; 11] = [
+    EventType::Mouse(MouseEventType::Mousedown),
+    EventType::Mouse(MouseEventType::Mousemove),
+    EventType::Mouse(MouseEventType::Mouseup),
+    EventType::Mouse(MouseEventType::Click),
+    EventType::Mouse(MouseEventType::Auxclick),
+    EventType::Key(KeyEventType::Keydown),
+    EventType::Key(KeyEventType::Keyup),
+    EventType::Touch(TouchEventType::Touchstart),
+    EventType::Touch(TouchEventType::Touchmove),
+    EventType::Touch(TouchEventType::Touchend),
+    EventType::Wheel,
+];
+
+const MAX_STATES: usize = 8;
+const MAX_TRANSITIONS: usize = 32;
+
+#[derive(Debug, Arbitrary)]
+enum StateSpec {
+    Std { starting: bool },
+    Terminal { starting: bool },
+    Cancelling,
+}
+
+#[derive(Debug, Arbitrary)]
+enum GuardSpec {
+    Always,
+    Never,
+    /// Alternately respected and not respected.
+    Alternate,
+    Failing,
+}
+
+#[derive(Debug, Arbitrary)]
+enum TransitionSpec {
+    Events {
+        src: u8,
+        tgt: u8,
+        events: Vec<u8>,
+        guard: Option<GuardSpec>,
+        failing_action: bool,
+    },
+    Timeout {
+        src: u8,
+        tgt: u8,
+        delay: u16,
+        failing_action: bool,
+    },
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    states: Vec<StateSpec>,
+    transitions: Vec<TransitionSpec>,
+    error_policy: u8,
+    events: EventSequence,
+}
+
+fn guard(spec: GuardSpec) -> TransitionGuard {
+    match spec {
+        GuardSpec::Always => Box::new(|_: &dyn Event| Ok(true)),
+        GuardSpec::Never => Box::new(|_: &dyn Event| Ok(false)),
+        GuardSpec::Alternate => {
+            let respected = Cell::new(false);
+            Box::new(move |_: &dyn Event| Ok(!respected.replace(!respected.get())))
+        }
+        GuardSpec::Failing => Box::new(|_: &dyn Event| Err("guard failure".into())),
+    }
+}
+
+fn build(input: Input, clock: Rc<VirtualClock>) -> FSMImpl {
+    let mut fsm = FSMImpl::with_clock(clock);
+    let mut outputs = vec![fsm.init_state().id()];
+    let mut inputs: Vec<StateId> = Vec::new();
+    for (index, spec) in input.states.into_iter().take(MAX_STATES).enumerate() {
+        let name = format!("s{index}");
+        match spec {
+            StateSpec::Std { starting } => {
+                let id = fsm.add_std_state(&name, starting);
+                outputs.push(id);
+                inputs.push(id);
+            }
+            StateSpec::Terminal { starting } => {
+                inputs.push(fsm.add_terminal_state(&name, starting))
+            }
+            StateSpec::Cancelling => inputs.push(fsm.add_cancelling_state(&name)),
+        }
+    }
+    if inputs.is_empty() {
+        return fsm;
+    }
+    for spec in input.transitions.into_iter().take(MAX_TRANSITIONS) {
+        match spec {
+            TransitionSpec::Events {
+                src,
+                tgt,
+                events,
+                guard: guard_spec,
+                failing_action,
+            } => {
+                let accepted: HashSet<EventType> = events
+                    .iter()
+                    .map(|event| EVENT_TYPES[*event as usize % EVENT_TYPES.len()])
+                    .collect();
+                let action: Option<TransitionAction> = failing_action
+                    .then(|| Box::new(|_: &dyn Event| Err("action failure".into())) as _);
+                fsm.add_transition(Box::new(TransitionBase::new(
+                    outputs[src as usize % outputs.len()],
+                    inputs[tgt as usize % inputs.len()],
+                    accepted,
+                    action,
+                    guard_spec.map(guard),
+                )));
+            }
+            TransitionSpec::Timeout {
+                src,
+                tgt,
+                delay,
+                failing_action,
+            } => {
+                fsm.add_transition(Box::new(TimeoutTransition::with_delay(
+                    outputs[src as usize % outputs.len()],
+                    inputs[tgt as usize % inputs.len()],
+                    u64::from(delay),
+                    failing_action.then(|| Box::new(|| Err("timeout failure".into())) as _),
+                )));
+            }
+        }
+    }
+    fsm.set_error_policy(match input.error_policy % 3 {
+        0 => ErrorPolicy::Stay,
+        1 => ErrorPolicy::Cancel,
+        _ => ErrorPolicy::Reinit,
+    });
+    fsm
+}
+
+fuzz_target!(|input: Input| {
+    let clock = Rc::new(VirtualClock::new());
+    let events = input.events.0.clone();
+    let mut fsm = build(input, clock.clone());
+    if let Err(violation) = check_fsm_invariants(&mut fsm, &events, Some(&clock)) {
+        panic!("{violation}");
+    }
+});
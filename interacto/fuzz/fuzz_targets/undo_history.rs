@@ -0,0 +1,128 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Applies arbitrary sequences of operations to a linear undo history and compares it
+//! with a model of the expected undo and redo stacks: undoable objects must be undone and
+//! redone in order, and the history must never exceed its maximal size.
+
+#![no_main]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use interacto::linearhistory::LinearHistoryImpl;
+use interacto::undoble::Undoable;
+use interacto::undohistory::{UndoHistory, UndoHistoryBase};
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Add,
+    Undo,
+    Redo,
+    Clear,
+    SetSizeMax(u8),
+}
+
+/// An undoable object that logs its undo and redo calls.
+struct LoggedUndoable {
+    name: String,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl Undoable for LoggedUndoable {
+    fn undo(&mut self) {
+        self.log.borrow_mut().push(format!("undo {}", self.name));
+    }
+
+    fn redo(&mut self) {
+        self.log.borrow_mut().push(format!("redo {}", self.name));
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+fn names(undoables: &[Box<dyn Undoable>]) -> Vec<String> {
+    undoables.iter().map(|u| u.get_undo_name()).collect()
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut history = LinearHistoryImpl::new();
+    let mut undos: Vec<String> = Vec::new();
+    let mut redos: Vec<String> = Vec::new();
+    let mut expected_log: Vec<String> = Vec::new();
+
+    for (index, op) in ops.into_iter().enumerate() {
+        match op {
+            Op::Add => {
+                let name = index.to_string();
+                let base: &mut dyn UndoHistoryBase = &mut history;
+                base.add(Box::new(LoggedUndoable {
+                    name: name.clone(),
+                    log: log.clone(),
+                }));
+                let size_max = history.get_size_max() as usize;
+                if size_max > 0 {
+                    if undos.len() == size_max {
+                        undos.remove(0);
+                    }
+                    undos.push(name);
+                    redos.clear();
+                }
+            }
+            Op::Undo => {
+                history.undo();
+                if let Some(name) = undos.pop() {
+                    expected_log.push(format!("undo {name}"));
+                    redos.push(name);
+                }
+            }
+            Op::Redo => {
+                history.redo();
+                if let Some(name) = redos.pop() {
+                    expected_log.push(format!("redo {name}"));
+                    undos.push(name);
+                }
+            }
+            Op::Clear => {
+                history.clear();
+                undos.clear();
+                redos.clear();
+            }
+            Op::SetSizeMax(max) => {
+                history.set_size_max(u64::from(max));
+                let removed = undos.len().saturating_sub(max as usize);
+                undos.drain(..removed);
+            }
+        }
+
+        assert!(history.get_undo().len() as u64 <= history.get_size_max());
+        assert_eq!(names(history.get_undo()), undos);
+        assert_eq!(names(history.get_redo()), redos);
+        assert_eq!(
+            history.get_last_undo().map(|u| u.get_undo_name()),
+            undos.last().cloned()
+        );
+        assert_eq!(
+            history.get_last_redo().map(|u| u.get_undo_name()),
+            redos.last().cloned()
+        );
+        assert_eq!(*log.borrow(), expected_log);
+    }
+});
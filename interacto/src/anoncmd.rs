@@ -18,11 +18,11 @@ use crate::command::CustomCmd;
  * The goal of this command is to avoid the creation of a command class for a small command.
  */
 pub struct AnonCmd<'a> {
-    exec: Box<dyn 'a + FnMut() -> ()>
+    exec: Box<dyn 'a + FnMut()>
 }
 
 impl<'a> AnonCmd<'a> {
-    pub fn new(fct: impl 'a + FnMut() -> ()) -> Self {
+    pub fn new(fct: impl 'a + FnMut()) -> Self {
         Self {
             exec: Box::new(fct)
         }
@@ -13,7 +13,6 @@
  */
 
 use std::collections::HashMap;
-use std::fmt;
 
 use crate::checker::{RuleName, Severity};
 use crate::command::Command;
@@ -85,14 +84,15 @@ where
     fn uninstall_binding(&self);
 
     /// Visiting the binding.
-    fn accept_visitor(&self, visitor: &dyn VisitorBinding);
+    fn accept_visitor(&self, visitor: &dyn VisitorBinding<C, I, A, D>);
 }
 
 /// Visitor trait
-pub trait VisitorBinding {
-    fn visit_binding<C, I, A, D>(&self, binding: &dyn Binding<C, I, A, D>)
-    where
-        C: Command,
-        I: Interaction<D>,
-        D: InteractionData;
+pub trait VisitorBinding<C, I, A, D>
+where
+    C: Command,
+    I: Interaction<D>,
+    D: InteractionData,
+{
+    fn visit_binding(&self, binding: &dyn Binding<C, I, A, D>);
 }
\ No newline at end of file
@@ -12,8 +12,6 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::collections::HashSet;
-
 /// A type for the rule names.
 /// @category Checker
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -38,7 +36,7 @@ pub type LinterRule = (RuleName, Severity);
 
 /// The interaction type check.
 /// @category Checker
-trait Checker {
+pub trait Checker {
     fn set_linter_rules(&mut self, rules: Vec<LinterRule>);
     // fn check_rules(&self, binding: &Binding<Command, Interaction<InteractionData>, ()>, 
     //                binds: &[Binding<Command, Interaction<InteractionData>, ()>]);
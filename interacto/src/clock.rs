@@ -0,0 +1,23 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// The source of time of the FSMs, used to run their timeout transitions.
+/// Platforms use the real time (see `SystemClock`), while tests can use a
+/// virtual clock to step time forward deterministically (see `VirtualClock`).
+/// # Category
+/// API FSM
+pub trait Clock {
+    /// The current time, in milliseconds, from an arbitrary origin.
+    fn now(&self) -> u64;
+}
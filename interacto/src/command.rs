@@ -12,6 +12,8 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use alloc::string::String;
+
 use crate::undoble::Undoable;
 
 /**
@@ -45,7 +47,7 @@ pub trait Command {
      */
     fn create_memento(&mut self);
 
-    fn can_execute(&self);
+    fn can_execute(&self) -> bool;
 
     /**
      * This method contains the statements to execute the command.
@@ -64,10 +66,14 @@ pub trait Command {
      */
     fn flush(&mut self);
 
-    fn had_effect(&self);
+    fn had_effect(&self) -> bool;
 
     fn is_done(&self) -> bool;
 
+    /**
+     * Cancels the command.
+     * A flushed command cannot be cancelled.
+     */
     fn cancel(&mut self);
 }
 
@@ -182,7 +188,9 @@ impl<T: CustomCmd> Command for Cmd<T> {
     }
 
     fn cancel(&mut self) {
-        self.set_status(CmdStatus::Cancelled)
+        if self.get_status() != CmdStatus::Flushed {
+            self.set_status(CmdStatus::Cancelled)
+        }
     }
 }
 
@@ -204,6 +212,7 @@ pub trait CustomCmd: Sized {
      */
     fn execution(&mut self);
 
+    #[allow(clippy::wrong_self_convention)]
     fn as_command(self) -> Cmd<Self> {
         Cmd::new(self)
     }
@@ -212,4 +221,18 @@ pub trait CustomCmd: Sized {
 pub trait UndoableCmd<'a>: CustomCmd + Undoable {}
 pub trait UndoableCommand<'a>: Command + Undoable {}
 
+impl<T: CustomCmd + Undoable> Undoable for Cmd<T> {
+    fn undo(&mut self) {
+        self.child.undo()
+    }
+
+    fn redo(&mut self) {
+        self.child.redo()
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.child.get_undo_name()
+    }
+}
+
 impl<'a, T: UndoableCmd<'a>> UndoableCommand<'a> for Cmd<T> {}
@@ -0,0 +1,260 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Event, EventType, NodeId, NodeTree};
+
+/// A listener of the events dispatched to a node.
+/// It can alter the dispatch through the given context.
+pub type EventListener = Box<dyn FnMut(&dyn Event, &mut DispatchContext)>;
+
+/**
+ * The phase of the propagation of an event, as in the DOM.
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventPhase {
+    /// The event goes down from the root to the parent of the target.
+    Capturing,
+    /// The event is at its target.
+    AtTarget,
+    /// The event goes up from the parent of the target to the root.
+    Bubbling,
+}
+
+/**
+ * Identifies a listener registered to an event dispatcher.
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ListenerId(u64);
+
+/**
+ * The state of the dispatch of an event, given to the listeners.
+ * Listeners, such as user interactions, stop the propagation of the event
+ * or prevent its default behaviour through it.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchContext {
+    target: NodeId,
+    current_target: NodeId,
+    phase: EventPhase,
+    propagation_stopped: bool,
+    immediate_propagation_stopped: bool,
+    default_prevented: bool,
+}
+
+impl DispatchContext {
+    fn new(target: NodeId) -> Self {
+        Self {
+            target,
+            current_target: target,
+            phase: EventPhase::Capturing,
+            propagation_stopped: false,
+            immediate_propagation_stopped: false,
+            default_prevented: false,
+        }
+    }
+
+    /// The node the event is dispatched to.
+    pub fn target(&self) -> NodeId {
+        self.target
+    }
+
+    /// The node whose listeners are currently called.
+    pub fn current_target(&self) -> NodeId {
+        self.current_target
+    }
+
+    pub fn phase(&self) -> EventPhase {
+        self.phase
+    }
+
+    /// Stops the propagation of the event once the listeners of the current node
+    /// for the current phase are called. At the target, the capture listeners and the
+    /// other ones are called in two steps: stopping the propagation in a capture listener
+    /// spares the other ones.
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    /// Stops the propagation of the event right now:
+    /// the next listeners, even of the current node, are not called.
+    pub fn stop_immediate_propagation(&mut self) {
+        self.propagation_stopped = true;
+        self.immediate_propagation_stopped = true;
+    }
+
+    /// Prevents the default behaviour associated to the event.
+    /// The propagation of the event goes on.
+    pub fn prevent_default(&mut self) {
+        self.default_prevented = true;
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.propagation_stopped
+    }
+
+    pub fn is_default_prevented(&self) -> bool {
+        self.default_prevented
+    }
+}
+
+/**
+ * The result of the dispatch of an event.
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DispatchOutcome {
+    /// True if a listener prevented the default behaviour of the event:
+    /// the UI toolkit must not perform it.
+    pub default_prevented: bool,
+    /// True if a listener stopped the propagation of the event.
+    pub propagation_stopped: bool,
+    /// The number of listeners called.
+    pub listeners_called: usize,
+}
+
+struct RegisteredListener {
+    id: ListenerId,
+    event_type: EventType,
+    capture: bool,
+    listener: EventListener,
+}
+
+/**
+ * Dispatches events over a tree of nodes, without a browser.
+ * As in the DOM, an event dispatched to a node goes through three phases:
+ * the capture listeners of its ancestors are called from the root down to its parent,
+ * then the listeners of the node itself (the capture ones first, then the other ones),
+ * then, if the event type bubbles, the non-capture listeners of its ancestors
+ * from its parent up to the root.
+ * The listeners of a node are called in their registration order.
+ * @category Helper
+ */
+#[derive(Default)]
+pub struct EventDispatcher {
+    listeners: BTreeMap<NodeId, Vec<RegisteredListener>>,
+    next_id: u64,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a listener of the events of the given type dispatched to the given node
+    /// or, through propagation, to its descendants.
+    /// A capture listener is called during the capture phase, a non-capture one during the bubble phase.
+    pub fn add_listener(
+        &mut self,
+        node: NodeId,
+        event_type: EventType,
+        capture: bool,
+        listener: impl FnMut(&dyn Event, &mut DispatchContext) + 'static,
+    ) -> ListenerId {
+        let id = ListenerId(self.next_id);
+        self.next_id += 1;
+        self.listeners
+            .entry(node)
+            .or_default()
+            .push(RegisteredListener {
+                id,
+                event_type,
+                capture,
+                listener: Box::new(listener),
+            });
+        id
+    }
+
+    /// Unregisters a listener. Returns false if it is not registered.
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        for listeners in self.listeners.values_mut() {
+            if let Some(index) = listeners.iter().position(|registered| registered.id == id) {
+                listeners.remove(index);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Unregisters all the listeners of the given node (for example when the node is removed from the UI).
+    pub fn remove_listeners_of(&mut self, node: NodeId) {
+        self.listeners.remove(&node);
+    }
+
+    /// Dispatches the event to the given node of the tree (usually `event.target()`).
+    /// A node that does not belong to the tree receives the event without propagation.
+    pub fn dispatch(
+        &mut self,
+        tree: &dyn NodeTree,
+        target: NodeId,
+        event: &dyn Event,
+    ) -> DispatchOutcome {
+        let mut path = tree.propagation_path(target);
+        if path.is_empty() {
+            path = vec![target];
+        }
+        let event_type = event.event_type();
+        let mut context = DispatchContext::new(target);
+        let mut outcome = DispatchOutcome::default();
+
+        let capture = path
+            .iter()
+            .skip(1)
+            .rev()
+            .map(|node| (*node, EventPhase::Capturing, true));
+        let at_target = [
+            (target, EventPhase::AtTarget, true),
+            (target, EventPhase::AtTarget, false),
+        ];
+        let bubble = path
+            .iter()
+            .skip(1)
+            .filter(|_| event_type.bubbles())
+            .map(|node| (*node, EventPhase::Bubbling, false));
+
+        for (node, phase, capturing) in capture.chain(at_target).chain(bubble) {
+            // As in the DOM, a capture listener of the target that stops the propagation
+            // also spares the non-capture listeners of the target.
+            if context.propagation_stopped {
+                break;
+            }
+            context.current_target = node;
+            context.phase = phase;
+            outcome.listeners_called +=
+                self.invoke(node, event_type, capturing, event, &mut context);
+        }
+
+        outcome.default_prevented = context.default_prevented;
+        outcome.propagation_stopped = context.propagation_stopped;
+        outcome
+    }
+
+    /// Calls the listeners of the node for the event. Returns the number of listeners called.
+    fn invoke(
+        &mut self,
+        node: NodeId,
+        event_type: EventType,
+        capture: bool,
+        event: &dyn Event,
+        context: &mut DispatchContext,
+    ) -> usize {
+        let Some(listeners) = self.listeners.get_mut(&node) else {
+            return 0;
+        };
+        let mut called = 0;
+        for registered in listeners.iter_mut().filter(|registered| {
+            registered.event_type == event_type && registered.capture == capture
+        }) {
+            if context.immediate_propagation_stopped {
+                break;
+            }
+            (registered.listener)(event, context);
+            called += 1;
+        }
+        called
+    }
+}
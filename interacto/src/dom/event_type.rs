@@ -1,25 +1,69 @@
-use std::fmt;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
 
 /**
  * The supported event types.
+ * Their string form is the DOM name of the event (eg `"mousedown"`, `"focusin"`):
+ * `Display` and `FromStr` convert from and to it.
  * @category Helper
  */
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EventType {
     Mouse(MouseEventType),
     Touch(TouchEventType),
     Key(KeyEventType),
+    Pointer(PointerEventType),
+    Focus(FocusEventType),
+    Composition(CompositionEventType),
+    Clipboard(ClipboardEventType),
+    Drag(DragEventType),
     Input,
     Scroll,
     Change,
     Wheel,
 }
 
+/// Declares the DOM names of the variants of an event type enum,
+/// and implements `Display` and `FromStr` from them.
+macro_rules! event_type_names {
+    ($ty:ident { $($variant:ident => $name:literal),+ $(,)? }) => {
+        impl $ty {
+            /// All the variants of the type.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),+];
+
+            /// The DOM name of the event type.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name),+
+                }
+            }
+        }
+
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = ParseEventTypeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok(Self::$variant),)+
+                    _ => Err(ParseEventTypeError::new(s)),
+                }
+            }
+        }
+    };
+}
+
 /**
  * The mouse event type
  * @category Helper
  */
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MouseEventType {
     Mousedown,
     Mouseup,
@@ -30,25 +74,239 @@ pub enum MouseEventType {
     Mouseleave,
     Click,
     Auxclick,
+    Dblclick,
+    Contextmenu,
 }
 
+event_type_names!(MouseEventType {
+    Mousedown => "mousedown",
+    Mouseup => "mouseup",
+    Mousemove => "mousemove",
+    Mouseover => "mouseover",
+    Mouseout => "mouseout",
+    Mouseenter => "mouseenter",
+    Mouseleave => "mouseleave",
+    Click => "click",
+    Auxclick => "auxclick",
+    Dblclick => "dblclick",
+    Contextmenu => "contextmenu",
+});
+
 /**
  * The touch event type
  * @category Helper
  */
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TouchEventType {
     Touchstart,
     Touchend,
     Touchmove,
 }
 
+event_type_names!(TouchEventType {
+    Touchstart => "touchstart",
+    Touchend => "touchend",
+    Touchmove => "touchmove",
+});
+
 /**
  * The key event type
  * @category Helper
  */
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KeyEventType {
     Keydown,
     Keyup,
 }
+
+event_type_names!(KeyEventType {
+    Keydown => "keydown",
+    Keyup => "keyup",
+});
+
+/**
+ * The pointer event type
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerEventType {
+    Pointerdown,
+    Pointermove,
+    Pointerup,
+    Pointercancel,
+    Pointerenter,
+    Pointerleave,
+}
+
+event_type_names!(PointerEventType {
+    Pointerdown => "pointerdown",
+    Pointermove => "pointermove",
+    Pointerup => "pointerup",
+    Pointercancel => "pointercancel",
+    Pointerenter => "pointerenter",
+    Pointerleave => "pointerleave",
+});
+
+/**
+ * The focus event type
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FocusEventType {
+    Focus,
+    Blur,
+    Focusin,
+    Focusout,
+}
+
+event_type_names!(FocusEventType {
+    Focus => "focus",
+    Blur => "blur",
+    Focusin => "focusin",
+    Focusout => "focusout",
+});
+
+/**
+ * The IME composition event type
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompositionEventType {
+    Compositionstart,
+    Compositionupdate,
+    Compositionend,
+}
+
+event_type_names!(CompositionEventType {
+    Compositionstart => "compositionstart",
+    Compositionupdate => "compositionupdate",
+    Compositionend => "compositionend",
+});
+
+/**
+ * The clipboard event type
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardEventType {
+    Copy,
+    Cut,
+    Paste,
+}
+
+event_type_names!(ClipboardEventType {
+    Copy => "copy",
+    Cut => "cut",
+    Paste => "paste",
+});
+
+/**
+ * The HTML5 drag and drop event type
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DragEventType {
+    Dragstart,
+    Dragover,
+    Drop,
+    Dragend,
+}
+
+event_type_names!(DragEventType {
+    Dragstart => "dragstart",
+    Dragover => "dragover",
+    Drop => "drop",
+    Dragend => "dragend",
+});
+
+impl EventType {
+    /// The DOM name of the event type.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mouse(event_type) => event_type.as_str(),
+            Self::Touch(event_type) => event_type.as_str(),
+            Self::Key(event_type) => event_type.as_str(),
+            Self::Pointer(event_type) => event_type.as_str(),
+            Self::Focus(event_type) => event_type.as_str(),
+            Self::Composition(event_type) => event_type.as_str(),
+            Self::Clipboard(event_type) => event_type.as_str(),
+            Self::Drag(event_type) => event_type.as_str(),
+            Self::Input => "input",
+            Self::Scroll => "scroll",
+            Self::Change => "change",
+            Self::Wheel => "wheel",
+        }
+    }
+
+    /// True if events of this type bubble up to the ancestors of their target
+    /// once dispatched to it (see `EventDispatcher`).
+    /// As in the DOM, `focus`, `blur`, the `enter`/`leave` events and `scroll` do not bubble.
+    pub const fn bubbles(&self) -> bool {
+        !matches!(
+            self,
+            Self::Focus(FocusEventType::Focus | FocusEventType::Blur)
+                | Self::Mouse(MouseEventType::Mouseenter | MouseEventType::Mouseleave)
+                | Self::Pointer(PointerEventType::Pointerenter | PointerEventType::Pointerleave)
+                | Self::Scroll
+        )
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventType {
+    type Err = ParseEventTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "input" => Ok(Self::Input),
+            "scroll" => Ok(Self::Scroll),
+            "change" => Ok(Self::Change),
+            "wheel" => Ok(Self::Wheel),
+            _ => s
+                .parse()
+                .map(Self::Mouse)
+                .or_else(|_| s.parse().map(Self::Touch))
+                .or_else(|_| s.parse().map(Self::Key))
+                .or_else(|_| s.parse().map(Self::Pointer))
+                .or_else(|_| s.parse().map(Self::Focus))
+                .or_else(|_| s.parse().map(Self::Composition))
+                .or_else(|_| s.parse().map(Self::Clipboard))
+                .or_else(|_| s.parse().map(Self::Drag)),
+        }
+    }
+}
+
+/**
+ * The error of parsing a string that is not the name of a supported event type.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEventTypeError {
+    name: String,
+}
+
+impl ParseEventTypeError {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    /// The string that could not be parsed.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ParseEventTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown event type '{}'", self.name)
+    }
+}
+
+impl core::error::Error for ParseEventTypeError {}
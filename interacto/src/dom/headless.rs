@@ -0,0 +1,404 @@
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use super::{
+    DispatchOutcome, Event, EventDispatcher, EventType, ListenerId, Node, NodeId, NodeTree, Point,
+    Rect,
+};
+use crate::fsm::{
+    CompositeState, ConcurrentFSM, InputState, OutputState, Transition, VisitorFSM, FSM,
+};
+
+/**
+ * A node of a `HeadlessTree`: a widget that only exists in memory.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessNode {
+    id: NodeId,
+    tag: String,
+    attributes: BTreeMap<String, String>,
+    bounds: Rect,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl HeadlessNode {
+    /// The kind of the node (eg `"button"`, `"canvas"`).
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// The attributes of the node, sorted by name.
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        self.attributes.insert(name.to_string(), value.to_string());
+    }
+
+    /// Removes an attribute. Returns its value, if the node had it.
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        self.attributes.remove(name)
+    }
+
+    /// The bounds of the node, in the coordinates of the UI (not relative to the parent).
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+    }
+}
+
+impl Node for HeadlessNode {
+    fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+/**
+ * A tree of nodes that only exists in memory, to run interactions without a browser or a window
+ * (in tests, on servers).
+ * The tree always has a root node. The nodes are identified by the order of their creation.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessTree {
+    nodes: BTreeMap<NodeId, HeadlessNode>,
+    root: NodeId,
+    next_id: u64,
+}
+
+impl HeadlessTree {
+    /// Creates a tree made of a root node.
+    pub fn new(root_tag: &str, bounds: Rect) -> Self {
+        let mut tree = Self {
+            nodes: BTreeMap::new(),
+            root: NodeId(0),
+            next_id: 0,
+        };
+        tree.root = tree.create(root_tag, bounds, None);
+        tree
+    }
+
+    fn create(&mut self, tag: &str, bounds: Rect, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            HeadlessNode {
+                id,
+                tag: tag.to_string(),
+                attributes: BTreeMap::new(),
+                bounds,
+                parent,
+                children: Vec::new(),
+            },
+        );
+        id
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&HeadlessNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut HeadlessNode> {
+        self.nodes.get_mut(&id)
+    }
+
+    /// The number of nodes of the tree, including the root.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Always false: a tree has a root, which cannot be removed.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Creates a node as the last child of the given node.
+    /// Returns `None` if the parent does not belong to the tree.
+    pub fn append_child(&mut self, parent: NodeId, tag: &str, bounds: Rect) -> Option<NodeId> {
+        if !self.nodes.contains_key(&parent) {
+            return None;
+        }
+        let id = self.create(tag, bounds, Some(parent));
+        self.nodes.get_mut(&parent)?.children.push(id);
+        Some(id)
+    }
+
+    /// Removes the given node and its descendants from the tree.
+    /// Returns the identifiers of the removed nodes (empty for the root or an unknown node).
+    pub fn remove(&mut self, id: NodeId) -> Vec<NodeId> {
+        let Some(parent) = self.nodes.get(&id).and_then(|node| node.parent) else {
+            return Vec::new();
+        };
+        if let Some(parent) = self.nodes.get_mut(&parent) {
+            parent.children.retain(|child| *child != id);
+        }
+        let removed = self.descendants(id);
+        for node in &removed {
+            self.nodes.remove(node);
+        }
+        removed
+    }
+
+    /// The given node and its descendants, in document order (depth-first, pre-order).
+    pub fn descendants(&self, id: NodeId) -> Vec<NodeId> {
+        let mut found = Vec::new();
+        let mut to_visit = vec![id];
+        while let Some(current) = to_visit.pop() {
+            if let Some(node) = self.nodes.get(&current) {
+                found.push(current);
+                to_visit.extend(node.children.iter().rev());
+            }
+        }
+        found
+    }
+
+    /// The nodes that match the predicate, in document order.
+    pub fn find_all(&self, predicate: impl Fn(&HeadlessNode) -> bool) -> Vec<NodeId> {
+        self.descendants(self.root)
+            .into_iter()
+            .filter(|id| self.nodes.get(id).is_some_and(&predicate))
+            .collect()
+    }
+
+    /// The nodes that have the given tag, in document order.
+    pub fn find_by_tag(&self, tag: &str) -> Vec<NodeId> {
+        self.find_all(|node| node.tag == tag)
+    }
+
+    /// The first node, in document order, whose attribute has the given value.
+    pub fn find_by_attribute(&self, name: &str, value: &str) -> Option<NodeId> {
+        self.find_all(|node| node.attribute(name) == Some(value))
+            .first()
+            .copied()
+    }
+
+    /// The deepest node whose bounds contain the point. Among siblings, the last ones are on top.
+    /// A node is only hit if its parent is.
+    pub fn hit_test(&self, point: Point) -> Option<NodeId> {
+        let mut hit = self
+            .nodes
+            .get(&self.root)
+            .filter(|root| root.bounds.contains(point))?;
+        while let Some(child) = hit
+            .children
+            .iter()
+            .rev()
+            .filter_map(|child| self.nodes.get(child))
+            .find(|child| child.bounds.contains(point))
+        {
+            hit = child;
+        }
+        Some(hit.id)
+    }
+}
+
+impl NodeTree for HeadlessTree {
+    fn node(&self, id: NodeId) -> Option<&dyn Node> {
+        self.nodes.get(&id).map(|node| node as &dyn Node)
+    }
+}
+
+/**
+ * How an FSM registered to a node of a `HeadlessUI` alters the propagation of the events it processes,
+ * as `Interaction::set_stop_immediate_propagation` and `Interaction::set_prevent_default` do in a browser.
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InteractionOptions {
+    /// Listens to the events during the capture phase instead of the bubble one.
+    pub capture: bool,
+    pub stop_immediate_propagation: bool,
+    pub prevent_default: bool,
+}
+
+/**
+ * A headless tree of nodes with its event dispatcher: tests build a small UI,
+ * register interactions (their FSMs) on its nodes, then fire events at them.
+ * @category Helper
+ */
+pub struct HeadlessUI {
+    pub tree: HeadlessTree,
+    pub dispatcher: EventDispatcher,
+}
+
+impl HeadlessUI {
+    pub fn new(tree: HeadlessTree) -> Self {
+        Self {
+            tree,
+            dispatcher: EventDispatcher::new(),
+        }
+    }
+
+    /// Makes the FSM process the events of the types it accepts dispatched to the given node
+    /// or, through propagation, to its descendants.
+    /// Registering the same FSM on several nodes makes it process the events of all these nodes.
+    /// Returns the identifiers of the created listeners.
+    pub fn register_fsm(
+        &mut self,
+        node: NodeId,
+        fsm: Rc<RefCell<dyn FSM>>,
+        options: InteractionOptions,
+    ) -> Vec<ListenerId> {
+        let process = {
+            let fsm = fsm.clone();
+            move |event: &dyn Event| fsm.borrow_mut().process(event)
+        };
+        self.register(node, &*fsm.borrow(), options, process)
+    }
+
+    /// Hands the events of the types the FSM accepts, dispatched to the given node or to its
+    /// descendants, to `process`: for a binding that owns the FSM of its interaction.
+    /// The listeners of all these event types share `process`, so that it can own the binding
+    /// (or the FSM) by value.
+    /// `process` returns whether the event was processed, which the options then apply to.
+    /// Returns the identifiers of the created listeners.
+    pub fn register(
+        &mut self,
+        node: NodeId,
+        fsm: &dyn FSM,
+        options: InteractionOptions,
+        process: impl FnMut(&dyn Event) -> bool + 'static,
+    ) -> Vec<ListenerId> {
+        let process = Rc::new(RefCell::new(process));
+        accepted_events(fsm)
+            .into_iter()
+            .map(|event_type| {
+                let process = process.clone();
+                self.dispatcher.add_listener(
+                    node,
+                    event_type,
+                    options.capture,
+                    move |event, context| {
+                        if (process.borrow_mut())(event) {
+                            if options.stop_immediate_propagation {
+                                context.stop_immediate_propagation();
+                            }
+                            if options.prevent_default {
+                                context.prevent_default();
+                            }
+                        }
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Dispatches the event to the given node.
+    pub fn fire(&mut self, target: NodeId, event: &dyn Event) -> DispatchOutcome {
+        self.dispatcher.dispatch(&self.tree, target, event)
+    }
+
+    /// Dispatches the event to the node under the point (see `HeadlessTree::hit_test`).
+    /// Returns `None` if no node is under the point.
+    pub fn fire_at(&mut self, point: Point, event: &dyn Event) -> Option<DispatchOutcome> {
+        let target = self.tree.hit_test(point)?;
+        Some(self.fire(target, event))
+    }
+
+    /// Removes the node and its descendants from the tree, with their listeners.
+    pub fn remove(&mut self, node: NodeId) -> Vec<NodeId> {
+        let removed = self.tree.remove(node);
+        for node in &removed {
+            self.dispatcher.remove_listeners_of(*node);
+        }
+        removed
+    }
+}
+
+/// The types of the events that the FSM (including its concurrent and sub-FSMs) may process.
+fn accepted_events(fsm: &dyn FSM) -> Vec<EventType> {
+    let mut collector = AcceptedEvents(Vec::new());
+    fsm.accept_visitor(&mut collector);
+    collector.0
+}
+
+struct AcceptedEvents(Vec<EventType>);
+
+impl AcceptedEvents {
+    fn add(&mut self, transition: &dyn Transition) {
+        for event_type in transition.get_accepted_events() {
+            if !self.0.contains(event_type) {
+                self.0.push(*event_type);
+            }
+        }
+    }
+}
+
+impl VisitorFSM for AcceptedEvents {
+    fn visit_fsm(&mut self, fsm: &dyn FSM) {
+        for state in fsm.states() {
+            for transition in state
+                .as_output()
+                .map(|s| s.transitions())
+                .unwrap_or_default()
+            {
+                transition.accept_visitor(self);
+            }
+        }
+    }
+
+    fn visit_and_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        for conc_fsm in fsm.conc_fsms() {
+            conc_fsm.accept_visitor(self);
+        }
+    }
+
+    fn visit_xor_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        for conc_fsm in fsm.conc_fsms() {
+            conc_fsm.accept_visitor(self);
+        }
+    }
+
+    fn visit_state(&mut self, _state: &dyn OutputState) {}
+
+    fn visit_init_state(&mut self, _state: &dyn OutputState) {}
+
+    fn visit_cancelling_state(&mut self, _state: &dyn InputState) {}
+
+    fn visit_terminal_state(&mut self, _state: &dyn InputState) {}
+
+    fn visit_composite_state(&mut self, _state: &dyn CompositeState) {}
+
+    fn visit_history_state(&mut self, _state: &dyn InputState) {}
+
+    fn visit_transition(&mut self, transition: &dyn Transition) {
+        self.add(transition);
+    }
+
+    fn visit_timeout_transition(&mut self, _transition: &dyn Transition) {}
+
+    fn visit_sub_fsm_transition(&mut self, transition: &dyn Transition) {
+        if let Some(inner) = transition.inner_fsm() {
+            inner.accept_visitor(self);
+        }
+    }
+}
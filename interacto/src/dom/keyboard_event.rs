@@ -0,0 +1,116 @@
+use alloc::string::{String, ToString};
+
+use super::{Event, EventType, KeyEventType, KeyboardEvent, Modifiers, NodeId};
+
+/**
+ * The location of a key on the keyboard, for keys that appear several times (eg Shift).
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KeyLocation {
+    #[default]
+    Standard,
+    Left,
+    Right,
+    Numpad,
+}
+
+/**
+ * A keyboard event, independent of any UI toolkit.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardEventImpl {
+    pub event_type: KeyEventType,
+    pub key: String,
+    pub code: String,
+    pub modifiers: Modifiers,
+    /// True if the key is held down so that the event is repeated.
+    pub repeat: bool,
+    pub location: KeyLocation,
+    /// The time (in ms) at which the event occurred.
+    pub timestamp: u64,
+    /// The node the event is dispatched to, if any.
+    pub target: Option<NodeId>,
+}
+
+impl KeyboardEventImpl {
+    /// Creates a keyboard event of a standard key, without modifier.
+    pub fn new(event_type: KeyEventType, key: &str, code: &str) -> Self {
+        Self {
+            event_type,
+            key: key.to_string(),
+            code: code.to_string(),
+            modifiers: Modifiers::NONE,
+            repeat: false,
+            location: KeyLocation::Standard,
+            timestamp: 0,
+            target: None,
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_location(mut self, location: KeyLocation) -> Self {
+        self.location = location;
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_target(mut self, target: NodeId) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+impl Event for KeyboardEventImpl {
+    fn event_type(&self) -> EventType {
+        EventType::Key(self.event_type)
+    }
+
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn target(&self) -> Option<NodeId> {
+        self.target
+    }
+
+    fn as_keyboard_event(&self) -> Option<&(dyn KeyboardEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl KeyboardEvent for KeyboardEventImpl {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn code(&self) -> &str {
+        &self.code
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    fn repeat(&self) -> bool {
+        self.repeat
+    }
+
+    fn location(&self) -> KeyLocation {
+        self.location
+    }
+}
@@ -1,18 +1,233 @@
+use alloc::vec::Vec;
+use core::any::Any;
+
+mod dispatcher;
 mod event_type;
+mod headless;
+mod keyboard_event;
+mod modifiers;
+mod mouse_event;
+mod point;
+mod rect;
+mod touch;
+mod touch_event;
+mod wheel_event;
 
+pub use dispatcher::*;
 pub use event_type::*;
+pub use headless::*;
+pub use keyboard_event::*;
+pub use modifiers::*;
+pub use mouse_event::*;
+pub use point::*;
+pub use rect::*;
+pub use touch::*;
+pub use touch_event::*;
+pub use wheel_event::*;
+
+/// The identifier of a node of a UI, that events target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u64);
+
+/// A node of a UI (a widget, a DOM element, a shape of a canvas), that events target.
+/// Nodes form a tree, navigated through the identifiers of the parent and children
+/// of each node (see `NodeTree`).
+pub trait Node {
+    /// The identifier of the node, unique in its tree.
+    fn id(&self) -> NodeId;
+
+    /// The identifier of the parent node. `None` for the root of the tree.
+    fn parent(&self) -> Option<NodeId>;
+
+    /// The identifiers of the child nodes, in order.
+    fn children(&self) -> &[NodeId];
+}
+
+/// A tree of nodes, that resolves node identifiers.
+pub trait NodeTree {
+    /// The node that has the given identifier, if it belongs to the tree.
+    fn node(&self, id: NodeId) -> Option<&dyn Node>;
+
+    /// The path of an event dispatched to the given node: the node, then its ancestors
+    /// up to the root. Empty if the node does not belong to the tree.
+    /// The path stops where a parent is unknown or already visited.
+    fn propagation_path(&self, target: NodeId) -> Vec<NodeId> {
+        let mut path = Vec::new();
+        let mut current = self.node(target);
+        while let Some(node) = current {
+            path.push(node.id());
+            current = node
+                .parent()
+                .filter(|parent| !path.contains(parent))
+                .and_then(|parent| self.node(parent));
+        }
+        path
+    }
+
+    /// True if `ancestor` is `node` or one of its ancestors.
+    fn contains(&self, ancestor: NodeId, node: NodeId) -> bool {
+        self.propagation_path(node).contains(&ancestor)
+    }
+}
+
+/// Gives access to a value as `Any`, so that it can be downcast.
+/// Implemented for all the `'static` types.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// An event produced by the UI, that FSMs process.
+/// The provided methods return neutral values: events that carry the data override them.
+pub trait Event: AsAny {
+    /// The type of the event.
+    fn event_type(&self) -> EventType;
+
+    /// The time (in ms) at which the event occurred.
+    fn timestamp(&self) -> u64 {
+        0
+    }
+
+    /// The node the event is dispatched to, if any.
+    fn target(&self) -> Option<NodeId> {
+        None
+    }
+
+    /// This event as a mouse event, if it is one.
+    /// Mouse events must return themselves.
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        None
+    }
+
+    /// This event as a touch event, if it is one.
+    /// Touch events must return themselves.
+    fn as_touch_event(&self) -> Option<&(dyn TouchEvent + 'static)> {
+        None
+    }
+
+    /// This event as a keyboard event, if it is one.
+    /// Keyboard events must return themselves.
+    fn as_keyboard_event(&self) -> Option<&(dyn KeyboardEvent + 'static)> {
+        None
+    }
+
+    /// This event as a wheel event, if it is one.
+    /// Wheel events must return themselves (here and in `as_mouse_event`).
+    fn as_wheel_event(&self) -> Option<&(dyn WheelEvent + 'static)> {
+        None
+    }
+}
+
+impl dyn Event + '_ {
+    /// Returns true if the event is of the concrete type `E`.
+    pub fn is<E: Event + 'static>(&self) -> bool {
+        self.as_any().is::<E>()
+    }
+
+    /// Returns the event as an event of the concrete type `E`, if it is one.
+    pub fn downcast_ref<E: Event + 'static>(&self) -> Option<&E> {
+        self.as_any().downcast_ref::<E>()
+    }
+}
+
+/// An event produced by a keyboard.
+pub trait KeyboardEvent: Event {
+    /// The value of the key, that depends on the keyboard layout and on the modifiers (eg `"a"`, `"A"`, `"Enter"`).
+    fn key(&self) -> &str;
+
+    /// The physical key, independently of the keyboard layout (eg `"KeyA"`, `"Enter"`).
+    fn code(&self) -> &str;
+
+    /// The modifier keys pressed when the event occurred.
+    fn modifiers(&self) -> Modifiers;
+
+    /// True if the key is held down so that the event is repeated.
+    fn repeat(&self) -> bool {
+        false
+    }
+
+    /// The location of the key on the keyboard.
+    fn location(&self) -> KeyLocation {
+        KeyLocation::Standard
+    }
+}
+
+/// An event produced by a pointer: a mouse, a pen or a finger.
+pub trait MouseEvent: Event {
+    /// The button that changed: 0 for the main button, 1 for the auxiliary one, 2 for the secondary one.
+    fn button(&self) -> i16;
+
+    /// The mask of the buttons pressed when the event occurred:
+    /// 1 for the main button, 2 for the secondary one, 4 for the auxiliary one.
+    fn buttons(&self) -> u16 {
+        0
+    }
+
+    /// The position of the pointer.
+    fn position(&self) -> Point {
+        Point::default()
+    }
+
+    /// The modifier keys pressed when the event occurred.
+    fn modifiers(&self) -> Modifiers {
+        Modifiers::NONE
+    }
+
+    /// The identifier of the pointer that produced the event. The mouse has the identifier 1.
+    fn pointer_id(&self) -> i32 {
+        1
+    }
+}
+
+/// An event produced by touch points on a touch surface.
+pub trait TouchEvent: Event {
+    /// The touch points that changed with this event.
+    fn changed_touches(&self) -> &[Touch];
+
+    /// All the touch points in contact with the surface.
+    fn touches(&self) -> &[Touch] {
+        self.changed_touches()
+    }
+
+    /// The modifier keys pressed when the event occurred.
+    fn modifiers(&self) -> Modifiers {
+        Modifiers::NONE
+    }
+}
 
-/// TODO DOM stuff
-pub trait Node {}
+impl dyn TouchEvent + '_ {
+    /// The changed touch point that has the given identifier, if any.
+    pub fn changed_touch(&self, identifier: i32) -> Option<&Touch> {
+        self.changed_touches()
+            .iter()
+            .find(|touch| touch.identifier == identifier)
+    }
+}
 
-/// TODO DOM stuff
-pub trait Event {}
+/// An event produced by a wheel (or a touchpad scroll gesture).
+pub trait WheelEvent: MouseEvent {
+    /// The horizontal scroll amount.
+    fn delta_x(&self) -> f64 {
+        0.0
+    }
 
-/// TODO DOM stuff
-pub trait KeyboardEvent: Event {}
+    /// The vertical scroll amount.
+    fn delta_y(&self) -> f64 {
+        0.0
+    }
 
-/// TODO DOM stuff
-pub trait MouseEvent: Event {}
+    /// The scroll amount on the z-axis.
+    fn delta_z(&self) -> f64 {
+        0.0
+    }
 
-/// TODO DOM stuff
-pub trait WheelEvent: MouseEvent {}
\ No newline at end of file
+    /// The unit of the deltas.
+    fn delta_mode(&self) -> DeltaMode {
+        DeltaMode::Pixel
+    }
+}
@@ -0,0 +1,21 @@
+/**
+ * The modifier keys pressed during an event.
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// No modifier key.
+    pub const NONE: Self = Self {
+        alt: false,
+        ctrl: false,
+        shift: false,
+        meta: false,
+    };
+}
@@ -0,0 +1,127 @@
+use super::{Event, EventType, Modifiers, MouseEvent, MouseEventType, NodeId, Point};
+
+/**
+ * The data of the pointer (mouse, pen, finger) that produced a mouse or wheel event.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerData {
+    /// The position of the pointer.
+    pub position: Point,
+    /// The button that changed (see `MouseEvent::button`).
+    pub button: i16,
+    /// The mask of the pressed buttons (see `MouseEvent::buttons`).
+    pub buttons: u16,
+    pub modifiers: Modifiers,
+    /// The time (in ms) at which the event occurred.
+    pub timestamp: u64,
+    /// The node the event is dispatched to, if any.
+    pub target: Option<NodeId>,
+    /// The identifier of the pointer (see `MouseEvent::pointer_id`).
+    pub pointer_id: i32,
+}
+
+impl PointerData {
+    /// Creates the data of the mouse pointer at the given position,
+    /// for the main button, without pressed buttons nor modifiers.
+    pub fn new(position: Point) -> Self {
+        Self {
+            position,
+            button: 0,
+            buttons: 0,
+            modifiers: Modifiers::NONE,
+            timestamp: 0,
+            target: None,
+            pointer_id: 1,
+        }
+    }
+
+    pub fn with_button(mut self, button: i16) -> Self {
+        self.button = button;
+        self
+    }
+
+    pub fn with_buttons(mut self, buttons: u16) -> Self {
+        self.buttons = buttons;
+        self
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_target(mut self, target: NodeId) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn with_pointer_id(mut self, pointer_id: i32) -> Self {
+        self.pointer_id = pointer_id;
+        self
+    }
+}
+
+/**
+ * A mouse event, independent of any UI toolkit.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseEventImpl {
+    pub event_type: MouseEventType,
+    pub pointer: PointerData,
+}
+
+impl MouseEventImpl {
+    pub fn new(event_type: MouseEventType, pointer: PointerData) -> Self {
+        Self {
+            event_type,
+            pointer,
+        }
+    }
+}
+
+impl Event for MouseEventImpl {
+    fn event_type(&self) -> EventType {
+        EventType::Mouse(self.event_type)
+    }
+
+    fn timestamp(&self) -> u64 {
+        self.pointer.timestamp
+    }
+
+    fn target(&self) -> Option<NodeId> {
+        self.pointer.target
+    }
+
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl MouseEvent for MouseEventImpl {
+    fn button(&self) -> i16 {
+        self.pointer.button
+    }
+
+    fn buttons(&self) -> u16 {
+        self.pointer.buttons
+    }
+
+    fn position(&self) -> Point {
+        self.pointer.position
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.pointer.modifiers
+    }
+
+    fn pointer_id(&self) -> i32 {
+        self.pointer.pointer_id
+    }
+}
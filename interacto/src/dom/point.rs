@@ -0,0 +1,15 @@
+/**
+ * A position in the coordinates of the UI (eg the viewport of a window).
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
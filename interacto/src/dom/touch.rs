@@ -0,0 +1,36 @@
+use super::{NodeId, Point};
+
+/**
+ * A touch point of a touch event.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Touch {
+    /// Identifies the touch point during the whole contact with the surface.
+    pub identifier: i32,
+    /// The position of the touch point.
+    pub position: Point,
+    /// The node on which the touch point started, if any.
+    pub target: Option<NodeId>,
+}
+
+impl Touch {
+    /// Creates a touch point at the origin, without target.
+    pub fn new(identifier: i32) -> Self {
+        Self {
+            identifier,
+            position: Point::default(),
+            target: None,
+        }
+    }
+
+    pub fn with_position(mut self, position: Point) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_target(mut self, target: NodeId) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
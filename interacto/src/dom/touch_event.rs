@@ -0,0 +1,92 @@
+use alloc::vec::Vec;
+
+use super::{Event, EventType, Modifiers, NodeId, Touch, TouchEvent, TouchEventType};
+
+/**
+ * A touch event, independent of any UI toolkit.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchEventImpl {
+    pub event_type: TouchEventType,
+    /// The touch points that changed with this event.
+    pub changed_touches: Vec<Touch>,
+    /// All the touch points in contact with the surface.
+    pub touches: Vec<Touch>,
+    pub modifiers: Modifiers,
+    /// The time (in ms) at which the event occurred.
+    pub timestamp: u64,
+    /// The node the event is dispatched to, if any.
+    pub target: Option<NodeId>,
+}
+
+impl TouchEventImpl {
+    /// Creates a touch event whose touch points in contact with the surface are
+    /// the changed ones (except for `touchend` events, where they left the surface).
+    pub fn new(event_type: TouchEventType, changed_touches: Vec<Touch>) -> Self {
+        let touches = match event_type {
+            TouchEventType::Touchend => Vec::new(),
+            _ => changed_touches.clone(),
+        };
+        Self {
+            event_type,
+            changed_touches,
+            touches,
+            modifiers: Modifiers::NONE,
+            timestamp: 0,
+            target: None,
+        }
+    }
+
+    pub fn with_touches(mut self, touches: Vec<Touch>) -> Self {
+        self.touches = touches;
+        self
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_target(mut self, target: NodeId) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+impl Event for TouchEventImpl {
+    fn event_type(&self) -> EventType {
+        EventType::Touch(self.event_type)
+    }
+
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn target(&self) -> Option<NodeId> {
+        self.target
+    }
+
+    fn as_touch_event(&self) -> Option<&(dyn TouchEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl TouchEvent for TouchEventImpl {
+    fn changed_touches(&self) -> &[Touch] {
+        &self.changed_touches
+    }
+
+    fn touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Event, EventType, Modifiers, MouseEvent, NodeId, Point, PointerData, WheelEvent};
+
+/**
+ * The unit of the deltas of a wheel event.
+ * @category Helper
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DeltaMode {
+    #[default]
+    Pixel,
+    Line,
+    Page,
+}
+
+/**
+ * A wheel event, independent of any UI toolkit.
+ * @category Helper
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct WheelEventImpl {
+    pub pointer: PointerData,
+    pub delta_x: f64,
+    pub delta_y: f64,
+    pub delta_z: f64,
+    pub delta_mode: DeltaMode,
+}
+
+impl WheelEventImpl {
+    /// Creates a wheel event whose deltas are given in pixels.
+    pub fn new(pointer: PointerData, delta_x: f64, delta_y: f64) -> Self {
+        Self {
+            pointer,
+            delta_x,
+            delta_y,
+            delta_z: 0.0,
+            delta_mode: DeltaMode::Pixel,
+        }
+    }
+
+    pub fn with_delta_z(mut self, delta_z: f64) -> Self {
+        self.delta_z = delta_z;
+        self
+    }
+
+    pub fn with_delta_mode(mut self, delta_mode: DeltaMode) -> Self {
+        self.delta_mode = delta_mode;
+        self
+    }
+}
+
+impl Event for WheelEventImpl {
+    fn event_type(&self) -> EventType {
+        EventType::Wheel
+    }
+
+    fn timestamp(&self) -> u64 {
+        self.pointer.timestamp
+    }
+
+    fn target(&self) -> Option<NodeId> {
+        self.pointer.target
+    }
+
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        Some(self)
+    }
+
+    fn as_wheel_event(&self) -> Option<&(dyn WheelEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl MouseEvent for WheelEventImpl {
+    fn button(&self) -> i16 {
+        self.pointer.button
+    }
+
+    fn buttons(&self) -> u16 {
+        self.pointer.buttons
+    }
+
+    fn position(&self) -> Point {
+        self.pointer.position
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.pointer.modifiers
+    }
+
+    fn pointer_id(&self) -> i32 {
+        self.pointer.pointer_id
+    }
+}
+
+impl WheelEvent for WheelEventImpl {
+    fn delta_x(&self) -> f64 {
+        self.delta_x
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.delta_y
+    }
+
+    fn delta_z(&self) -> f64 {
+        self.delta_z
+    }
+
+    fn delta_mode(&self) -> DeltaMode {
+        self.delta_mode
+    }
+}
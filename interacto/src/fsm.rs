@@ -12,10 +12,99 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::collections::HashSet;
-
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+
+use crate::collections::HashSet;
 use crate::dom::{Event, EventType};
-// use super::{FSMHandler, InputState, OutputState, State, VisitorFSM};
+
+/// Identifies a state within the FSM that contains it.
+/// # Category
+/// API FSM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateId(pub(crate) usize);
+
+impl StateId {
+    /// The position of the state in the states of its FSM.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Identifies a transition within the FSM that contains it:
+/// the transitions of an FSM are stored with their source state.
+/// # Category
+/// API FSM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TransitionId {
+    pub(crate) src: StateId,
+    pub(crate) index: usize,
+}
+
+impl TransitionId {
+    /// The source state of the transition.
+    pub fn src(&self) -> StateId {
+        self.src
+    }
+
+    /// The position of the transition in the transitions of its source state.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// The different kinds of states an FSM is made of.
+/// # Category
+/// API FSM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateKind {
+    /// The initial state of the FSM. Can only be left.
+    Init,
+    /// A standard state, that can be entered and left.
+    Std,
+    /// A state that terminates the FSM when entered.
+    Terminal,
+    /// A state that cancels the FSM when entered.
+    Cancelling,
+    /// A state that contains child states (see `CompositeState`).
+    Composite,
+    /// A pseudo-state that enters the child of its composite state that was active
+    /// when the composite state was last left.
+    ShallowHistory,
+    /// A pseudo-state that enters the innermost state that was active
+    /// when its composite state was last left.
+    DeepHistory,
+}
+
+/// An error raised while running an FSM, for example by the guard or the action of a transition.
+/// # Category
+/// API FSM
+pub type FSMError = Box<dyn core::error::Error + Send + Sync>;
+
+/// An `FSMError` shared between the handlers of an FSM, so that they can keep it.
+/// # Category
+/// API FSM
+pub type SharedFSMError = Arc<dyn core::error::Error + Send + Sync>;
+
+/// What an FSM does once one of its transitions failed, after having given the error to `FSM::on_error`.
+/// # Category
+/// API FSM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// The FSM stays in its current state, as if the event had not been processed.
+    #[default]
+    Stay,
+    /// The FSM cancels.
+    Cancel,
+    /// The FSM goes back to its initial state, keeping the events that remain to be processed.
+    /// Its handlers are notified of a cancellation if it had started.
+    Reinit,
+}
 
 /// A finite state machine that defines the behavior of a user interaction.
 /// # Category
@@ -23,13 +112,17 @@ use crate::dom::{Event, EventType};
 pub trait FSM {
     /// The set of the states that compose the FSM.
     /// This returns a copy of the real set.
-    fn states(&self) -> &[&dyn State];
+    fn states(&self) -> Vec<&dyn State>;
 
     /// The current state of the FSM.
     fn current_state(&self) -> &dyn OutputState;
 
-    // /// An observable value for observing the current state of FSM during its execution.
-    // fn current_state_observable(&self) -> broadcast::Receiver<(OutputState, OutputState)>;
+    /// Subscribes to the execution of the FSM: the subscriber receives a trace record
+    /// on each executed transition, and when the FSM starts, terminates or cancels.
+    fn subscribe(&mut self, subscriber: Rc<RefCell<dyn FSMTraceSubscriber>>);
+
+    /// Unsubscribes the given subscriber from the execution of the FSM.
+    fn unsubscribe(&mut self, subscriber: &Rc<RefCell<dyn FSMTraceSubscriber>>);
 
     /// The initial state of the FSM.
     fn init_state(&self) -> &dyn OutputState;
@@ -42,6 +135,10 @@ pub trait FSM {
     /// By default, this attribute is set with the initial state of the FSM.
     fn starting_state(&self) -> &dyn State;
 
+    /// Sets the state of the FSM that must trigger the start event.
+    /// See `starting_state`.
+    fn set_starting_state(&mut self, state: StateId);
+
     /// True: The FSM started.
     fn started(&self) -> bool;
 
@@ -49,48 +146,101 @@ pub trait FSM {
     /// a sub-FSM transition).
     fn is_inner(&self) -> bool;
 
-    // /// The current sub FSM in which this FSM is while running.
-    // fn current_sub_fsm(&self) -> Option<Arc<dyn FSM>>;
+    /// Sets whether the FSM is an inner FSM.
+    fn set_inner(&mut self, inner: bool);
+
+    /// The current sub FSM in which this FSM is while running.
+    fn current_sub_fsm(&self) -> Option<&dyn FSM>;
 
     /// Logs (or not) information about the execution of the FSM.
+    /// When logging with `std`, the FSM prints its trace records on the standard error.
     fn log(&self) -> bool;
 
+    /// Sets whether the FSM logs information about its execution.
+    fn set_log(&mut self, log: bool);
+
+    /// Creates a new instance of this FSM. The instance shares the definition of this FSM
+    /// (ie its states and its transitions) but has its own execution data:
+    /// it is in its initial state and has no handler nor subscriber.
+    fn instance(&self) -> Self
+    where
+        Self: Sized;
+
+    /// Creates a new instance of this FSM (see `instance`), as a trait object.
+    fn boxed_instance(&self) -> Box<dyn FSM>;
+
     /// Processes the provided event to run the FSM.
     /// Returns true if the FSM correctly processed the event.
     fn process(&mut self, event: &dyn Event) -> bool;
 
+    /// Returns true if the FSM would process the provided event in its current state,
+    /// without processing it: a transition accepts the event and its guard holds.
+    /// A guard that fails rejects the event.
+    fn accepts(&self, event: &dyn Event) -> bool;
+
+    /// The types of the events that the FSM can process in its current state,
+    /// regardless of the guards of the transitions.
+    fn accepted_events(&self) -> HashSet<EventType>;
+
     /// Starts the state machine.
-    async fn on_starting(&mut self);
+    /// # Errors
+    /// - `CancelFSMException`: a handler cancelled the FSM, that is already cancelled when this returns.
+    fn on_starting(&mut self) -> Result<(), CancelFSMException>;
 
     /// Updates the state machine.
-    async fn on_updating(&mut self);
+    /// # Errors
+    /// - `CancelFSMException`: a handler cancelled the FSM, that is already cancelled when this returns.
+    fn on_updating(&mut self) -> Result<(), CancelFSMException>;
 
     /// Cancels the state machine.
-    async fn on_cancelling(&mut self);
+    fn on_cancelling(&mut self);
 
     /// Terminates the state machine.
-    async fn on_terminating(&mut self);
+    /// # Errors
+    /// - `CancelFSMException`: a handler cancelled the FSM, that is already cancelled when this returns.
+    fn on_terminating(&mut self) -> Result<(), CancelFSMException>;
+
+    /// Processes an error produced in the FSM: its handlers are notified.
+    fn on_error(&mut self, err: FSMError);
 
-    /// Processes an error produced in the FSM.
-    async fn on_error(&mut self, err: Box<dyn std::error::Error + Send + Sync>);
+    /// What the FSM does once one of its transitions failed.
+    fn error_policy(&self) -> ErrorPolicy;
+
+    /// Sets what the FSM, and the FSMs it contains, do once one of their transitions failed.
+    fn set_error_policy(&mut self, policy: ErrorPolicy);
 
     /// Jobs to do when a timeout transition is executed.
     /// Because the timeout transition is based on a separated thread, the job
     /// done by this method must be executed in the UI thread.
-    /// UI Platforms must override this method to do that.
-    async fn on_timeout(&mut self);
+    /// UI Platforms must override this method to do that, or post timeouts into
+    /// an `FSMEventQueue` drained in the UI thread.
+    fn on_timeout(&mut self);
 
     /// Stops the current timeout transition.
-    async fn stop_current_timeout(&mut self);
+    fn stop_current_timeout(&mut self);
+
+    /// The time (following the clock of the FSM, in ms) at which the current
+    /// timeout transition must be executed, if any.
+    /// UI platforms schedule their timer at this time and then call `check_timeout`.
+    fn next_timeout(&self) -> Option<u64>;
+
+    /// Executes the current timeout transition if its delay elapsed.
+    /// Returns true if the timeout transition has been executed.
+    fn check_timeout(&mut self) -> bool;
 
     /// Enters a standard state.
-    fn enter_std_state(&mut self, state: &dyn InputState);
+    /// # Errors
+    /// - `CancelFSMException`: a handler cancelled the FSM, that is already cancelled when this returns.
+    fn enter_std_state(&mut self, state: StateId) -> Result<(), CancelFSMException>;
 
     /// Adds an FSM handler.
-    fn add_handler(&mut self, handler: Box<dyn FSMHandler>);
+    fn add_handler(&mut self, handler: Rc<RefCell<dyn FSMHandler>>);
 
     /// Removes the given FSM handler from this FSM.
-    fn remove_handler(&mut self, handler: &dyn FSMHandler);
+    fn remove_handler(&mut self, handler: &Rc<RefCell<dyn FSMHandler>>);
+
+    /// Adds an event that will be processed once the FSM terminates its current execution.
+    fn add_remaining_event_to_process(&mut self, event: Box<dyn Event>);
 
     /// Reinitializes the FSM.
     /// Remaining events to process are however not clear.
@@ -108,10 +258,16 @@ pub trait FSM {
     fn uninstall(&mut self);
 
     /// Visiting the FSM.
-    fn accept_visitor(&self, visitor: &dyn VisitorFSM<dyn Event>);
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM);
 }
 
-struct FsmHandle;
+/// An FSM that runs several FSMs concurrently.
+/// # Category
+/// API FSM
+pub trait ConcurrentFSM: FSM {
+    /// The FSMs that run concurrently.
+    fn conc_fsms(&self) -> Vec<&dyn FSM>;
+}
 
 /// The base type of an FSM state.
 /// # category API FSM
@@ -119,14 +275,36 @@ pub trait State {
     /// The name of the state.
     fn name(&self) -> &str;
 
-    /// The FSM that contains the state.
-    fn fsm(&self) -> &FsmHandle;
+    /// The identifier of the state in its FSM.
+    fn id(&self) -> StateId;
+
+    /// The kind of the state, that drives how the FSM enters it.
+    fn kind(&self) -> StateKind;
+
+    /// This state as an output state, if it can be left.
+    fn as_output(&self) -> Option<&dyn OutputState> {
+        None
+    }
+
+    /// This state as a mutable output state, if it can be left.
+    fn as_output_mut(&mut self) -> Option<&mut dyn OutputState> {
+        None
+    }
 
-    /// Checks whether the starting state of the fsm is this state.
-    /// In this case, the fsm is notified about the starting of the FSM.
-    /// # Throws
-    /// - `CancelFSMException`
-    fn check_starting_state(&self) -> Result<(), CancelFSMException>;
+    /// This state as an input state, if it can be entered.
+    fn as_input(&self) -> Option<&dyn InputState> {
+        None
+    }
+
+    /// This state as a composite state, if it contains child states.
+    fn as_composite(&self) -> Option<&dyn CompositeState> {
+        None
+    }
+
+    /// This state as a mutable composite state, if it contains child states.
+    fn as_composite_mut(&mut self) -> Option<&mut dyn CompositeState> {
+        None
+    }
 
     /// Uninstall (ie flushes) the state.
     /// Useful to clear data.
@@ -136,31 +314,108 @@ pub trait State {
     /// Visiting the state.
     /// # Arguments
     /// - `visitor`: The visitor.
-    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM<dyn Event>);
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM);
 }
 
 /// Exception indicating cancellation of FSM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CancelFSMException;
 
 /**
  * The concept of FSM transition.
  * @category API FSM
  */
-pub trait Transition<E: Event> {
+pub trait Transition {
+    /**
+     * The source state of the transition
+     */
+    fn src(&self) -> StateId;
+
     /**
      * The target state of the transition
      */
-    fn target(&self) -> &dyn InputState;
+    fn target(&self) -> StateId;
 
     /**
      * Executes the transition.
      * @param event - The event to process.
-     * @returns The potential output state.
-     * @throws CancelFSMException If the execution cancels the FSM execution.
+     * @returns The target state if the transition has been executed.
+     * @throws The error of the guard or of the action. The transition is then not executed.
+     */
+    fn execute(&self, event: &dyn Event) -> Result<Option<StateId>, FSMError>;
+
+    /**
+     * @returns True if the guard of the transition accepts the given event.
+     * @throws The error of the guard.
+     */
+    fn guard(&self, event: &dyn Event) -> Result<bool, FSMError>;
+
+    /**
+     * @returns A textual description of the guard of the transition,
+     * or None if the transition is not guarded.
+     */
+    fn guard_label(&self) -> Option<&str> {
+        None
+    }
+
+    /**
+     * @returns True if the transition has a guard, that may refuse the events it accepts.
+     */
+    fn is_guarded(&self) -> bool {
+        false
+    }
+
+    /**
+     * @returns True if the transition filters the events of its accepted types
+     * on something else than its guard (eg their concrete type or their mouse button).
+     */
+    fn has_filter(&self) -> bool {
+        false
+    }
+
+    /**
+     * @returns True if the transition is a timeout transition.
+     * Unlike `timeout_delay`, it does not compute the delay of the transition.
+     */
+    fn is_timeout(&self) -> bool {
+        false
+    }
+
+    /**
+     * @returns The delay (in ms) after which the transition is executed, if it is a timeout transition.
+     */
+    fn timeout_delay(&self) -> Option<u64> {
+        None
+    }
+
+    /**
+     * @returns The delay (in ms) of a timeout transition whose delay is fixed.
+     * Unlike `timeout_delay`, it does not compute the delay: it is `None` for a delay
+     * computed from the current data, which only exists once the source state is entered.
+     */
+    fn fixed_timeout_delay(&self) -> Option<u64> {
+        None
+    }
+
+    /**
+     * Executes the transition because its delay elapsed.
+     * Only timeout transitions can be executed this way.
+     * @returns The target state if the transition has been executed.
+     * @throws The error of the action. The transition is then not executed.
      */
-    fn execute(&mut self, event: &dyn Event) -> Result<&dyn InputState,CancelFSMException>;
+    fn execute_on_timeout(&self) -> Result<Option<StateId>, FSMError> {
+        Ok(None)
+    }
 
-    fn guard(&self, event: &dyn Event) -> bool;
+    /**
+     * @returns The FSM entered by the transition, if it is a sub-FSM transition.
+     * Such a transition is not executed through `execute`: the FSM that contains it
+     * runs an instance of the sub-FSM (see `FSM::instance`), that processes the events
+     * until it terminates or cancels.
+     */
+    fn inner_fsm(&self) -> Option<&dyn FSM> {
+        None
+    }
 
     fn accept(&self, event: &dyn Event) -> bool;
 
@@ -168,10 +423,12 @@ pub trait Transition<E: Event> {
      * Visiting the transition.
      * @param visitor - The visitor.
      */
-    fn accept_visitor<EV:Event, V: VisitorFSM<EV>>(&self, visitor: &mut V);
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM);
 
     /**
      * @returns The set of events accepted by the transition.
+     * An FSM only gives a transition the events of these types:
+     * `accept` must return false for the events of the other types.
      */
     fn get_accepted_events(&self) -> &HashSet<EventType>;
 
@@ -181,28 +438,65 @@ pub trait Transition<E: Event> {
     fn uninstall(&mut self);
 }
 
-// Define the OutputState trait extending the State trait
+/// Defines a type of state that can be left through transitions.
+/// # category
+/// API FSM
 pub trait OutputState: State {
+    /// The transitions that start from this state.
+    fn transitions(&self) -> &[Box<dyn Transition>];
 
-    fn transitions(&self) -> &[Box<dyn Transition<dyn Event>>];
+    /// The transitions that start from this state, mutably.
+    fn transitions_mut(&mut self) -> &mut Vec<Box<dyn Transition>>;
 
-    fn exit(&mut self) -> Result<(), CancelFSMException>;
+    /// Adds a transition that starts from this state.
+    fn add_transition(&mut self, tr: Box<dyn Transition>) {
+        self.transitions_mut().push(tr);
+    }
 
-    fn process(&mut self, event: &dyn Event) -> bool;
-
-    fn add_transition(&mut self, tr: Box<dyn Transition<dyn Event>>);
+    /// Executes the first transition of this state that can process the given event.
+    /// Returns the target state of the executed transition, if any.
+    /// # Errors
+    /// The error of the first transition that failed.
+    fn process(&self, event: &dyn Event) -> Result<Option<StateId>, FSMError> {
+        for tr in self.transitions() {
+            if let Some(tgt) = tr.execute(event)? {
+                return Ok(Some(tgt));
+            }
+        }
+        Ok(None)
+    }
 }
 
 /// Defines a type of state that can receive input events.
+/// What entering such a state does depends on its kind (see `State::kind`).
 /// # category
 /// API FSM
-pub trait InputState: State {
-    /// Actions done while entering this state.
-    /// # Errors
-    /// - `CancelFSMException`: If entering the state leads to a cancelling of the FSM execution.
-    fn enter(&self) -> Result<(), CancelFSMException>;
+pub trait InputState: State {}
+
+/// Defines a state that contains child states, as in statecharts.
+/// While the FSM is in one of the children, the transitions of the composite state
+/// can also be executed: they leave the child and the composite state.
+/// Entering a composite state enters its initial child.
+/// # category
+/// API FSM
+pub trait CompositeState: OutputState + InputState {
+    /// The child states, in the order they were added.
+    fn children(&self) -> &[StateId];
+
+    /// The child entered when the composite state is entered, if any.
+    /// A composite state that has no initial child behaves as a standard state.
+    fn initial_child(&self) -> Option<StateId>;
+
+    /// Adds a child state.
+    fn add_child(&mut self, child: StateId);
+
+    /// Sets the child entered when the composite state is entered.
+    fn set_initial_child(&mut self, child: StateId);
 }
 
+/// The observer of the life cycle of an FSM.
+/// # category
+/// API FSM
 pub trait FSMHandler {
     fn fsm_starts(&mut self) -> Result<(), CancelFSMException>;
 
@@ -212,7 +506,13 @@ pub trait FSMHandler {
 
     fn fsm_cancels(&mut self);
 
-    fn fsm_error(&mut self, err: Box<dyn std::error::Error>);
+    fn fsm_error(&mut self, err: &(dyn core::error::Error + Send + Sync));
+
+    /// Receives the error of the FSM as shared by all its handlers, so that it can be kept.
+    /// By default, gives the error to `fsm_error`.
+    fn fsm_shared_error(&mut self, err: &SharedFSMError) {
+        self.fsm_error(err.as_ref());
+    }
 
     fn pre_fsm_start(&mut self);
 
@@ -221,16 +521,120 @@ pub trait FSMHandler {
     fn pre_fsm_stop(&mut self);
 }
 
+/// The asynchronous counterpart of `FSMHandler`: an `FSMDriver` awaits its callbacks
+/// once the FSM processed an event, in the order of the notifications of the FSM.
+/// A callback that returns `CancelFSMException` cancels the FSM.
+/// # category
+/// API FSM
+// The callbacks are awaited by the thread of the FSM, so their futures do not need to be `Send`.
+#[allow(async_fn_in_trait)]
+pub trait AsyncFSMHandler {
+    async fn fsm_starts(&mut self) -> Result<(), CancelFSMException> {
+        Ok(())
+    }
+
+    async fn fsm_updates(&mut self) -> Result<(), CancelFSMException> {
+        Ok(())
+    }
+
+    async fn fsm_stops(&mut self) -> Result<(), CancelFSMException> {
+        Ok(())
+    }
+
+    async fn fsm_cancels(&mut self) {}
+
+    async fn fsm_error(&mut self, _err: &(dyn core::error::Error + Send + Sync)) {}
+}
+
+impl AsyncFSMHandler for () {}
+
+/// A record of the execution of an FSM.
+/// Timestamps are given in ms, following the clock of the FSM.
+/// # Category
+/// API FSM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FSMTrace {
+    /// The FSM started.
+    Start { timestamp: u64 },
+    /// The FSM executed a transition.
+    Transition {
+        previous_state: String,
+        new_state: String,
+        /// The type of the event that triggered the transition.
+        /// None for timeout transitions and for sub-FSM transitions (their events are traced by the sub-FSM).
+        event_type: Option<EventType>,
+        timestamp: u64,
+    },
+    /// The FSM terminated.
+    Terminate { timestamp: u64 },
+    /// The FSM cancelled.
+    Cancel { timestamp: u64 },
+}
+
+impl FSMTrace {
+    /// The time at which the record has been produced.
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Self::Start { timestamp }
+            | Self::Transition { timestamp, .. }
+            | Self::Terminate { timestamp }
+            | Self::Cancel { timestamp } => *timestamp,
+        }
+    }
+}
+
+impl fmt::Display for FSMTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start { timestamp } => write!(f, "[{timestamp} ms] start"),
+            Self::Transition {
+                previous_state,
+                new_state,
+                event_type: Some(event_type),
+                timestamp,
+            } => write!(
+                f,
+                "[{timestamp} ms] {previous_state} -> {new_state} on {event_type:?}"
+            ),
+            Self::Transition {
+                previous_state,
+                new_state,
+                event_type: None,
+                timestamp,
+            } => write!(f, "[{timestamp} ms] {previous_state} -> {new_state}"),
+            Self::Terminate { timestamp } => write!(f, "[{timestamp} ms] terminate"),
+            Self::Cancel { timestamp } => write!(f, "[{timestamp} ms] cancel"),
+        }
+    }
+}
+
+/// A subscriber to the execution of FSMs (see `FSM::subscribe`).
+/// Closures taking an `&FSMTrace` are subscribers.
+/// # category
+/// API FSM
+pub trait FSMTraceSubscriber {
+    fn on_trace(&mut self, trace: &FSMTrace);
+}
+
+impl<F: FnMut(&FSMTrace)> FSMTraceSubscriber for F {
+    fn on_trace(&mut self, trace: &FSMTrace) {
+        self(trace);
+    }
+}
+
 /// The main interface for visiting FSMs.
 /// # category API FSM
-pub trait VisitorFSM<Event> {
-    fn visit_fsm<F:FSM>(&mut self, fsm: &F);
-    // fn visit_and_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM<dyn FSM<Event>>);
-    // fn visit_xor_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM<dyn FSM<Event>>);
+pub trait VisitorFSM {
+    fn visit_fsm(&mut self, fsm: &dyn FSM);
+    fn visit_and_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM);
+    fn visit_xor_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM);
     fn visit_state(&mut self, state: &dyn OutputState);
     fn visit_init_state(&mut self, state: &dyn OutputState);
     fn visit_cancelling_state(&mut self, state: &dyn InputState);
     fn visit_terminal_state(&mut self, state: &dyn InputState);
-    fn visit_transition(&mut self, transition: &dyn Transition<Event>);
-    fn visit_timeout_transition(&mut self, transition: &dyn Transition<Event>);
+    fn visit_composite_state(&mut self, state: &dyn CompositeState);
+    fn visit_history_state(&mut self, state: &dyn InputState);
+    fn visit_transition(&mut self, transition: &dyn Transition);
+    fn visit_timeout_transition(&mut self, transition: &dyn Transition);
+    fn visit_sub_fsm_transition(&mut self, transition: &dyn Transition);
 }
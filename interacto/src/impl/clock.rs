@@ -0,0 +1,93 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::rc::Rc;
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::clock::Clock;
+
+/// The clock of the FSMs built without one: the real time with `std`.
+/// Without `std`, there is no real time to follow: the FSMs get a virtual clock
+/// that nothing moves, until the platform gives them its own clock.
+pub(crate) fn default_clock() -> Rc<dyn Clock> {
+    #[cfg(feature = "std")]
+    return Rc::new(SystemClock::new());
+    #[cfg(not(feature = "std"))]
+    return Rc::new(VirtualClock::new());
+}
+
+/// A clock that follows the real time, from its creation.
+/// # Category
+/// Helper
+#[cfg(feature = "std")]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        self.origin.elapsed().as_millis() as u64
+    }
+}
+
+/// A clock whose time only changes when asked to.
+/// Useful for testing timeout transitions without sleeping.
+/// # Category
+/// Helper
+#[derive(Default)]
+pub struct VirtualClock {
+    now: Cell<u64>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the time forward of the given duration (in ms).
+    /// The time stops at `u64::MAX` instead of overflowing.
+    pub fn advance(&self, duration: u64) {
+        self.now.set(self.now.get().saturating_add(duration));
+    }
+
+    /// Sets the current time (in ms).
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
@@ -1,149 +1,82 @@
-use std::collections::HashSet;
-
-use crate::dom::{Event, EventType, KeyEventType, KeyboardEvent, WheelEvent};
-use crate::fsm::{InputState, OutputState, Transition, VisitorFSM};
-
-pub struct TransitionBase<E: Event> {
-    src: Box<dyn OutputState>,
-    tgt: Box<dyn InputState>,
-    action: Box<dyn Fn(&E)>,
-    guard: Box<dyn Fn(&E) -> bool>,
-    _phantom: std::marker::PhantomData<E>,
-}
-
-impl<E: Event> TransitionBase<E> {
-    fn new(
-        src_state: Box<dyn OutputState>,
-        tgt_state: Box<dyn InputState>,
-        action: Option<Box<dyn Fn(&E)>>,
-        guard: Option<Box<dyn Fn(&E) -> bool>>,
-    ) -> Self {
-        let action_fn = action.unwrap_or_else(|| Box::new(|_: &E| {}));
-        let guard_fn = guard.unwrap_or_else(|| Box::new(|_: &E| true));
-        Self {
-            src: src_state,
-            tgt: tgt_state,
-            action: action_fn,
-            guard: guard_fn,
-            _phantom: std::marker::PhantomData,
-        }
-    }
-}
-
-impl<E: Event> Transition<E> for TransitionBase<E> {
-    fn execute(&self, event: &dyn Event) -> Option<&dyn InputState> {
-        if self.accept(event) && (self.guard)(event) {
-            // Assuming `stop_current_timeout` is a method of `OutputState`
-            self.src.stop_current_timeout();
-            (self.action)(unsafe { &*(event as *const dyn Event as *const E) });
-            self.src.exit();
-            self.tgt.enter();
-            Some(&self.tgt)
-        } else {
-            None
-        }
-    }
-    
-    fn guard(&self, event: &E) -> bool {
-        todo!()
-    }
-
-    fn accept(&self, event: &dyn Event) -> bool {
-        event.is::<E>()
-    }
-
-    fn accept_visitor(&self, visitor: &dyn VisitorFSM<dyn Event>) {
-        visitor.visit_transition(self);
-    }
-
-    fn get_accepted_events(&self) -> HashSet<EventType> {
-        unimplemented!()
-    }
-
-    fn target(&self) -> &dyn InputState {
-        &self.tgt
-    }
-
-    fn uninstall(&self) {}
-}
-
-struct KeyTransition<E> {
-    base: TransitionBase<E>,
-    accepted_events: HashSet<EventType>,
-}
-
-impl<E: KeyboardEvent> KeyTransition<E> {
-    fn new(
-        src_state: Box<dyn OutputState>,
-        tgt_state: Box<dyn InputState>,
-        key_type: KeyEventType,
-        action: Option<fn(&dyn KeyboardEvent)>,
-        guard: Option<fn(&dyn KeyboardEvent) -> bool>,
-    ) -> Self {
-        let mut accepted_events = HashSet::new();
-        accepted_events.insert(EventType::from(key_type));
-        Self {
-            base: TransitionBase::new(src_state, tgt_state, action, guard),
-            accepted_events,
-        }
-    }
-}
-
-impl<E: KeyboardEvent> Transition<E> for KeyTransition<E> {
-    fn accept(&self, event: &dyn KeyboardEvent) -> bool {
-        event.is_key_event() && self.accepted_events.contains(&EventType::from(event.key_type))
-    }
-
-    fn target(&self) -> &dyn InputState {
-        self.base.target()
-    }
-
-    fn execute(&self, event: &dyn Event) -> Option<&dyn InputState> {
-        self.base.execute(event)
-    }
-
-    fn guard(&self, event: &dyn KeyboardEvent) -> bool {
-        self.base.guard(event)
-    }
-
-    fn accept_visitor(&self, visitor: &dyn VisitorFSM<dyn Event>) {
-        self.base.accept_visitor(visitor)
-    }
-
-    fn get_accepted_events(&self) -> &HashSet<EventType> {
-        &self.accepted_events
-    }
-
-    fn uninstall(&self) {
-        self.base.uninstall()
-    }
-}
-
-// Define WheelTransition struct
-struct WheelTransition<E: WheelEvent> {
-    base: TransitionBase<E>,
-
-}
-
-impl<E: WheelEvent> WheelTransition<E> {
-    // Define accepted_events as a static HashSet
-    const ACCEPTED_EVENTS: HashSet<EventType> = {
-        let mut set = HashSet::new();
-        set.insert(EventType::Wheel);
-        set
-    };
-
-    // Define accept method
-    fn accept(&self, event: &dyn Event) -> bool {
-        if let Some(wheel_event) = event.downcast_ref::<WheelEvent>() {
-            self.get_accepted_events().contains(&EventType::Wheel)
-        } else {
-            false
-        }
-    }
-
-    // Define get_accepted_events method
-    fn get_accepted_events(&self) -> &HashSet<EventType> {
-        &Self::ACCEPTED_EVENTS
-    }
-}
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+mod cancelling_state;
+mod change_transition;
+mod composite_state;
+mod concurrent_and_fsm;
+mod concurrent_xor_fsm;
+mod dot_visitor;
+mod filtered_transition;
+mod fsm_coverage;
+mod fsm_definition;
+#[cfg(all(feature = "std", any(test, feature = "async")))]
+mod fsm_driver;
+mod fsm_explorer;
+mod fsm_handlers;
+mod fsm_impl;
+mod fsm_lifecycle;
+mod fsm_labels;
+mod fsm_macro;
+#[cfg(feature = "std")]
+mod fsm_queue;
+mod fsm_recorder;
+mod fsm_tracers;
+mod fsm_validator;
+mod history_state;
+mod init_state;
+mod input_transition;
+mod key_transition;
+mod mermaid_visitor;
+mod mouse_transition;
+mod scroll_transition;
+mod std_state;
+mod sub_fsm_transition;
+mod terminal_state;
+mod timeout_transition;
+mod touch_transition;
+mod transition_base;
+mod typed_transition;
+mod wheel_transition;
+
+pub use cancelling_state::CancellingState;
+pub use change_transition::ChangeTransition;
+pub use composite_state::CompositeStateImpl;
+pub use concurrent_and_fsm::ConcurrentAndFSM;
+pub use concurrent_xor_fsm::ConcurrentXorFSM;
+pub use dot_visitor::DotVisitor;
+pub use fsm_coverage::{CoverageReport, FSMCoverage, UncoveredTransition};
+pub use fsm_definition::{FSMDefinition, StateAction};
+#[cfg(all(feature = "std", any(test, feature = "async")))]
+pub use fsm_driver::{FSMDriver, InteractionOutcome};
+pub use fsm_explorer::FSMExplorer;
+pub use fsm_impl::FSMImpl;
+#[cfg(feature = "std")]
+pub use fsm_queue::{FSMEventQueue, FSMEventSender, FSMMessage, FSMTimer};
+pub use fsm_validator::{FSMDefect, FSMValidator};
+pub use history_state::HistoryState;
+pub use init_state::InitState;
+pub use input_transition::InputTransition;
+pub use key_transition::KeyTransition;
+pub use mermaid_visitor::MermaidVisitor;
+pub use mouse_transition::MouseTransition;
+pub use scroll_transition::ScrollTransition;
+pub use std_state::StdState;
+pub use sub_fsm_transition::SubFSMTransition;
+pub use terminal_state::TerminalState;
+pub use timeout_transition::{TimeoutAction, TimeoutDelay, TimeoutTransition};
+pub use touch_transition::TouchTransition;
+pub use transition_base::{TransitionAction, TransitionBase, TransitionGuard};
+pub use typed_transition::{TypedAction, TypedGuard, TypedTransition};
+pub use wheel_transition::WheelTransition;
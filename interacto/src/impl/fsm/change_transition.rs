@@ -0,0 +1,47 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::collections::HashSet;
+use crate::dom::EventType;
+use crate::fsm::StateId;
+
+use super::filtered_transition::{filtered_transition_impl, FilteredTransition};
+use super::{TransitionAction, TransitionBase, TransitionGuard};
+
+/// A transition on change (eg of the value of an input field) events.
+/// # Category
+/// FSM
+pub struct ChangeTransition(FilteredTransition);
+
+impl ChangeTransition {
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        action: Option<TransitionAction>,
+        guard: Option<TransitionGuard>,
+    ) -> Self {
+        let events = HashSet::from([EventType::Change]);
+        Self(FilteredTransition::new(TransitionBase::new(
+            src_state, tgt_state, events, action, guard,
+        )))
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    pub fn with_guard_label(mut self, label: &str) -> Self {
+        self.0.set_guard_label(label);
+        self
+    }
+}
+
+filtered_transition_impl!(ChangeTransition);
@@ -0,0 +1,120 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::fsm::{
+    CompositeState, InputState, OutputState, State, StateId, StateKind, Transition, VisitorFSM,
+};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// A state that contains child states.
+/// Its transitions can be executed while the FSM is in any of its children.
+/// # Category
+/// FSM
+pub struct CompositeStateImpl {
+    name: String,
+    id: StateId,
+    transitions: Vec<Box<dyn Transition>>,
+    children: Vec<StateId>,
+    initial_child: Option<StateId>,
+}
+
+impl CompositeStateImpl {
+    pub fn new(name: &str, id: StateId) -> Self {
+        Self {
+            name: name.to_string(),
+            id,
+            transitions: Vec::new(),
+            children: Vec::new(),
+            initial_child: None,
+        }
+    }
+}
+
+impl State for CompositeStateImpl {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> StateId {
+        self.id
+    }
+
+    fn kind(&self) -> StateKind {
+        StateKind::Composite
+    }
+
+    fn as_output(&self) -> Option<&dyn OutputState> {
+        Some(self)
+    }
+
+    fn as_output_mut(&mut self) -> Option<&mut dyn OutputState> {
+        Some(self)
+    }
+
+    fn as_input(&self) -> Option<&dyn InputState> {
+        Some(self)
+    }
+
+    fn as_composite(&self) -> Option<&dyn CompositeState> {
+        Some(self)
+    }
+
+    fn as_composite_mut(&mut self) -> Option<&mut dyn CompositeState> {
+        Some(self)
+    }
+
+    fn uninstall(&mut self) {
+        self.transitions.iter_mut().for_each(|tr| tr.uninstall());
+        self.transitions.clear();
+        self.children.clear();
+        self.initial_child = None;
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_composite_state(self);
+    }
+}
+
+impl OutputState for CompositeStateImpl {
+    fn transitions(&self) -> &[Box<dyn Transition>] {
+        &self.transitions
+    }
+
+    fn transitions_mut(&mut self) -> &mut Vec<Box<dyn Transition>> {
+        &mut self.transitions
+    }
+}
+
+impl InputState for CompositeStateImpl {}
+
+impl CompositeState for CompositeStateImpl {
+    fn children(&self) -> &[StateId] {
+        &self.children
+    }
+
+    fn initial_child(&self) -> Option<StateId> {
+        self.initial_child
+    }
+
+    fn add_child(&mut self, child: StateId) {
+        self.children.push(child);
+    }
+
+    fn set_initial_child(&mut self, child: StateId) {
+        self.initial_child = Some(child);
+    }
+}
@@ -0,0 +1,364 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::clock::Clock;
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{
+    CancelFSMException, ConcurrentFSM, ErrorPolicy, FSMError, FSMHandler, FSMTraceSubscriber,
+    OutputState, State, StateId, VisitorFSM, FSM,
+};
+
+use crate::r#impl::clock::default_clock;
+
+use super::fsm_lifecycle::{FSMLifecycle, LifecycleFSM};
+use super::fsm_recorder::{FSMNotification, FSMRecorder};
+use super::InitState;
+
+/// An FSM that runs several FSMs concurrently, for example one FSM per touch point
+/// of a multi-touch interaction.
+/// It starts when one of its FSMs starts, terminates when all its required FSMs terminated,
+/// and cancels as soon as one of its FSMs cancels.
+/// Secondary FSMs process the events too, but their termination is not required.
+/// # Category
+/// FSM
+pub struct ConcurrentAndFSM<F: FSM> {
+    init: InitState,
+    /// The required FSMs, followed by the secondary ones.
+    fsms: Vec<F>,
+    recorders: Vec<Rc<RefCell<FSMRecorder>>>,
+    required: usize,
+    /// Whether each required FSM terminated during the current execution.
+    terminated: Vec<bool>,
+    lifecycle: FSMLifecycle,
+}
+
+impl<F: FSM + 'static> ConcurrentAndFSM<F> {
+    /// Creates a concurrent FSM that terminates once all the given FSMs terminated.
+    pub fn new(fsms: Vec<F>) -> Self {
+        Self::with_secondary(fsms, Vec::new())
+    }
+
+    /// Creates a concurrent FSM whose secondary FSMs do not have to terminate for this FSM to terminate.
+    /// # Panics
+    /// If no required FSM is provided.
+    pub fn with_secondary(fsms: Vec<F>, secondary_fsms: Vec<F>) -> Self {
+        assert!(
+            !fsms.is_empty(),
+            "A concurrent FSM requires at least one FSM"
+        );
+        let required = fsms.len();
+        let mut fsms: Vec<F> = fsms.into_iter().chain(secondary_fsms).collect();
+        let recorders = fsms
+            .iter_mut()
+            .map(|fsm| {
+                let recorder = Rc::new(RefCell::new(FSMRecorder::default()));
+                fsm.set_inner(true);
+                fsm.add_handler(recorder.clone());
+                recorder
+            })
+            .collect();
+        Self {
+            init: InitState::new("init", StateId(0)),
+            fsms,
+            recorders,
+            required,
+            terminated: vec![false; required],
+            lifecycle: FSMLifecycle::new(default_clock()),
+        }
+    }
+
+    /// Sets the clock that timestamps the trace records of this FSM.
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.lifecycle.clock = clock;
+    }
+
+    /// The required FSMs, which all have to terminate for this FSM to terminate.
+    pub fn required_fsms(&self) -> &[F] {
+        &self.fsms[..self.required]
+    }
+
+    /// The secondary FSMs, whose termination is not required.
+    pub fn secondary_fsms(&self) -> &[F] {
+        &self.fsms[self.required..]
+    }
+
+    /// Reacts to what the FSMs notified while processing the last event or timeout.
+    fn check_fsms(&mut self) {
+        for i in 0..self.fsms.len() {
+            let notifications = self.recorders[i].borrow_mut().take();
+            for notification in notifications {
+                let res = match notification {
+                    FSMNotification::Start => {
+                        if i < self.required {
+                            self.terminated[i] = false;
+                        }
+                        if self.lifecycle.started() {
+                            Ok(())
+                        } else {
+                            self.on_starting()
+                        }
+                    }
+                    FSMNotification::Update => self.on_updating(),
+                    FSMNotification::Stop if i < self.required => {
+                        self.terminated[i] = true;
+                        if self.terminated.iter().all(|t| *t) {
+                            self.on_terminating()
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    FSMNotification::Stop => Ok(()),
+                    FSMNotification::Cancel => {
+                        self.on_cancelling();
+                        Err(CancelFSMException)
+                    }
+                    FSMNotification::Error(err) => {
+                        self.lifecycle.error(&err);
+                        Ok(())
+                    }
+                };
+                // The FSMs have been reinitialised: the other notifications are outdated.
+                if res.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn clear_notifications(&self) {
+        self.recorders.iter().for_each(|r| {
+            r.borrow_mut().take();
+        });
+    }
+}
+
+impl<F: FSM + 'static> LifecycleFSM for ConcurrentAndFSM<F> {
+    fn lifecycle(&self) -> &FSMLifecycle {
+        &self.lifecycle
+    }
+
+    fn lifecycle_mut(&mut self) -> &mut FSMLifecycle {
+        &mut self.lifecycle
+    }
+}
+
+impl<F: FSM + 'static> ConcurrentFSM for ConcurrentAndFSM<F> {
+    /// All the FSMs: the required ones, followed by the secondary ones.
+    fn conc_fsms(&self) -> Vec<&dyn FSM> {
+        self.fsms.iter().map(|fsm| fsm as &dyn FSM).collect()
+    }
+}
+
+impl<F: FSM + 'static> FSM for ConcurrentAndFSM<F> {
+    fn states(&self) -> Vec<&dyn State> {
+        vec![&self.init]
+    }
+
+    fn current_state(&self) -> &dyn OutputState {
+        &self.init
+    }
+
+    fn init_state(&self) -> &dyn OutputState {
+        &self.init
+    }
+
+    /// A concurrent FSM starts when one of its FSMs starts.
+    fn starting_state(&self) -> &dyn State {
+        &self.init
+    }
+
+    /// The starting of a concurrent FSM depends on the one of its FSMs.
+    fn set_starting_state(&mut self, _state: StateId) {}
+
+    fn started(&self) -> bool {
+        self.lifecycle.started()
+    }
+
+    fn is_inner(&self) -> bool {
+        self.lifecycle.inner
+    }
+
+    fn set_inner(&mut self, inner: bool) {
+        self.lifecycle.inner = inner;
+    }
+
+    /// The FSMs of a concurrent FSM are not entered through sub-FSM transitions.
+    fn current_sub_fsm(&self) -> Option<&dyn FSM> {
+        None
+    }
+
+    fn log(&self) -> bool {
+        self.lifecycle.log
+    }
+
+    fn set_log(&mut self, log: bool) {
+        self.lifecycle.log = log;
+        self.fsms.iter_mut().for_each(|fsm| fsm.set_log(log));
+    }
+
+    /// The instance runs instances of the FSMs of this FSM,
+    /// and keeps the clock, the logging and the error policy of this FSM.
+    fn instance(&self) -> Self {
+        let mut fsm = Self::with_secondary(
+            self.required_fsms().iter().map(F::instance).collect(),
+            self.secondary_fsms().iter().map(F::instance).collect(),
+        );
+        fsm.lifecycle = self.lifecycle.instance();
+        fsm
+    }
+
+    fn boxed_instance(&self) -> Box<dyn FSM> {
+        Box::new(self.instance())
+    }
+
+    fn process(&mut self, event: &dyn Event) -> bool {
+        let mut processed = false;
+        for fsm in &mut self.fsms {
+            processed |= fsm.process(event);
+        }
+        self.check_fsms();
+        processed
+    }
+
+    fn accepts(&self, event: &dyn Event) -> bool {
+        self.fsms.iter().any(|fsm| fsm.accepts(event))
+    }
+
+    fn accepted_events(&self) -> HashSet<EventType> {
+        self.fsms
+            .iter()
+            .flat_map(|fsm| fsm.accepted_events())
+            .collect()
+    }
+
+    fn on_starting(&mut self) -> Result<(), CancelFSMException> {
+        self.starts()
+    }
+
+    fn on_updating(&mut self) -> Result<(), CancelFSMException> {
+        self.updates()
+    }
+
+    fn on_cancelling(&mut self) {
+        self.cancels();
+    }
+
+    fn on_terminating(&mut self) -> Result<(), CancelFSMException> {
+        self.stops()
+    }
+
+    fn on_error(&mut self, err: FSMError) {
+        self.lifecycle.error(&Arc::from(err));
+    }
+
+    fn error_policy(&self) -> ErrorPolicy {
+        self.lifecycle.error_policy
+    }
+
+    /// The FSMs recover from the errors of their transitions by themselves:
+    /// this FSM only gives them the policy.
+    fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.lifecycle.error_policy = policy;
+        self.fsms
+            .iter_mut()
+            .for_each(|fsm| fsm.set_error_policy(policy));
+    }
+
+    /// Executes the timeout transitions of the FSMs that have the earliest timeout.
+    fn on_timeout(&mut self) {
+        let Some(next) = self.next_timeout() else {
+            return;
+        };
+        self.fsms
+            .iter_mut()
+            .filter(|fsm| fsm.next_timeout() == Some(next))
+            .for_each(|fsm| fsm.on_timeout());
+        self.check_fsms();
+    }
+
+    fn stop_current_timeout(&mut self) {
+        self.fsms
+            .iter_mut()
+            .for_each(|fsm| fsm.stop_current_timeout());
+    }
+
+    fn next_timeout(&self) -> Option<u64> {
+        self.fsms.iter().filter_map(|fsm| fsm.next_timeout()).min()
+    }
+
+    fn check_timeout(&mut self) -> bool {
+        let mut executed = false;
+        for fsm in &mut self.fsms {
+            executed |= fsm.check_timeout();
+        }
+        self.check_fsms();
+        executed
+    }
+
+    /// A concurrent FSM has no standard state of its own.
+    fn enter_std_state(&mut self, _state: StateId) -> Result<(), CancelFSMException> {
+        Ok(())
+    }
+
+    fn add_handler(&mut self, handler: Rc<RefCell<dyn FSMHandler>>) {
+        self.lifecycle.add_handler(handler);
+    }
+
+    fn remove_handler(&mut self, handler: &Rc<RefCell<dyn FSMHandler>>) {
+        self.lifecycle.remove_handler(handler);
+    }
+
+    fn subscribe(&mut self, subscriber: Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.lifecycle.subscribe(subscriber);
+    }
+
+    fn unsubscribe(&mut self, subscriber: &Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.lifecycle.unsubscribe(subscriber);
+    }
+
+    fn add_remaining_event_to_process(&mut self, event: Box<dyn Event>) {
+        self.lifecycle.add_remaining_event(event);
+    }
+
+    fn reinit(&mut self) {
+        self.fsms.iter_mut().for_each(|fsm| fsm.reinit());
+        self.clear_notifications();
+        self.terminated.fill(false);
+        self.lifecycle.reinit();
+    }
+
+    fn full_reinit(&mut self) {
+        self.lifecycle.full_reinit();
+        self.fsms.iter_mut().for_each(|fsm| fsm.full_reinit());
+        self.reinit();
+    }
+
+    fn uninstall(&mut self) {
+        self.full_reinit();
+        self.fsms.iter_mut().for_each(|fsm| fsm.uninstall());
+        self.lifecycle.uninstall();
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_and_concurrent_fsm(self);
+    }
+}
@@ -0,0 +1,353 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::clock::Clock;
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{
+    CancelFSMException, ConcurrentFSM, ErrorPolicy, FSMError, FSMHandler, FSMTraceSubscriber,
+    OutputState, State, StateId, VisitorFSM, FSM,
+};
+
+use crate::r#impl::clock::default_clock;
+
+use super::fsm_lifecycle::{FSMLifecycle, LifecycleFSM};
+use super::fsm_recorder::{FSMNotification, FSMRecorder};
+use super::InitState;
+
+/// An FSM that runs one FSM among several alternative ones, for example
+/// to accept either a mouse click or a touch tap.
+/// The FSMs are given an event in turn until one of them processes it:
+/// this FSM then runs alone until it terminates or cancels.
+/// The other FSMs execute no transition and only notify the errors of their guards.
+/// # Category
+/// FSM
+pub struct ConcurrentXorFSM<F: FSM> {
+    init: InitState,
+    fsms: Vec<F>,
+    recorders: Vec<Rc<RefCell<FSMRecorder>>>,
+    /// The position of the FSM that currently runs, if any.
+    current: Option<usize>,
+    lifecycle: FSMLifecycle,
+}
+
+impl<F: FSM + 'static> ConcurrentXorFSM<F> {
+    /// # Panics
+    /// If no FSM is provided.
+    pub fn new(mut fsms: Vec<F>) -> Self {
+        assert!(
+            !fsms.is_empty(),
+            "A concurrent FSM requires at least one FSM"
+        );
+        let recorders = fsms
+            .iter_mut()
+            .map(|fsm| {
+                let recorder = Rc::new(RefCell::new(FSMRecorder::default()));
+                fsm.set_inner(true);
+                fsm.add_handler(recorder.clone());
+                recorder
+            })
+            .collect();
+        Self {
+            init: InitState::new("init", StateId(0)),
+            fsms,
+            recorders,
+            current: None,
+            lifecycle: FSMLifecycle::new(default_clock()),
+        }
+    }
+
+    /// Sets the clock that timestamps the trace records of this FSM.
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.lifecycle.clock = clock;
+    }
+
+    /// The FSM that currently runs, if any.
+    pub fn current_fsm(&self) -> Option<&F> {
+        self.current.map(|i| &self.fsms[i])
+    }
+
+    /// Gives the event to the FSMs in turn: the first one that processes it becomes the current FSM.
+    /// The FSMs that do not process it execute no transition, but the errors of their guards
+    /// go through their error policy and are forwarded to the handlers of this FSM.
+    fn elect(&mut self, event: &dyn Event) -> bool {
+        for i in 0..self.fsms.len() {
+            if self.fsms[i].process(event) {
+                self.current = Some(i);
+                return true;
+            }
+            for notification in self.recorders[i].borrow_mut().take() {
+                if let FSMNotification::Error(err) = notification {
+                    self.lifecycle.error(&err);
+                }
+            }
+        }
+        false
+    }
+
+    /// Reacts to what the current FSM notified while processing the last event or timeout.
+    fn check_current_fsm(&mut self) {
+        let Some(current) = self.current else {
+            return;
+        };
+        let notifications = self.recorders[current].borrow_mut().take();
+        for notification in notifications {
+            let res = match notification {
+                FSMNotification::Start => self.on_starting(),
+                FSMNotification::Update => self.on_updating(),
+                FSMNotification::Stop => self.on_terminating(),
+                FSMNotification::Cancel => {
+                    self.on_cancelling();
+                    Err(CancelFSMException)
+                }
+                FSMNotification::Error(err) => {
+                    self.lifecycle.error(&err);
+                    Ok(())
+                }
+            };
+            if res.is_err() {
+                return;
+            }
+        }
+        // The current FSM may have gone back to its initial state without notifying
+        // (eg when cancelled before starting): the alternatives are then open again.
+        if let Some(i) = self.current {
+            let fsm = &self.fsms[i];
+            if !fsm.started() && fsm.current_state().id() == fsm.init_state().id() {
+                self.current = None;
+            }
+        }
+    }
+}
+
+impl<F: FSM + 'static> LifecycleFSM for ConcurrentXorFSM<F> {
+    fn lifecycle(&self) -> &FSMLifecycle {
+        &self.lifecycle
+    }
+
+    fn lifecycle_mut(&mut self) -> &mut FSMLifecycle {
+        &mut self.lifecycle
+    }
+}
+
+impl<F: FSM + 'static> ConcurrentFSM for ConcurrentXorFSM<F> {
+    fn conc_fsms(&self) -> Vec<&dyn FSM> {
+        self.fsms.iter().map(|fsm| fsm as &dyn FSM).collect()
+    }
+}
+
+impl<F: FSM + 'static> FSM for ConcurrentXorFSM<F> {
+    fn states(&self) -> Vec<&dyn State> {
+        vec![&self.init]
+    }
+
+    fn current_state(&self) -> &dyn OutputState {
+        &self.init
+    }
+
+    fn init_state(&self) -> &dyn OutputState {
+        &self.init
+    }
+
+    /// A concurrent FSM starts when its current FSM starts.
+    fn starting_state(&self) -> &dyn State {
+        &self.init
+    }
+
+    /// The starting of a concurrent FSM depends on the one of its FSMs.
+    fn set_starting_state(&mut self, _state: StateId) {}
+
+    fn started(&self) -> bool {
+        self.lifecycle.started()
+    }
+
+    fn is_inner(&self) -> bool {
+        self.lifecycle.inner
+    }
+
+    fn set_inner(&mut self, inner: bool) {
+        self.lifecycle.inner = inner;
+    }
+
+    /// The FSMs of a concurrent FSM are not entered through sub-FSM transitions.
+    fn current_sub_fsm(&self) -> Option<&dyn FSM> {
+        None
+    }
+
+    fn log(&self) -> bool {
+        self.lifecycle.log
+    }
+
+    fn set_log(&mut self, log: bool) {
+        self.lifecycle.log = log;
+        self.fsms.iter_mut().for_each(|fsm| fsm.set_log(log));
+    }
+
+    /// The instance runs instances of the FSMs of this FSM,
+    /// and keeps the clock, the logging and the error policy of this FSM.
+    fn instance(&self) -> Self {
+        let mut fsm = Self::new(self.fsms.iter().map(F::instance).collect());
+        fsm.lifecycle = self.lifecycle.instance();
+        fsm
+    }
+
+    fn boxed_instance(&self) -> Box<dyn FSM> {
+        Box::new(self.instance())
+    }
+
+    fn process(&mut self, event: &dyn Event) -> bool {
+        let processed = match self.current {
+            Some(i) => self.fsms[i].process(event),
+            None => self.elect(event),
+        };
+        self.check_current_fsm();
+        processed
+    }
+
+    fn accepts(&self, event: &dyn Event) -> bool {
+        match self.current {
+            Some(i) => self.fsms[i].accepts(event),
+            None => self.fsms.iter().any(|fsm| fsm.accepts(event)),
+        }
+    }
+
+    fn accepted_events(&self) -> HashSet<EventType> {
+        match self.current {
+            Some(i) => self.fsms[i].accepted_events(),
+            None => self
+                .fsms
+                .iter()
+                .flat_map(|fsm| fsm.accepted_events())
+                .collect(),
+        }
+    }
+
+    fn on_starting(&mut self) -> Result<(), CancelFSMException> {
+        self.starts()
+    }
+
+    fn on_updating(&mut self) -> Result<(), CancelFSMException> {
+        self.updates()
+    }
+
+    fn on_cancelling(&mut self) {
+        self.cancels();
+    }
+
+    fn on_terminating(&mut self) -> Result<(), CancelFSMException> {
+        self.stops()
+    }
+
+    fn on_error(&mut self, err: FSMError) {
+        self.lifecycle.error(&Arc::from(err));
+    }
+
+    fn error_policy(&self) -> ErrorPolicy {
+        self.lifecycle.error_policy
+    }
+
+    /// The FSMs recover from the errors of their transitions by themselves:
+    /// this FSM only gives them the policy.
+    fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.lifecycle.error_policy = policy;
+        self.fsms
+            .iter_mut()
+            .for_each(|fsm| fsm.set_error_policy(policy));
+    }
+
+    /// Only the current FSM can have a running timeout transition,
+    /// since the other ones are in their initial state.
+    fn on_timeout(&mut self) {
+        if let Some(i) = self.current {
+            self.fsms[i].on_timeout();
+            self.check_current_fsm();
+        }
+    }
+
+    fn stop_current_timeout(&mut self) {
+        self.fsms
+            .iter_mut()
+            .for_each(|fsm| fsm.stop_current_timeout());
+    }
+
+    fn next_timeout(&self) -> Option<u64> {
+        self.current.and_then(|i| self.fsms[i].next_timeout())
+    }
+
+    fn check_timeout(&mut self) -> bool {
+        let Some(i) = self.current else {
+            return false;
+        };
+        let executed = self.fsms[i].check_timeout();
+        self.check_current_fsm();
+        executed
+    }
+
+    /// A concurrent FSM has no standard state of its own.
+    fn enter_std_state(&mut self, _state: StateId) -> Result<(), CancelFSMException> {
+        Ok(())
+    }
+
+    fn add_handler(&mut self, handler: Rc<RefCell<dyn FSMHandler>>) {
+        self.lifecycle.add_handler(handler);
+    }
+
+    fn remove_handler(&mut self, handler: &Rc<RefCell<dyn FSMHandler>>) {
+        self.lifecycle.remove_handler(handler);
+    }
+
+    fn subscribe(&mut self, subscriber: Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.lifecycle.subscribe(subscriber);
+    }
+
+    fn unsubscribe(&mut self, subscriber: &Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.lifecycle.unsubscribe(subscriber);
+    }
+
+    fn add_remaining_event_to_process(&mut self, event: Box<dyn Event>) {
+        self.lifecycle.add_remaining_event(event);
+    }
+
+    fn reinit(&mut self) {
+        self.fsms.iter_mut().for_each(|fsm| fsm.reinit());
+        self.recorders.iter().for_each(|r| {
+            r.borrow_mut().take();
+        });
+        self.current = None;
+        self.lifecycle.reinit();
+    }
+
+    fn full_reinit(&mut self) {
+        self.lifecycle.full_reinit();
+        self.fsms.iter_mut().for_each(|fsm| fsm.full_reinit());
+        self.reinit();
+    }
+
+    fn uninstall(&mut self) {
+        self.full_reinit();
+        self.fsms.iter_mut().for_each(|fsm| fsm.uninstall());
+        self.lifecycle.uninstall();
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_xor_concurrent_fsm(self);
+    }
+}
@@ -0,0 +1,276 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::collections::HashSet;
+use crate::fsm::{
+    CompositeState, ConcurrentFSM, InputState, OutputState, State, StateId, Transition, VisitorFSM,
+    FSM,
+};
+use crate::r#impl::fsm::fsm_labels::{
+    child_states, timeout_label, top_level_states, transition_label,
+};
+
+/// A visitor that renders an FSM as a Graphviz DOT graph.
+/// Edges are labelled with the events their transitions accept and with their guard labels.
+/// The starting state of each FSM is drawn bold and labelled `starting`.
+/// Sub-FSMs, the FSMs of concurrent FSMs and composite states are rendered as clusters.
+/// A composite state is drawn as a point in its cluster, the edges that start from or lead to it
+/// being clipped at the border of the cluster.
+/// # Category
+/// FSM
+#[derive(Default)]
+pub struct DotVisitor {
+    output: String,
+    depth: usize,
+    fsm_count: usize,
+    frames: Vec<Frame>,
+}
+
+/// The FSM being visited.
+struct Frame {
+    fsm: usize,
+    starting: StateId,
+    /// The composite states visited so far.
+    composites: HashSet<StateId>,
+}
+
+impl DotVisitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The DOT graph of the visited FSM.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str("  ");
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    /// Opens the graph (for the visited FSM) or a cluster (for the nested ones).
+    /// Returns the identifier of the opened FSM.
+    fn open(&mut self, label: Option<&str>) -> usize {
+        let id = self.fsm_count;
+        self.fsm_count += 1;
+        if self.depth == 0 {
+            self.line("digraph fsm {");
+            self.depth += 1;
+            self.line("compound=true;");
+            self.line("rankdir=LR;");
+        } else {
+            self.line(&format!("subgraph cluster_f{id} {{"));
+            self.depth += 1;
+            self.line("style=dashed;");
+        }
+        if let Some(label) = label {
+            self.line(&format!("label=\"{}\";", escape(label)));
+        }
+        id
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn node(&mut self, state: &dyn State, attributes: &str) {
+        let frame = self
+            .frames
+            .last()
+            .expect("states are visited through their FSM");
+        let starting = if frame.starting == state.id() {
+            ", penwidth=3, xlabel=\"starting\""
+        } else {
+            ""
+        };
+        let node = node_id(frame.fsm, state.id());
+        self.line(&format!(
+            "{node} [label=\"{}\", {attributes}{starting}];",
+            escape(state.name())
+        ));
+    }
+
+    fn edge(&mut self, transition: &dyn Transition, label: &str, attributes: &str) {
+        let frame = self
+            .frames
+            .last()
+            .expect("transitions are visited through their FSM");
+        let fsm = frame.fsm;
+        let mut clip = String::new();
+        if frame.composites.contains(&transition.src()) {
+            clip.push_str(&format!(", ltail={}", cluster_id(fsm, transition.src())));
+        }
+        if frame.composites.contains(&transition.target()) {
+            clip.push_str(&format!(", lhead={}", cluster_id(fsm, transition.target())));
+        }
+        let src = node_id(fsm, transition.src());
+        let tgt = node_id(fsm, transition.target());
+        self.line(&format!(
+            "{src} -> {tgt} [label=\"{}\"{attributes}{clip}];",
+            escape(label)
+        ));
+    }
+
+    /// Visits the given state, then its children in the cluster of the state if it is composite.
+    fn visit_nested_state(&mut self, states: &[&dyn State], state: &dyn State) {
+        state.accept_visitor(self);
+        if state.as_composite().is_some() {
+            for child in child_states(states, state) {
+                self.visit_nested_state(states, child);
+            }
+            // Opened by visit_composite_state.
+            self.close();
+        }
+    }
+
+    fn visit_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM, label: &str) {
+        let id = self.open(Some(label));
+        self.line(&format!(
+            "{} [label=\"init\", {INIT_ATTRIBUTES}];",
+            node_id(id, StateId(0))
+        ));
+        for conc_fsm in fsm.conc_fsms() {
+            let conc_id = self.fsm_count;
+            conc_fsm.accept_visitor(self);
+            self.line(&format!(
+                "{} -> {} [lhead=cluster_f{conc_id}, style=dotted];",
+                node_id(id, StateId(0)),
+                node_id(conc_id, StateId(0))
+            ));
+        }
+        self.close();
+    }
+}
+
+const INIT_ATTRIBUTES: &str = "shape=circle, style=filled, fillcolor=black, fontcolor=white";
+
+fn node_id(fsm: usize, state: StateId) -> String {
+    format!("f{fsm}_s{}", state.index())
+}
+
+fn cluster_id(fsm: usize, state: StateId) -> String {
+    format!("cluster_f{fsm}_s{}", state.index())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl VisitorFSM for DotVisitor {
+    fn visit_fsm(&mut self, fsm: &dyn FSM) {
+        let id = self.open(None);
+        self.frames.push(Frame {
+            fsm: id,
+            starting: fsm.starting_state().id(),
+            composites: HashSet::new(),
+        });
+        let states = fsm.states();
+        for state in top_level_states(&states) {
+            self.visit_nested_state(&states, state);
+        }
+        for state in fsm.states() {
+            for transition in state
+                .as_output()
+                .map(|s| s.transitions())
+                .unwrap_or_default()
+            {
+                transition.accept_visitor(self);
+            }
+        }
+        self.frames.pop();
+        self.close();
+    }
+
+    fn visit_and_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        self.visit_concurrent_fsm(fsm, "AND");
+    }
+
+    fn visit_xor_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        self.visit_concurrent_fsm(fsm, "XOR");
+    }
+
+    fn visit_state(&mut self, state: &dyn OutputState) {
+        self.node(state, "shape=circle");
+    }
+
+    fn visit_init_state(&mut self, state: &dyn OutputState) {
+        self.node(state, INIT_ATTRIBUTES);
+    }
+
+    fn visit_cancelling_state(&mut self, state: &dyn InputState) {
+        self.node(state, "shape=doublecircle, color=red");
+    }
+
+    fn visit_terminal_state(&mut self, state: &dyn InputState) {
+        self.node(state, "shape=doublecircle");
+    }
+
+    /// Opens the cluster of the state, closed once its children are visited.
+    fn visit_composite_state(&mut self, state: &dyn CompositeState) {
+        let frame = self
+            .frames
+            .last_mut()
+            .expect("states are visited through their FSM");
+        frame.composites.insert(state.id());
+        let cluster = cluster_id(frame.fsm, state.id());
+        self.line(&format!("subgraph {cluster} {{"));
+        self.depth += 1;
+        self.line(&format!("label=\"{}\";", escape(state.name())));
+        self.node(state, "shape=point");
+    }
+
+    fn visit_history_state(&mut self, state: &dyn InputState) {
+        self.node(state, "shape=circle, style=dashed");
+    }
+
+    fn visit_transition(&mut self, transition: &dyn Transition) {
+        self.edge(transition, &transition_label(transition), "");
+    }
+
+    fn visit_timeout_transition(&mut self, transition: &dyn Transition) {
+        self.edge(transition, &timeout_label(transition), ", style=dashed");
+    }
+
+    fn visit_sub_fsm_transition(&mut self, transition: &dyn Transition) {
+        let Some(inner) = transition.inner_fsm() else {
+            return;
+        };
+        let fsm = self
+            .frames
+            .last()
+            .expect("transitions are visited through their FSM")
+            .fsm;
+        let inner_id = self.fsm_count;
+        inner.accept_visitor(self);
+        let inner_init = node_id(inner_id, StateId(0));
+        self.line(&format!(
+            "{} -> {inner_init} [label=\"{}\", lhead=cluster_f{inner_id}];",
+            node_id(fsm, transition.src()),
+            escape(&transition_label(transition))
+        ));
+        self.line(&format!(
+            "{inner_init} -> {} [ltail=cluster_f{inner_id}, style=dotted];",
+            node_id(fsm, transition.target())
+        ));
+    }
+}
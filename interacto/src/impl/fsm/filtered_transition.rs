@@ -0,0 +1,213 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{FSMError, StateId, Transition, VisitorFSM};
+
+use super::{TransitionAction, TransitionBase, TransitionGuard, TypedAction, TypedGuard};
+
+/// A condition on the events a transition accepts, on top of their event type.
+type EventFilter = Box<dyn Fn(&dyn Event) -> bool>;
+
+/// Gives an event as an event of a family (eg `Event::as_mouse_event`), if it is one.
+pub(crate) type EventCast<T> = fn(&dyn Event) -> Option<&T>;
+
+/// A transition that accepts the events of its event types that pass all its filters.
+/// Filters are described in the guard label of the transition.
+/// The transitions of the different event families wrap this one.
+pub(crate) struct FilteredTransition {
+    base: TransitionBase,
+    filters: Vec<(Option<String>, EventFilter)>,
+    guard_label: Option<String>,
+}
+
+impl FilteredTransition {
+    pub(crate) fn new(base: TransitionBase) -> Self {
+        let guard_label = base.guard_label().map(str::to_string);
+        Self {
+            base,
+            filters: Vec::new(),
+            guard_label,
+        }
+    }
+
+    /// Creates a transition on the events that `cast` recognises.
+    /// The action and the guard are given the events as casted.
+    pub(crate) fn with_family<T: ?Sized + 'static>(
+        src_state: StateId,
+        tgt_state: StateId,
+        accepted_events: HashSet<EventType>,
+        action: Option<TypedAction<T>>,
+        guard: Option<TypedGuard<T>>,
+        cast: EventCast<T>,
+    ) -> Self {
+        let action = action.map(|action| -> TransitionAction {
+            Box::new(move |event| cast(event).map_or(Ok(()), &action))
+        });
+        let guard = guard.map(|guard| -> TransitionGuard {
+            Box::new(move |event| cast(event).map_or(Ok(false), &guard))
+        });
+        let base = TransitionBase::new(src_state, tgt_state, accepted_events, action, guard);
+        let mut transition = Self::new(base);
+        transition
+            .filters
+            .push((None, Box::new(move |event| cast(event).is_some())));
+        transition
+    }
+
+    /// Restricts the accepted events to the ones that `cast` recognises and that pass `filter`.
+    /// `label` describes the filter.
+    pub(crate) fn add_filter<T: ?Sized + 'static>(
+        &mut self,
+        label: String,
+        cast: EventCast<T>,
+        filter: impl Fn(&T) -> bool + 'static,
+    ) {
+        let filter: EventFilter = Box::new(move |event| cast(event).is_some_and(&filter));
+        self.filters.push((Some(label), filter));
+        self.update_guard_label();
+    }
+
+    pub(crate) fn set_guard_label(&mut self, label: &str) {
+        self.base.set_guard_label(label);
+        self.update_guard_label();
+    }
+
+    fn update_guard_label(&mut self) {
+        let labels: Vec<&str> = self
+            .filters
+            .iter()
+            .filter_map(|(label, _)| label.as_deref())
+            .chain(self.base.guard_label())
+            .collect();
+        self.guard_label = (!labels.is_empty()).then(|| labels.join(" && "));
+    }
+
+    fn filter(&self, event: &dyn Event) -> bool {
+        self.filters.iter().all(|(_, filter)| filter(event))
+    }
+}
+
+impl Transition for FilteredTransition {
+    fn src(&self) -> StateId {
+        self.base.src()
+    }
+
+    fn target(&self) -> StateId {
+        self.base.target()
+    }
+
+    fn execute(&self, event: &dyn Event) -> Result<Option<StateId>, FSMError> {
+        if self.filter(event) {
+            self.base.execute(event)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn guard(&self, event: &dyn Event) -> Result<bool, FSMError> {
+        self.base.guard(event)
+    }
+
+    fn guard_label(&self) -> Option<&str> {
+        self.guard_label.as_deref()
+    }
+
+    fn is_guarded(&self) -> bool {
+        self.base.is_guarded()
+    }
+
+    /// The filter of the event family is not counted: it only checks the type of the events.
+    fn has_filter(&self) -> bool {
+        self.filters.iter().any(|(label, _)| label.is_some())
+    }
+
+    fn accept(&self, event: &dyn Event) -> bool {
+        self.base.accept(event) && self.filter(event)
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_transition(self);
+    }
+
+    fn get_accepted_events(&self) -> &HashSet<EventType> {
+        self.base.get_accepted_events()
+    }
+
+    fn uninstall(&mut self) {
+        self.base.uninstall();
+    }
+}
+
+/// Implements `Transition` for a wrapper (ie a tuple struct) of a `FilteredTransition`.
+macro_rules! filtered_transition_impl {
+    ($transition:ty) => {
+        impl $crate::fsm::Transition for $transition {
+            fn src(&self) -> $crate::fsm::StateId {
+                self.0.src()
+            }
+
+            fn target(&self) -> $crate::fsm::StateId {
+                self.0.target()
+            }
+
+            fn execute(
+                &self,
+                event: &dyn $crate::dom::Event,
+            ) -> Result<Option<$crate::fsm::StateId>, $crate::fsm::FSMError> {
+                self.0.execute(event)
+            }
+
+            fn guard(&self, event: &dyn $crate::dom::Event) -> Result<bool, $crate::fsm::FSMError> {
+                self.0.guard(event)
+            }
+
+            fn guard_label(&self) -> Option<&str> {
+                self.0.guard_label()
+            }
+
+            fn is_guarded(&self) -> bool {
+                self.0.is_guarded()
+            }
+
+            fn has_filter(&self) -> bool {
+                self.0.has_filter()
+            }
+
+            fn accept(&self, event: &dyn $crate::dom::Event) -> bool {
+                self.0.accept(event)
+            }
+
+            fn accept_visitor(&self, visitor: &mut dyn $crate::fsm::VisitorFSM) {
+                visitor.visit_transition(self);
+            }
+
+            fn get_accepted_events(&self) -> &$crate::collections::HashSet<$crate::dom::EventType> {
+                self.0.get_accepted_events()
+            }
+
+            fn uninstall(&mut self) {
+                self.0.uninstall();
+            }
+        }
+    };
+}
+
+pub(crate) use filtered_transition_impl;
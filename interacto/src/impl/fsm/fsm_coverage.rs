@@ -0,0 +1,153 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::collections::HashSet;
+use crate::fsm::{StateId, TransitionId};
+use crate::r#impl::fsm::fsm_labels::{timeout_label, transition_label};
+
+use super::FSMDefinition;
+
+/// The states visited and the transitions executed by instrumented FSMs
+/// (see `FSMImpl::instrument`), that must run a same definition.
+/// # Category
+/// FSM
+#[derive(Debug, Default)]
+pub struct FSMCoverage {
+    states: HashSet<StateId>,
+    transitions: HashSet<TransitionId>,
+}
+
+impl FSMCoverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The states entered so far.
+    /// A history state is visited when a transition that targets it is executed.
+    pub fn visited_states(&self) -> &HashSet<StateId> {
+        &self.states
+    }
+
+    /// The transitions executed so far.
+    pub fn executed_transitions(&self) -> &HashSet<TransitionId> {
+        &self.transitions
+    }
+
+    pub(crate) fn visit_state(&mut self, state: StateId) {
+        self.states.insert(state);
+    }
+
+    pub(crate) fn execute_transition(&mut self, transition: TransitionId) {
+        self.transitions.insert(transition);
+    }
+
+    /// Reports the states and the transitions of the given definition that are not covered.
+    pub fn report(&self, definition: &FSMDefinition) -> CoverageReport {
+        let uncovered_states = definition
+            .states()
+            .iter()
+            .filter(|state| !self.states.contains(&state.id()))
+            .map(|state| state.name().to_string())
+            .collect();
+        let mut transition_count = 0;
+        let mut uncovered_transitions = Vec::new();
+        for state in definition.states() {
+            for (id, tr) in definition.transitions_from(state.id()) {
+                transition_count += 1;
+                if !self.transitions.contains(&id) {
+                    uncovered_transitions.push(UncoveredTransition {
+                        src: state.name().to_string(),
+                        tgt: definition.state(tr.target()).name().to_string(),
+                        label: if tr.is_timeout() {
+                            timeout_label(tr)
+                        } else {
+                            transition_label(tr)
+                        },
+                    });
+                }
+            }
+        }
+        CoverageReport {
+            state_count: definition.states().len(),
+            transition_count,
+            uncovered_states,
+            uncovered_transitions,
+        }
+    }
+}
+
+/// A transition that has never been executed, given by the names of its source and target
+/// states, and by the label of its events (as rendered by `DotVisitor`).
+/// # Category
+/// FSM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UncoveredTransition {
+    pub src: String,
+    pub tgt: String,
+    pub label: String,
+}
+
+/// The states and the transitions of an FSM definition not covered by its executions,
+/// as produced by `FSMCoverage::report` or `FSMExplorer::explore`.
+/// # Category
+/// FSM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub state_count: usize,
+    pub transition_count: usize,
+    /// The names of the states never visited, ordered by identifier.
+    pub uncovered_states: Vec<String>,
+    /// The transitions never executed, ordered by source state.
+    pub uncovered_transitions: Vec<UncoveredTransition>,
+}
+
+impl CoverageReport {
+    /// True: all the states have been visited and all the transitions have been executed.
+    pub fn is_complete(&self) -> bool {
+        self.uncovered_states.is_empty() && self.uncovered_transitions.is_empty()
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "states: {}/{} covered",
+            self.state_count - self.uncovered_states.len(),
+            self.state_count
+        )?;
+        writeln!(
+            f,
+            "transitions: {}/{} covered",
+            self.transition_count - self.uncovered_transitions.len(),
+            self.transition_count
+        )?;
+        for state in &self.uncovered_states {
+            writeln!(f, "state '{state}' never visited")?;
+        }
+        for tr in &self.uncovered_transitions {
+            writeln!(
+                f,
+                "transition '{}' -> '{}' ({}) never executed",
+                tr.src, tr.tgt, tr.label
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::collections::HashMap;
+use crate::dom::EventType;
+use crate::fsm::{
+    CompositeState, FSMError, OutputState, State, StateId, StateKind, Transition, TransitionId,
+};
+
+use super::{
+    CancellingState, CompositeStateImpl, HistoryState, InitState, StdState, TerminalState,
+};
+
+/// An action executed when a state is entered or left.
+/// # Category
+/// FSM
+pub type StateAction = Box<dyn Fn() -> Result<(), FSMError>>;
+
+/// The entry and exit actions of a state.
+#[derive(Default)]
+struct StateActions {
+    entry: Option<StateAction>,
+    exit: Option<StateAction>,
+}
+
+/// The identifier of the initial state, that is always the first state of an FSM.
+pub(crate) const INIT_STATE: StateId = StateId(0);
+
+/// The immutable part of an FSM: its states and its transitions, addressed by their identifiers.
+/// A definition is built once, then shared (through an `Arc`) by all the FSMs that run it,
+/// each of them keeping only its own execution data (see `FSMImpl::from_definition`).
+/// A definition is created with its initial state. The other states and the transitions
+/// are then added using `add_std_state`, `add_terminal_state`, `add_cancelling_state`
+/// and `add_transition`.
+/// States can be nested in composite states (see `add_composite_state` and `add_child`),
+/// and can have entry and exit actions.
+/// The definition indexes the transitions of each state by the event types they accept,
+/// so that processing an event only involves the transitions that may accept it.
+/// # Category
+/// FSM
+pub struct FSMDefinition {
+    states: Vec<Box<dyn State>>,
+    /// For each state, the positions of its transitions that accept each event type.
+    dispatch: Vec<HashMap<EventType, Vec<usize>>>,
+    /// For each state, the composite state that contains it, if any.
+    parents: Vec<Option<StateId>>,
+    actions: Vec<StateActions>,
+    starting_state: StateId,
+}
+
+impl FSMDefinition {
+    pub fn new() -> Self {
+        Self {
+            states: vec![Box::new(InitState::new("init", INIT_STATE))],
+            dispatch: vec![HashMap::new()],
+            parents: vec![None],
+            actions: vec![StateActions::default()],
+            starting_state: INIT_STATE,
+        }
+    }
+
+    /// Adds a standard state to the definition.
+    /// If `starting_state` is true, the FSM will start when entering this state.
+    pub fn add_std_state(&mut self, name: &str, starting_state: bool) -> StateId {
+        let id = self.add_state(|id| Box::new(StdState::new(name, id)));
+        if starting_state {
+            self.starting_state = id;
+        }
+        id
+    }
+
+    /// Adds a terminal state to the definition.
+    /// If `starting_state` is true, the FSM will start when entering this state.
+    pub fn add_terminal_state(&mut self, name: &str, starting_state: bool) -> StateId {
+        let id = self.add_state(|id| Box::new(TerminalState::new(name, id)));
+        if starting_state {
+            self.starting_state = id;
+        }
+        id
+    }
+
+    /// Adds a cancelling state to the definition.
+    pub fn add_cancelling_state(&mut self, name: &str) -> StateId {
+        self.add_state(|id| Box::new(CancellingState::new(name, id)))
+    }
+
+    /// Adds a composite state to the definition. Its children are then added using `add_child`.
+    /// If `starting_state` is true, the FSM will start when entering this state.
+    pub fn add_composite_state(&mut self, name: &str, starting_state: bool) -> StateId {
+        let id = self.add_state(|id| Box::new(CompositeStateImpl::new(name, id)));
+        if starting_state {
+            self.starting_state = id;
+        }
+        id
+    }
+
+    /// Adds a shallow history state to the given composite state.
+    /// # Panics
+    /// If `composite` is not a composite state of this definition.
+    pub fn add_shallow_history_state(&mut self, name: &str, composite: StateId) -> StateId {
+        self.add_history_state(name, composite, false)
+    }
+
+    /// Adds a deep history state to the given composite state.
+    /// # Panics
+    /// If `composite` is not a composite state of this definition.
+    pub fn add_deep_history_state(&mut self, name: &str, composite: StateId) -> StateId {
+        self.add_history_state(name, composite, true)
+    }
+
+    fn add_history_state(&mut self, name: &str, composite: StateId, deep: bool) -> StateId {
+        self.assert_composite(composite);
+        let id = self.add_state(|id| Box::new(HistoryState::new(name, id, composite, deep)));
+        self.parents[id.0] = Some(composite);
+        self.composite_mut(composite).add_child(id);
+        id
+    }
+
+    fn add_state(&mut self, create: impl FnOnce(StateId) -> Box<dyn State>) -> StateId {
+        let id = StateId(self.states.len());
+        self.states.push(create(id));
+        self.dispatch.push(HashMap::new());
+        self.parents.push(None);
+        self.actions.push(StateActions::default());
+        id
+    }
+
+    /// The composite state that has the given identifier, if it is one.
+    pub fn composite_state(&self, id: StateId) -> Option<&dyn CompositeState> {
+        self.states.get(id.0).and_then(|s| s.as_composite())
+    }
+
+    fn assert_composite(&self, state: StateId) {
+        assert!(
+            self.composite_state(state).is_some(),
+            "The state must be a composite state of the FSM"
+        );
+    }
+
+    /// # Panics
+    /// If the state is not a composite state of this definition.
+    fn composite_mut(&mut self, state: StateId) -> &mut dyn CompositeState {
+        self.states
+            .get_mut(state.0)
+            .and_then(|s| s.as_composite_mut())
+            .expect("The state must be a composite state of the FSM")
+    }
+
+    /// Nests the given state into the given composite state.
+    /// The first child added to a composite state is its initial child (see `set_initial_child`).
+    /// # Panics
+    /// If `composite` is not a composite state of this definition, if `child` is not a state
+    /// of this definition, if `child` is the initial state or a history state, if `child` is
+    /// already nested, or if `child` contains `composite`.
+    pub fn add_child(&mut self, composite: StateId, child: StateId) {
+        self.assert_composite(composite);
+        let kind = self
+            .states
+            .get(child.0)
+            .expect("The child must be a state of the FSM")
+            .kind();
+        assert!(
+            !matches!(
+                kind,
+                StateKind::Init | StateKind::ShallowHistory | StateKind::DeepHistory
+            ),
+            "The initial state and the history states cannot be added as children"
+        );
+        assert!(
+            self.parents[child.0].is_none(),
+            "A state can only be the child of one composite state"
+        );
+        assert!(
+            child != composite && !self.ancestors(composite).any(|s| s == child),
+            "A composite state cannot contain itself"
+        );
+        self.parents[child.0] = Some(composite);
+        let state = self.composite_mut(composite);
+        if state.initial_child().is_none() {
+            state.set_initial_child(child);
+        }
+        state.add_child(child);
+    }
+
+    /// Sets the child entered when the given composite state is entered.
+    /// # Panics
+    /// If `composite` is not a composite state of this definition,
+    /// or if `child` is not one of its children (history states excluded).
+    pub fn set_initial_child(&mut self, composite: StateId, child: StateId) {
+        let is_history = self.states.get(child.0).is_some_and(|s| {
+            matches!(s.kind(), StateKind::ShallowHistory | StateKind::DeepHistory)
+        });
+        assert!(
+            self.parent(child) == Some(composite) && !is_history,
+            "The initial child of a composite state must be one of its children"
+        );
+        self.composite_mut(composite).set_initial_child(child);
+    }
+
+    /// The composite state that contains the given state, if any.
+    pub fn parent(&self, state: StateId) -> Option<StateId> {
+        self.parents.get(state.0).copied().flatten()
+    }
+
+    /// The composite states that contain the given state, from the innermost one.
+    pub fn ancestors(&self, state: StateId) -> impl Iterator<Item = StateId> + '_ {
+        core::iter::successors(self.parent(state), |s| self.parent(*s))
+    }
+
+    /// The innermost composite state that contains both given states (but is none of them), if any.
+    pub fn common_ancestor(&self, state: StateId, other: StateId) -> Option<StateId> {
+        self.ancestors(state)
+            .find(|ancestor| self.ancestors(other).any(|s| s == *ancestor))
+    }
+
+    /// Sets the action executed when the given state is entered.
+    /// # Panics
+    /// If the state is not a state of this definition.
+    pub fn set_entry_action(&mut self, state: StateId, action: StateAction) {
+        self.actions[state.0].entry = Some(action);
+    }
+
+    /// Sets the action executed when the given state is left.
+    /// Terminal and cancelling states are never left: the FSM is reinitialised instead.
+    /// # Panics
+    /// If the state is not a state of this definition.
+    pub fn set_exit_action(&mut self, state: StateId, action: StateAction) {
+        self.actions[state.0].exit = Some(action);
+    }
+
+    /// Executes the entry action of the given state, if any.
+    pub(crate) fn run_entry_action(&self, state: StateId) -> Result<(), FSMError> {
+        self.actions
+            .get(state.0)
+            .and_then(|actions| actions.entry.as_ref())
+            .map_or(Ok(()), |action| action())
+    }
+
+    /// Executes the exit action of the given state, if any.
+    pub(crate) fn run_exit_action(&self, state: StateId) -> Result<(), FSMError> {
+        self.actions
+            .get(state.0)
+            .and_then(|actions| actions.exit.as_ref())
+            .map_or(Ok(()), |action| action())
+    }
+
+    /// Adds a transition to its source state.
+    /// # Panics
+    /// If the source state of the transition is not an output state of this definition,
+    /// or if its target state is not an input state of this definition.
+    pub fn add_transition(&mut self, tr: Box<dyn Transition>) -> TransitionId {
+        assert!(
+            self.states
+                .get(tr.target().0)
+                .and_then(|s| s.as_input())
+                .is_some(),
+            "The target of a transition must be an input state of the FSM"
+        );
+        let src = tr.src();
+        let state = self
+            .states
+            .get_mut(src.0)
+            .and_then(|s| s.as_output_mut())
+            .expect("The source of a transition must be an output state of the FSM");
+        let index = state.transitions().len();
+        for event_type in tr.get_accepted_events() {
+            self.dispatch[src.0]
+                .entry(*event_type)
+                .or_default()
+                .push(index);
+        }
+        state.add_transition(tr);
+        TransitionId { src, index }
+    }
+
+    /// The states of the definition, ordered by identifier.
+    pub fn states(&self) -> &[Box<dyn State>] {
+        &self.states
+    }
+
+    /// The state that has the given identifier.
+    /// # Panics
+    /// If the state is not a state of this definition.
+    pub fn state(&self, id: StateId) -> &dyn State {
+        self.states[id.0].as_ref()
+    }
+
+    /// The initial state of the definition.
+    pub fn init_state(&self) -> &dyn OutputState {
+        self.output_state(INIT_STATE)
+            .expect("The initial state of an FSM is an output state")
+    }
+
+    /// The state that has the given identifier, if it can be left.
+    pub fn output_state(&self, id: StateId) -> Option<&dyn OutputState> {
+        self.states.get(id.0).and_then(|s| s.as_output())
+    }
+
+    /// The transition that has the given identifier, if any.
+    pub fn transition(&self, id: TransitionId) -> Option<&dyn Transition> {
+        self.output_state(id.src)?
+            .transitions()
+            .get(id.index)
+            .map(|tr| tr.as_ref())
+    }
+
+    /// The transitions that start from the given state.
+    pub fn transitions_from(
+        &self,
+        state: StateId,
+    ) -> impl Iterator<Item = (TransitionId, &dyn Transition)> {
+        self.output_state(state)
+            .map(|s| s.transitions())
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(move |(index, tr)| (TransitionId { src: state, index }, tr.as_ref()))
+    }
+
+    /// The transitions that start from the given state and that accept the given event type
+    /// (see `Transition::get_accepted_events`), in the order they were added.
+    pub fn candidate_transitions(
+        &self,
+        state: StateId,
+        event_type: EventType,
+    ) -> impl Iterator<Item = (TransitionId, &dyn Transition)> {
+        let transitions = self
+            .output_state(state)
+            .map(|s| s.transitions())
+            .unwrap_or_default();
+        self.dispatch
+            .get(state.0)
+            .and_then(|dispatch| dispatch.get(&event_type))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(move |&index| {
+                (
+                    TransitionId { src: state, index },
+                    transitions[index].as_ref(),
+                )
+            })
+    }
+
+    /// The state that makes the FSM start (see `FSM::starting_state`).
+    pub fn starting_state(&self) -> StateId {
+        self.starting_state
+    }
+
+    /// Sets the state that makes the FSM start.
+    /// # Panics
+    /// If the state is not a state of this definition.
+    pub fn set_starting_state(&mut self, state: StateId) {
+        assert!(
+            state.0 < self.states.len(),
+            "The starting state must be a state of the FSM"
+        );
+        self.starting_state = state;
+    }
+
+    /// Uninstalls the states and the transitions of the definition.
+    pub(crate) fn uninstall(&mut self) {
+        self.states.iter_mut().for_each(|s| s.uninstall());
+        self.actions.clear();
+    }
+}
+
+impl Default for FSMDefinition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
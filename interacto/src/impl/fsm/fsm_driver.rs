@@ -0,0 +1,206 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures::future::{self, Either};
+use futures::{Stream, StreamExt};
+
+use crate::clock::Clock;
+use crate::dom::Event;
+use crate::fsm::{AsyncFSMHandler, FSMHandler, FSM};
+use crate::r#impl::fsm::fsm_recorder::{FSMNotification, FSMRecorder};
+
+/// How an interaction run by an `FSMDriver` ended.
+/// # Category
+/// FSM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionOutcome {
+    /// The FSM reached a terminal state.
+    Terminated,
+    /// The FSM cancelled.
+    Cancelled,
+}
+
+/// Runs an FSM on a stream of events, independently of any async executor.
+/// The driver waits for the next event or, if the FSM is in a state that has a timeout
+/// transition, for the first of the next event and of a timer future created by `timer`
+/// for the remaining delay. Once the FSM processed an event or a timeout, the driver awaits
+/// the callbacks of an `AsyncFSMHandler` for each notification of the FSM.
+/// # Category
+/// FSM
+pub struct FSMDriver<F, S, T> {
+    fsm: F,
+    clock: Rc<dyn Clock>,
+    events: S,
+    /// Whether the stream of events ended: it is not polled anymore.
+    events_ended: bool,
+    timer: T,
+    recorder: Rc<RefCell<FSMRecorder>>,
+    /// The notifications of the FSM that were not sent to an async handler yet.
+    pending: VecDeque<FSMNotification>,
+}
+
+impl<F, S, T, Fut> FSMDriver<F, S, T>
+where
+    F: FSM,
+    S: Stream<Item = Box<dyn Event>> + Unpin,
+    T: FnMut(Duration) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    /// Creates a driver of the given FSM. `clock` must be the clock of the FSM, and `timer`
+    /// creates futures that complete once the given delay elapsed following this clock.
+    pub fn new(mut fsm: F, clock: Rc<dyn Clock>, events: S, timer: T) -> Self {
+        let recorder = Rc::new(RefCell::new(FSMRecorder::default()));
+        fsm.add_handler(recorder.clone());
+        Self {
+            fsm,
+            clock,
+            events,
+            events_ended: false,
+            timer,
+            recorder,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// The driven FSM.
+    pub fn fsm(&self) -> &F {
+        &self.fsm
+    }
+
+    /// The driven FSM.
+    pub fn fsm_mut(&mut self) -> &mut F {
+        &mut self.fsm
+    }
+
+    /// Stops driving the FSM and gives it back.
+    pub fn into_fsm(mut self) -> F {
+        let recorder: Rc<RefCell<dyn FSMHandler>> = self.recorder;
+        self.fsm.remove_handler(&recorder);
+        self.fsm
+    }
+
+    /// Runs the FSM until its current (or next) interaction terminates or cancels.
+    /// Returns `None` if the stream of events ends before.
+    pub async fn next_interaction(
+        &mut self,
+        handler: &mut impl AsyncFSMHandler,
+    ) -> Option<InteractionOutcome> {
+        loop {
+            while let Some(notification) = self.pending.pop_front() {
+                if let Some(outcome) = self.notify(handler, notification).await {
+                    return Some(outcome);
+                }
+            }
+            if !self.step().await {
+                return None;
+            }
+            let notifications = self.recorder.borrow_mut().take();
+            self.pending.extend(notifications);
+        }
+    }
+
+    /// Processes the next event, or the current timeout transition once its delay elapsed.
+    /// Returns false if nothing can happen anymore.
+    async fn step(&mut self) -> bool {
+        let timer = self.fsm.next_timeout().map(|deadline| {
+            let delay = Duration::from_millis(deadline.saturating_sub(self.clock.now()));
+            Box::pin((self.timer)(delay))
+        });
+        match (timer, self.events_ended) {
+            (None, true) => return false,
+            (None, false) => match self.events.next().await {
+                Some(event) => {
+                    self.fsm.process(event.as_ref());
+                }
+                None => {
+                    self.events_ended = true;
+                    return false;
+                }
+            },
+            // Only the timeout transitions can still be executed.
+            (Some(timer), true) => {
+                timer.await;
+                self.fsm.check_timeout();
+            }
+            (Some(timer), false) => match future::select(self.events.next(), timer).await {
+                Either::Left((Some(event), _)) => {
+                    self.fsm.process(event.as_ref());
+                }
+                Either::Left((None, timer)) => {
+                    self.events_ended = true;
+                    timer.await;
+                    self.fsm.check_timeout();
+                }
+                Either::Right(_) => {
+                    self.fsm.check_timeout();
+                }
+            },
+        }
+        true
+    }
+
+    /// Awaits the callback of the handler that matches the notification.
+    /// Returns the outcome of the interaction if the notification ends it.
+    async fn notify(
+        &mut self,
+        handler: &mut impl AsyncFSMHandler,
+        notification: FSMNotification,
+    ) -> Option<InteractionOutcome> {
+        let res = match &notification {
+            FSMNotification::Start => handler.fsm_starts().await,
+            FSMNotification::Update => handler.fsm_updates().await,
+            FSMNotification::Stop => handler.fsm_stops().await,
+            FSMNotification::Cancel => {
+                handler.fsm_cancels().await;
+                return Some(InteractionOutcome::Cancelled);
+            }
+            FSMNotification::Error(err) => {
+                handler.fsm_error(err.as_ref()).await;
+                return None;
+            }
+        };
+        match (res, notification) {
+            (Ok(()), FSMNotification::Stop) => Some(InteractionOutcome::Terminated),
+            (Ok(()), _) => None,
+            (Err(_), notification) => {
+                self.cancel_interaction(matches!(notification, FSMNotification::Stop));
+                handler.fsm_cancels().await;
+                Some(InteractionOutcome::Cancelled)
+            }
+        }
+    }
+
+    /// Cancels the current interaction, since a handler cancelled it.
+    /// `ended`: true if the FSM already ended the interaction.
+    fn cancel_interaction(&mut self, mut ended: bool) {
+        // The notifications that follow, until the end of the interaction, are obsolete.
+        while !ended {
+            match self.pending.pop_front() {
+                Some(FSMNotification::Stop | FSMNotification::Cancel) => ended = true,
+                Some(_) => {}
+                None => break,
+            }
+        }
+        if !ended && self.fsm.started() {
+            self.fsm.on_cancelling();
+            self.recorder.borrow_mut().take();
+        }
+    }
+}
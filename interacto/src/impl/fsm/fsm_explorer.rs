@@ -0,0 +1,129 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::collections::HashSet;
+use crate::dom::Event;
+use crate::fsm::{StateId, FSM};
+
+use super::{CoverageReport, FSMCoverage, FSMImpl};
+
+/// A step of an exploration.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// The FSM processes the event of the alphabet at the given position.
+    Event(usize),
+    /// The current timeout transition of the FSM elapses.
+    Timeout,
+}
+
+/// What identifies an FSM during an exploration: two FSMs that have the same configuration
+/// behave the same way.
+#[derive(PartialEq, Eq, Hash)]
+struct Configuration {
+    state: StateId,
+    started: bool,
+    history: Vec<(StateId, StateId)>,
+    /// The current state of the running sub-FSMs (and whether they started), from the outermost one.
+    sub_fsms: Vec<(StateId, bool)>,
+}
+
+impl Configuration {
+    fn of(fsm: &FSMImpl) -> Self {
+        let mut history: Vec<(StateId, StateId)> =
+            fsm.history().iter().map(|(c, s)| (*c, *s)).collect();
+        history.sort();
+        let mut sub_fsms = Vec::new();
+        let mut sub_fsm = fsm.current_sub_fsm();
+        while let Some(inner) = sub_fsm {
+            sub_fsms.push((inner.current_state().id(), inner.started()));
+            sub_fsm = inner.current_sub_fsm();
+        }
+        Self {
+            state: fsm.current_state_id(),
+            started: fsm.started(),
+            history,
+            sub_fsms,
+        }
+    }
+}
+
+/// Explores the configurations an FSM can reach from its initial state, breadth-first,
+/// by processing the events of a finite alphabet and by making its timeout transitions elapse.
+/// The exploration reports the states and the transitions that have never been exercised.
+/// Each configuration is reached by replaying, on a new instance of the FSM (see `FSM::instance`),
+/// the steps that lead to it: the actions and the guards of the FSM must thus not depend
+/// on data shared between its instances.
+/// # Category
+/// FSM
+pub struct FSMExplorer;
+
+impl FSMExplorer {
+    /// Explores the configurations the given FSM reaches in at most `max_depth` steps,
+    /// a step being either the processing of an event of `alphabet`
+    /// or the execution of the current timeout transition.
+    /// The given FSM itself is not modified.
+    pub fn explore(fsm: &FSMImpl, alphabet: &[&dyn Event], max_depth: usize) -> CoverageReport {
+        let coverage = Rc::new(RefCell::new(FSMCoverage::new()));
+        let replay = |steps: &[Step]| {
+            let mut instance = fsm.instance();
+            instance.instrument(coverage.clone());
+            for step in steps {
+                Self::apply(&mut instance, alphabet, *step);
+            }
+            instance
+        };
+        let mut visited: HashSet<Configuration> = HashSet::from([Configuration::of(&replay(&[]))]);
+        let mut frontier: Vec<Vec<Step>> = vec![Vec::new()];
+        for _ in 0..max_depth {
+            let mut next = Vec::new();
+            for steps in &frontier {
+                let candidates = (0..alphabet.len())
+                    .map(Step::Event)
+                    .chain(core::iter::once(Step::Timeout));
+                for step in candidates {
+                    let mut instance = replay(steps);
+                    if matches!(step, Step::Timeout) && instance.next_timeout().is_none() {
+                        continue;
+                    }
+                    Self::apply(&mut instance, alphabet, step);
+                    if visited.insert(Configuration::of(&instance)) {
+                        let mut path = steps.clone();
+                        path.push(step);
+                        next.push(path);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        let report = coverage.borrow().report(fsm.definition());
+        report
+    }
+
+    fn apply(fsm: &mut FSMImpl, alphabet: &[&dyn Event], step: Step) {
+        match step {
+            Step::Event(index) => {
+                fsm.process(alphabet[index]);
+            }
+            Step::Timeout => fsm.on_timeout(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::fsm::{CancelFSMException, FSMHandler, SharedFSMError};
+
+/// The handlers of an FSM, notified about its life cycle.
+#[derive(Default)]
+pub(crate) struct FSMHandlers {
+    handlers: Vec<Rc<RefCell<dyn FSMHandler>>>,
+}
+
+impl FSMHandlers {
+    pub(crate) fn add(&mut self, handler: Rc<RefCell<dyn FSMHandler>>) {
+        self.handlers.push(handler);
+    }
+
+    pub(crate) fn remove(&mut self, handler: &Rc<RefCell<dyn FSMHandler>>) {
+        self.handlers.retain(|h| !Rc::ptr_eq(h, handler));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.handlers.clear();
+    }
+
+    /// Notifies all the handlers, stopping at the first one that cancels the FSM.
+    fn notify(
+        &self,
+        mut notify: impl FnMut(&mut dyn FSMHandler) -> Result<(), CancelFSMException>,
+    ) -> Result<(), CancelFSMException> {
+        // Iterating over a copy, so that handlers cannot interfere with the notification.
+        let handlers = self.handlers.clone();
+        handlers
+            .iter()
+            .try_for_each(|handler| notify(&mut *handler.borrow_mut()))
+    }
+
+    pub(crate) fn starts(&self) -> Result<(), CancelFSMException> {
+        self.notify(|handler| {
+            handler.pre_fsm_start();
+            handler.fsm_starts()
+        })
+    }
+
+    pub(crate) fn updates(&self) -> Result<(), CancelFSMException> {
+        self.notify(|handler| {
+            handler.pre_fsm_update();
+            handler.fsm_updates()
+        })
+    }
+
+    pub(crate) fn stops(&self) -> Result<(), CancelFSMException> {
+        self.notify(|handler| {
+            handler.pre_fsm_stop();
+            handler.fsm_stops()
+        })
+    }
+
+    pub(crate) fn cancels(&self) {
+        let _ = self.notify(|handler| {
+            handler.fsm_cancels();
+            Ok(())
+        });
+    }
+
+    pub(crate) fn error(&self, err: &SharedFSMError) {
+        let _ = self.notify(|handler| {
+            handler.fsm_shared_error(err);
+            Ok(())
+        });
+    }
+}
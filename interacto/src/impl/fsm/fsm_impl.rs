@@ -0,0 +1,811 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::clock::Clock;
+use crate::collections::{HashMap, HashSet};
+use crate::dom::{Event, EventType};
+use crate::fsm::{
+    CancelFSMException, ErrorPolicy, FSMError, FSMHandler, FSMTrace, FSMTraceSubscriber,
+    OutputState, State, StateId, StateKind, Transition, TransitionId, VisitorFSM, FSM,
+};
+
+use crate::r#impl::clock::default_clock;
+
+use super::fsm_definition::INIT_STATE;
+use super::fsm_lifecycle::{FSMLifecycle, LifecycleFSM};
+use super::fsm_recorder::{FSMNotification, FSMRecorder};
+use super::{FSMCoverage, FSMDefinition, StateAction};
+
+/// The timeout transition of the current state, waiting for its delay to elapse.
+struct CurrentTimeout {
+    transition: TransitionId,
+    /// The time (in ms) at which the transition must be executed.
+    deadline: u64,
+}
+
+/// The instance of an inner FSM that runs while the FSM is in a sub-FSM transition.
+struct CurrentSubFSM {
+    /// The sub-FSM transition the FSM entered.
+    transition: TransitionId,
+    fsm: Box<dyn FSM>,
+}
+
+/// What the current state does with an event.
+enum Firing {
+    /// The given transition has been executed towards the given state.
+    Transition(TransitionId, StateId),
+    /// The given sub-FSM transition must be entered.
+    SubFSM(TransitionId),
+}
+
+/// Looks for the first transition of the given state, or else of the composite states
+/// that contain it (from the innermost one), that processes the event,
+/// and executes it if it is not a sub-FSM transition.
+/// Only the transitions that accept the type of the event are considered.
+fn fire(
+    definition: &FSMDefinition,
+    state: StateId,
+    event: &dyn Event,
+) -> Result<Option<Firing>, FSMError> {
+    for src in core::iter::once(state).chain(definition.ancestors(state)) {
+        for (id, tr) in definition.candidate_transitions(src, event.event_type()) {
+            if tr.inner_fsm().is_some() {
+                if tr.accept(event) && tr.guard(event)? {
+                    return Ok(Some(Firing::SubFSM(id)));
+                }
+            } else if let Some(tgt) = tr.execute(event)? {
+                return Ok(Some(Firing::Transition(id, tgt)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// A state change stopped before its end, because an entry or exit action failed
+/// or because the FSM cancelled.
+struct StateChangeAborted;
+
+impl From<CancelFSMException> for StateChangeAborted {
+    fn from(_: CancelFSMException) -> Self {
+        Self
+    }
+}
+
+/// The base implementation of the FSM trait.
+/// An FSM runs an `FSMDefinition` (its states and transitions), that several FSMs can share:
+/// an FSM only owns its execution data, such as its current state and its current timeout.
+/// An FSM is created with its initial state. As long as its definition is not shared,
+/// the other states and the transitions can be added using `add_std_state`, `add_terminal_state`,
+/// `add_cancelling_state` and `add_transition`.
+/// # Hierarchical states
+/// The states of an FSM can be nested in composite states (see `add_composite_state`).
+/// The current state of the FSM is then always a state that contains no other state,
+/// and the transitions of the composite states that contain it can also be executed.
+/// Executing a transition leaves the current state and its composite states up to the innermost
+/// composite state that contains both the source and the target of the transition, executing
+/// their exit actions from the innermost one. It then enters the target state, and its initial
+/// children when it is a composite state, executing their entry actions from the outermost one.
+/// The history of a composite state (see `add_shallow_history_state`) is recorded when it is left,
+/// and is cleared when the FSM is reinitialised.
+/// Only the first timeout transition found from the current state (then its composite states) runs,
+/// and it restarts on each state change.
+/// # Category
+/// FSM
+pub struct FSMImpl {
+    definition: Arc<FSMDefinition>,
+    current_state: StateId,
+    lifecycle: FSMLifecycle,
+    current_timeout: Option<CurrentTimeout>,
+    current_sub_fsm: Option<CurrentSubFSM>,
+    /// For each composite state left since the FSM was reinitialised,
+    /// the innermost state that was active when it was left.
+    history: HashMap<StateId, StateId>,
+    /// Records the states and transitions covered by the FSM, if it is instrumented.
+    coverage: Option<Rc<RefCell<FSMCoverage>>>,
+    /// Observes the current sub-FSM, so that this FSM follows its execution.
+    sub_fsm_recorder: Rc<RefCell<FSMRecorder>>,
+}
+
+impl FSMImpl {
+    pub fn new() -> Self {
+        Self::with_clock(default_clock())
+    }
+
+    /// Creates an FSM whose timeout transitions follow the given clock.
+    // The states and transitions of an FSM are not thread-safe:
+    // its definition can only be shared between the FSMs of a same thread.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn with_clock(clock: Rc<dyn Clock>) -> Self {
+        Self::from_definition(Arc::new(FSMDefinition::new()), clock)
+    }
+
+    /// Creates an FSM that runs the given definition,
+    /// and whose timeout transitions follow the given clock.
+    pub fn from_definition(definition: Arc<FSMDefinition>, clock: Rc<dyn Clock>) -> Self {
+        Self {
+            definition,
+            current_state: INIT_STATE,
+            lifecycle: FSMLifecycle::new(clock),
+            current_timeout: None,
+            current_sub_fsm: None,
+            history: HashMap::new(),
+            coverage: None,
+            sub_fsm_recorder: Rc::new(RefCell::new(FSMRecorder::default())),
+        }
+    }
+
+    /// The definition that this FSM runs.
+    pub fn definition(&self) -> &Arc<FSMDefinition> {
+        &self.definition
+    }
+
+    /// # Panics
+    /// If the definition is shared with other FSMs.
+    fn definition_mut(&mut self) -> &mut FSMDefinition {
+        Arc::get_mut(&mut self.definition)
+            .expect("The definition of an FSM cannot be modified once shared")
+    }
+
+    /// Adds a standard state to the FSM.
+    /// If `starting_state` is true, the FSM will start when entering this state.
+    /// # Panics
+    /// If the definition of the FSM is shared.
+    pub fn add_std_state(&mut self, name: &str, starting_state: bool) -> StateId {
+        self.definition_mut().add_std_state(name, starting_state)
+    }
+
+    /// Adds a terminal state to the FSM.
+    /// If `starting_state` is true, the FSM will start when entering this state.
+    /// # Panics
+    /// If the definition of the FSM is shared.
+    pub fn add_terminal_state(&mut self, name: &str, starting_state: bool) -> StateId {
+        self.definition_mut()
+            .add_terminal_state(name, starting_state)
+    }
+
+    /// Adds a cancelling state to the FSM.
+    /// # Panics
+    /// If the definition of the FSM is shared.
+    pub fn add_cancelling_state(&mut self, name: &str) -> StateId {
+        self.definition_mut().add_cancelling_state(name)
+    }
+
+    /// Adds a transition to its source state.
+    /// # Panics
+    /// If the definition of the FSM is shared, if the source state of the transition is not
+    /// an output state of this FSM, or if its target state is not an input state of this FSM.
+    pub fn add_transition(&mut self, tr: Box<dyn Transition>) -> TransitionId {
+        self.definition_mut().add_transition(tr)
+    }
+
+    /// Adds a composite state to the FSM.
+    /// If `starting_state` is true, the FSM will start when entering this state.
+    /// # Panics
+    /// If the definition of the FSM is shared.
+    pub fn add_composite_state(&mut self, name: &str, starting_state: bool) -> StateId {
+        self.definition_mut()
+            .add_composite_state(name, starting_state)
+    }
+
+    /// Nests the given state into the given composite state (see `FSMDefinition::add_child`).
+    /// # Panics
+    /// If the definition of the FSM is shared, or if the state cannot be nested.
+    pub fn add_child(&mut self, composite: StateId, child: StateId) {
+        self.definition_mut().add_child(composite, child);
+    }
+
+    /// Sets the child entered when the given composite state is entered.
+    /// # Panics
+    /// If the definition of the FSM is shared, or if `child` is not a child of `composite`.
+    pub fn set_initial_child(&mut self, composite: StateId, child: StateId) {
+        self.definition_mut().set_initial_child(composite, child);
+    }
+
+    /// Adds a shallow history state to the given composite state.
+    /// # Panics
+    /// If the definition of the FSM is shared, or if `composite` is not a composite state.
+    pub fn add_shallow_history_state(&mut self, name: &str, composite: StateId) -> StateId {
+        self.definition_mut()
+            .add_shallow_history_state(name, composite)
+    }
+
+    /// Adds a deep history state to the given composite state.
+    /// # Panics
+    /// If the definition of the FSM is shared, or if `composite` is not a composite state.
+    pub fn add_deep_history_state(&mut self, name: &str, composite: StateId) -> StateId {
+        self.definition_mut()
+            .add_deep_history_state(name, composite)
+    }
+
+    /// Sets the action executed when the given state is entered.
+    /// # Panics
+    /// If the definition of the FSM is shared, or if the state is not a state of this FSM.
+    pub fn set_entry_action(&mut self, state: StateId, action: StateAction) {
+        self.definition_mut().set_entry_action(state, action);
+    }
+
+    /// Sets the action executed when the given state is left.
+    /// # Panics
+    /// If the definition of the FSM is shared, or if the state is not a state of this FSM.
+    pub fn set_exit_action(&mut self, state: StateId, action: StateAction) {
+        self.definition_mut().set_exit_action(state, action);
+    }
+
+    /// Instruments the FSM (and its future instances, see `FSM::instance`): the states it enters
+    /// and the transitions it executes are recorded in the given coverage.
+    /// The initial state is considered as visited.
+    /// Sub-FSMs are not instrumented: the coverage only concerns the definition of this FSM.
+    pub fn instrument(&mut self, coverage: Rc<RefCell<FSMCoverage>>) {
+        coverage.borrow_mut().visit_state(INIT_STATE);
+        self.coverage = Some(coverage);
+    }
+
+    /// The history of the composite states left since the FSM was reinitialised.
+    pub(crate) fn history(&self) -> &HashMap<StateId, StateId> {
+        &self.history
+    }
+
+    /// The state of this FSM that has the given identifier.
+    pub fn state(&self, id: StateId) -> &dyn State {
+        self.definition.state(id)
+    }
+
+    /// The identifier of the current state of the FSM.
+    pub fn current_state_id(&self) -> StateId {
+        self.current_state
+    }
+
+    /// The number of events waiting for the end of the current execution of the FSM.
+    pub fn remaining_events_count(&self) -> usize {
+        self.lifecycle.remaining_events_count()
+    }
+
+    /// Checks whether the given state is the starting state of the FSM.
+    /// In this case, the FSM is notified about its starting.
+    fn check_starting_state(&mut self, state: StateId) -> Result<(), CancelFSMException> {
+        if !self.lifecycle.started() && self.definition.starting_state() == state {
+            self.on_starting()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn exit_state(&mut self, state: StateId) -> Result<(), CancelFSMException> {
+        // Leaving the initial state starts the FSM by default.
+        if self.definition.state(state).kind() == StateKind::Init {
+            self.check_starting_state(state)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The state the FSM ends in when entering the given state: the initial children of
+    /// a composite state are entered, and history states are replaced by the states they restore.
+    fn resolve_target(&self, state: StateId) -> StateId {
+        let definition = &self.definition;
+        match definition.state(state).kind() {
+            StateKind::Composite => definition
+                .composite_state(state)
+                .and_then(|composite| composite.initial_child())
+                .map_or(state, |child| self.resolve_target(child)),
+            StateKind::ShallowHistory | StateKind::DeepHistory => {
+                let Some(composite) = definition.parent(state) else {
+                    return state;
+                };
+                let Some(&last) = self.history.get(&composite) else {
+                    return self.resolve_target(composite);
+                };
+                if definition.state(state).kind() == StateKind::DeepHistory {
+                    return last;
+                }
+                // The child of the composite state that contains the last active state.
+                let child = core::iter::once(last)
+                    .chain(definition.ancestors(last))
+                    .find(|s| definition.parent(*s) == Some(composite))
+                    .unwrap_or(composite);
+                self.resolve_target(child)
+            }
+            _ => state,
+        }
+    }
+
+    /// Leaves the current state and the composite states that contain it, up to the given one
+    /// (excluded), executing their exit actions and recording their history.
+    fn exit_states(&mut self, until: Option<StateId>) -> Result<(), StateChangeAborted> {
+        let leaf = self.current_state;
+        self.exit_state(leaf)?;
+        let exited: Vec<StateId> = core::iter::once(leaf)
+            .chain(self.definition.ancestors(leaf))
+            .take_while(|s| Some(*s) != until)
+            .collect();
+        for state in exited {
+            if let Err(err) = self.definition.run_exit_action(state) {
+                self.fail(err);
+                return Err(StateChangeAborted);
+            }
+            if self.definition.state(state).kind() == StateKind::Composite {
+                self.history.insert(state, leaf);
+            }
+        }
+        Ok(())
+    }
+
+    /// Enters the given state and the composite states that contain it, from the given one
+    /// (excluded), executing their entry actions.
+    /// With the `Stay` error policy, a failing entry action does not stop the state change
+    /// since the FSM already left its previous state.
+    fn enter_states(
+        &mut self,
+        from: Option<StateId>,
+        leaf: StateId,
+    ) -> Result<(), StateChangeAborted> {
+        let mut entered: Vec<StateId> = core::iter::once(leaf)
+            .chain(self.definition.ancestors(leaf))
+            .take_while(|s| Some(*s) != from)
+            .collect();
+        entered.reverse();
+        for state in entered {
+            if let Some(coverage) = &self.coverage {
+                coverage.borrow_mut().visit_state(state);
+            }
+            if let Err(err) = self.definition.run_entry_action(state) {
+                self.fail(err);
+                if self.lifecycle.error_policy != ErrorPolicy::Stay {
+                    return Err(StateChangeAborted);
+                }
+            }
+            if self.definition.state(state).kind() != StateKind::Cancelling {
+                self.check_starting_state(state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Terminates, cancels or settles the FSM in the given state, once entered.
+    fn reach_state(&mut self, leaf: StateId) -> Result<(), StateChangeAborted> {
+        match self.definition.state(leaf).kind() {
+            StateKind::Terminal => self.on_terminating()?,
+            StateKind::Cancelling => self.on_cancelling(),
+            StateKind::Std | StateKind::Composite => self.enter_std_state(leaf)?,
+            StateKind::Init | StateKind::ShallowHistory | StateKind::DeepHistory => {
+                unreachable!("The FSM cannot end in the initial state or in a history state")
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the FSM from its current state to the target of the given transition, that starts
+    /// from the current state or from a composite state that contains it.
+    /// The transition is traced and covered once its exit and entry actions succeeded.
+    fn change_state(
+        &mut self,
+        transition: TransitionId,
+        tgt: StateId,
+        event_type: Option<EventType>,
+    ) -> Result<(), StateChangeAborted> {
+        let src = self.current_state;
+        let leaf = self.resolve_target(tgt);
+        self.stop_current_timeout();
+        let until = self.definition.common_ancestor(transition.src, tgt);
+        self.exit_states(until)?;
+        self.enter_states(until, leaf)?;
+        if let Some(coverage) = &self.coverage {
+            let mut coverage = coverage.borrow_mut();
+            coverage.execute_transition(transition);
+            coverage.visit_state(tgt);
+        }
+        self.trace_transition(src, leaf, event_type);
+        self.reach_state(leaf)
+    }
+
+    fn trace_transition(&self, src: StateId, tgt: StateId, event_type: Option<EventType>) {
+        self.lifecycle.trace(|timestamp| FSMTrace::Transition {
+            previous_state: self.definition.state(src).name().to_string(),
+            new_state: self.definition.state(tgt).name().to_string(),
+            event_type,
+            timestamp,
+        });
+    }
+
+    fn execute_transition(
+        &mut self,
+        transition: TransitionId,
+        tgt: StateId,
+        event_type: Option<EventType>,
+    ) {
+        // A cancellation or a failure has already been processed when it is reported here.
+        let _ = self.change_state(transition, tgt, event_type);
+    }
+
+    /// Starts the timeout transition of the current state, or else of the composite states
+    /// that contain it, if any.
+    fn check_timeout_transition(&mut self) {
+        let now = self.lifecycle.clock.now();
+        let definition = &self.definition;
+        self.current_timeout = core::iter::once(self.current_state)
+            .chain(definition.ancestors(self.current_state))
+            .flat_map(|state| definition.transitions_from(state))
+            .find_map(|(transition, tr)| {
+                tr.timeout_delay().map(|delay| CurrentTimeout {
+                    transition,
+                    deadline: now.saturating_add(delay),
+                })
+            });
+    }
+
+    fn sub_fsm_mut(&mut self) -> Option<&mut dyn FSM> {
+        Some(self.current_sub_fsm.as_mut()?.fsm.as_mut())
+    }
+
+    /// Enters the given sub-FSM transition: an instance of its sub-FSM processes
+    /// the event and the next ones until it terminates or cancels.
+    /// Returns false if the sub-FSM did not process the event: this FSM is then left as is.
+    fn enter_sub_fsm(&mut self, transition: TransitionId, event: &dyn Event) -> bool {
+        let Some(mut fsm) = self
+            .definition
+            .transition(transition)
+            .and_then(|tr| tr.inner_fsm())
+            .map(|fsm| fsm.boxed_instance())
+        else {
+            return false;
+        };
+        fsm.set_inner(true);
+        fsm.set_error_policy(self.lifecycle.error_policy);
+        fsm.add_handler(self.sub_fsm_recorder.clone());
+        if !fsm.process(event) {
+            // The sub-FSM may have notified an error.
+            self.check_sub_fsm();
+            return false;
+        }
+        self.stop_current_timeout();
+        self.current_sub_fsm = Some(CurrentSubFSM { transition, fsm });
+        self.check_sub_fsm();
+        true
+    }
+
+    /// Leaves the current sub-FSM, whose instance is dropped.
+    /// Returns the sub-FSM transition.
+    fn leave_sub_fsm(&mut self) -> Option<TransitionId> {
+        let sub_fsm = self.current_sub_fsm.take()?;
+        self.sub_fsm_recorder.borrow_mut().take();
+        Some(sub_fsm.transition)
+    }
+
+    /// Follows what the current sub-FSM notified while processing the last event or timeout.
+    /// The start of the sub-FSM makes this FSM leave the source state of the sub-FSM transition,
+    /// and its termination makes this FSM enter the target state of the transition.
+    fn check_sub_fsm(&mut self) {
+        let notifications = self.sub_fsm_recorder.borrow_mut().take();
+        for notification in notifications {
+            let res = match notification {
+                FSMNotification::Start => self.exit_state(self.current_state),
+                FSMNotification::Update => self.on_updating(),
+                FSMNotification::Stop => {
+                    let transition = self
+                        .leave_sub_fsm()
+                        .and_then(|id| Some((id, self.definition.transition(id)?.target())));
+                    match transition {
+                        Some((transition, tgt)) => self
+                            .change_state(transition, tgt, None)
+                            .map_err(|_| CancelFSMException),
+                        None => Ok(()),
+                    }
+                }
+                FSMNotification::Cancel => {
+                    self.leave_sub_fsm();
+                    self.on_cancelling();
+                    Err(CancelFSMException)
+                }
+                FSMNotification::Error(err) => {
+                    self.lifecycle.error(&err);
+                    Ok(())
+                }
+            };
+            if res.is_err() {
+                return;
+            }
+        }
+        // The sub-FSM may have gone back to its initial state without notifying
+        // (eg when cancelled before starting): this FSM then stays in the source state.
+        if let Some(fsm) = self.current_sub_fsm() {
+            if !fsm.started() && fsm.current_state().id() == fsm.init_state().id() {
+                self.leave_sub_fsm();
+            }
+        }
+    }
+
+    /// Gives the error of a transition to `on_error`, then recovers from it following the error policy.
+    /// The errors of a sub-FSM are recovered by the sub-FSM itself.
+    fn fail(&mut self, err: FSMError) {
+        self.on_error(err);
+        match self.lifecycle.error_policy {
+            ErrorPolicy::Stay => {}
+            ErrorPolicy::Cancel => self.on_cancelling(),
+            ErrorPolicy::Reinit => {
+                // Unlike a cancellation, the remaining events are kept.
+                self.lifecycle.cancel();
+                self.reinit();
+            }
+        }
+    }
+}
+
+impl LifecycleFSM for FSMImpl {
+    fn lifecycle(&self) -> &FSMLifecycle {
+        &self.lifecycle
+    }
+
+    fn lifecycle_mut(&mut self) -> &mut FSMLifecycle {
+        &mut self.lifecycle
+    }
+}
+
+impl Default for FSMImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FSM for FSMImpl {
+    fn states(&self) -> Vec<&dyn State> {
+        self.definition
+            .states()
+            .iter()
+            .map(|s| s.as_ref())
+            .collect()
+    }
+
+    fn current_state(&self) -> &dyn OutputState {
+        self.definition
+            .output_state(self.current_state)
+            .expect("The current state of an FSM is an output state")
+    }
+
+    fn init_state(&self) -> &dyn OutputState {
+        self.definition.init_state()
+    }
+
+    fn starting_state(&self) -> &dyn State {
+        self.definition.state(self.definition.starting_state())
+    }
+
+    /// # Panics
+    /// If the state is not a state of this FSM, or if the definition of the FSM is shared.
+    fn set_starting_state(&mut self, state: StateId) {
+        self.definition_mut().set_starting_state(state);
+    }
+
+    fn started(&self) -> bool {
+        self.lifecycle.started()
+    }
+
+    fn is_inner(&self) -> bool {
+        self.lifecycle.inner
+    }
+
+    fn set_inner(&mut self, inner: bool) {
+        self.lifecycle.inner = inner;
+    }
+
+    fn current_sub_fsm(&self) -> Option<&dyn FSM> {
+        self.current_sub_fsm
+            .as_ref()
+            .map(|sub_fsm| sub_fsm.fsm.as_ref())
+    }
+
+    fn log(&self) -> bool {
+        self.lifecycle.log
+    }
+
+    fn set_log(&mut self, log: bool) {
+        self.lifecycle.log = log;
+    }
+
+    /// The instance keeps the clock, the logging, the error policy and the coverage of this FSM.
+    fn instance(&self) -> Self {
+        let mut fsm = Self::from_definition(self.definition.clone(), self.lifecycle.clock.clone());
+        fsm.lifecycle = self.lifecycle.instance();
+        fsm.coverage = self.coverage.clone();
+        fsm
+    }
+
+    fn boxed_instance(&self) -> Box<dyn FSM> {
+        Box::new(self.instance())
+    }
+
+    fn process(&mut self, event: &dyn Event) -> bool {
+        // An elapsed timeout occurred before the event.
+        self.check_timeout();
+        if let Some(fsm) = self.sub_fsm_mut() {
+            let processed = fsm.process(event);
+            self.check_sub_fsm();
+            return processed;
+        }
+        match fire(&self.definition, self.current_state, event) {
+            Ok(Some(Firing::Transition(transition, tgt))) => {
+                self.execute_transition(transition, tgt, Some(event.event_type()))
+            }
+            Ok(Some(Firing::SubFSM(transition))) => return self.enter_sub_fsm(transition, event),
+            Ok(None) => return false,
+            Err(err) => {
+                self.fail(err);
+                return false;
+            }
+        }
+        true
+    }
+
+    fn accepts(&self, event: &dyn Event) -> bool {
+        if let Some(fsm) = self.current_sub_fsm() {
+            return fsm.accepts(event);
+        }
+        core::iter::once(self.current_state)
+            .chain(self.definition.ancestors(self.current_state))
+            .any(|src| {
+                self.definition
+                    .candidate_transitions(src, event.event_type())
+                    .any(|(_, tr)| tr.accept(event) && tr.guard(event).unwrap_or(false))
+            })
+    }
+
+    fn accepted_events(&self) -> HashSet<EventType> {
+        if let Some(fsm) = self.current_sub_fsm() {
+            return fsm.accepted_events();
+        }
+        core::iter::once(self.current_state)
+            .chain(self.definition.ancestors(self.current_state))
+            .flat_map(|src| self.definition.transitions_from(src))
+            .flat_map(|(_, tr)| tr.get_accepted_events().iter().copied())
+            .collect()
+    }
+
+    fn on_starting(&mut self) -> Result<(), CancelFSMException> {
+        self.starts()
+    }
+
+    fn on_updating(&mut self) -> Result<(), CancelFSMException> {
+        self.updates()
+    }
+
+    fn on_cancelling(&mut self) {
+        self.cancels();
+    }
+
+    fn on_terminating(&mut self) -> Result<(), CancelFSMException> {
+        self.stops()
+    }
+
+    fn on_error(&mut self, err: FSMError) {
+        self.lifecycle.error(&Arc::from(err));
+    }
+
+    fn error_policy(&self) -> ErrorPolicy {
+        self.lifecycle.error_policy
+    }
+
+    fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.lifecycle.error_policy = policy;
+        if let Some(fsm) = self.sub_fsm_mut() {
+            fsm.set_error_policy(policy);
+        }
+    }
+
+    fn on_timeout(&mut self) {
+        if let Some(fsm) = self.sub_fsm_mut() {
+            fsm.on_timeout();
+            self.check_sub_fsm();
+            return;
+        }
+        let Some(timeout) = self.current_timeout.take() else {
+            return;
+        };
+        let tgt = self
+            .definition
+            .transition(timeout.transition)
+            .map_or(Ok(None), |tr| tr.execute_on_timeout());
+        match tgt {
+            Ok(Some(tgt)) => self.execute_transition(timeout.transition, tgt, None),
+            Ok(None) => {}
+            // The failed timeout transition is not executed again.
+            Err(err) => self.fail(err),
+        }
+    }
+
+    fn stop_current_timeout(&mut self) {
+        self.current_timeout = None;
+    }
+
+    fn next_timeout(&self) -> Option<u64> {
+        if let Some(fsm) = self.current_sub_fsm() {
+            return fsm.next_timeout();
+        }
+        self.current_timeout
+            .as_ref()
+            .map(|timeout| timeout.deadline)
+    }
+
+    fn check_timeout(&mut self) -> bool {
+        if let Some(fsm) = self.sub_fsm_mut() {
+            let executed = fsm.check_timeout();
+            self.check_sub_fsm();
+            return executed;
+        }
+        match self.next_timeout() {
+            Some(deadline) if deadline <= self.lifecycle.clock.now() => {
+                self.on_timeout();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn enter_std_state(&mut self, state: StateId) -> Result<(), CancelFSMException> {
+        self.current_state = state;
+        self.check_timeout_transition();
+        self.on_updating()
+    }
+
+    fn add_handler(&mut self, handler: Rc<RefCell<dyn FSMHandler>>) {
+        self.lifecycle.add_handler(handler);
+    }
+
+    fn remove_handler(&mut self, handler: &Rc<RefCell<dyn FSMHandler>>) {
+        self.lifecycle.remove_handler(handler);
+    }
+
+    fn subscribe(&mut self, subscriber: Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.lifecycle.subscribe(subscriber);
+    }
+
+    fn unsubscribe(&mut self, subscriber: &Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.lifecycle.unsubscribe(subscriber);
+    }
+
+    fn add_remaining_event_to_process(&mut self, event: Box<dyn Event>) {
+        self.lifecycle.add_remaining_event(event);
+    }
+
+    fn reinit(&mut self) {
+        self.leave_sub_fsm();
+        self.stop_current_timeout();
+        self.history.clear();
+        self.lifecycle.reinit();
+        self.current_state = INIT_STATE;
+    }
+
+    fn full_reinit(&mut self) {
+        self.lifecycle.full_reinit();
+        self.reinit();
+    }
+
+    /// The definition of the FSM is uninstalled only if it is not shared with other FSMs.
+    fn uninstall(&mut self) {
+        self.full_reinit();
+        if let Some(definition) = Arc::get_mut(&mut self.definition) {
+            definition.uninstall();
+        }
+        self.lifecycle.uninstall();
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_fsm(self);
+    }
+}
@@ -0,0 +1,76 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::collections::HashSet;
+use crate::fsm::{State, StateId, Transition};
+
+/// Describes a transition for rendering: its accepted events (sorted, so that
+/// the rendering is stable) followed by its guard label, if any.
+pub(crate) fn transition_label(tr: &dyn Transition) -> String {
+    let mut events: Vec<String> = tr
+        .get_accepted_events()
+        .iter()
+        .map(|evt| evt.to_string())
+        .collect();
+    events.sort();
+    with_guard(events.join(", "), tr)
+}
+
+/// Describes a timeout transition for rendering: its delay, if fixed, followed by its guard
+/// label, if any. A computed delay is not rendered, since it depends on the current data.
+pub(crate) fn timeout_label(tr: &dyn Transition) -> String {
+    let label = match tr.fixed_timeout_delay() {
+        Some(delay) => format!("timeout ({delay} ms)"),
+        None => "timeout".to_string(),
+    };
+    with_guard(label, tr)
+}
+
+fn with_guard(label: String, tr: &dyn Transition) -> String {
+    match tr.guard_label() {
+        Some(guard) if label.is_empty() => format!("[{guard}]"),
+        Some(guard) => format!("{label} [{guard}]"),
+        None => label,
+    }
+}
+
+/// The states that are not nested in a composite state, for rendering the nesting of states.
+pub(crate) fn top_level_states<'a>(states: &[&'a dyn State]) -> Vec<&'a dyn State> {
+    let nested: HashSet<StateId> = states
+        .iter()
+        .filter_map(|state| state.as_composite())
+        .flat_map(|composite| composite.children().iter().copied())
+        .collect();
+    states
+        .iter()
+        .filter(|state| !nested.contains(&state.id()))
+        .copied()
+        .collect()
+}
+
+/// The children of the given state, if it is a composite state.
+pub(crate) fn child_states<'a>(states: &[&'a dyn State], state: &dyn State) -> Vec<&'a dyn State> {
+    state
+        .as_composite()
+        .map(|composite| composite.children())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|child| states.iter().find(|s| s.id() == *child).copied())
+        .collect()
+}
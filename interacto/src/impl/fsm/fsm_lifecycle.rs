@@ -0,0 +1,189 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+use crate::clock::Clock;
+use crate::dom::Event;
+use crate::fsm::{
+    CancelFSMException, ErrorPolicy, FSMHandler, FSMTrace, FSMTraceSubscriber, SharedFSMError, FSM,
+};
+
+use super::fsm_handlers::FSMHandlers;
+use super::fsm_tracers::FSMTracers;
+
+/// The execution data that all the FSM implementations share: whether the FSM started,
+/// its settings, the events that wait for the end of its current execution,
+/// and the handlers and subscribers notified about its life cycle.
+pub(crate) struct FSMLifecycle {
+    started: bool,
+    pub(crate) inner: bool,
+    pub(crate) log: bool,
+    pub(crate) error_policy: ErrorPolicy,
+    /// Timestamps the trace records.
+    pub(crate) clock: Rc<dyn Clock>,
+    /// The events still in process.
+    /// They are processed once the FSM terminates its current execution.
+    events_to_process: VecDeque<Box<dyn Event>>,
+    handlers: FSMHandlers,
+    tracers: FSMTracers,
+}
+
+impl FSMLifecycle {
+    pub(crate) fn new(clock: Rc<dyn Clock>) -> Self {
+        Self {
+            started: false,
+            inner: false,
+            log: false,
+            error_policy: ErrorPolicy::default(),
+            clock,
+            events_to_process: VecDeque::new(),
+            handlers: FSMHandlers::default(),
+            tracers: FSMTracers::default(),
+        }
+    }
+
+    /// The life cycle of a new instance of the FSM: it keeps the settings and the clock,
+    /// but neither the handlers, the subscribers nor the remaining events.
+    pub(crate) fn instance(&self) -> Self {
+        Self {
+            inner: self.inner,
+            log: self.log,
+            error_policy: self.error_policy,
+            ..Self::new(self.clock.clone())
+        }
+    }
+
+    pub(crate) fn started(&self) -> bool {
+        self.started
+    }
+
+    pub(crate) fn trace(&self, trace: impl FnOnce(u64) -> FSMTrace) {
+        self.tracers.emit(self.log, || trace(self.clock.now()));
+    }
+
+    pub(crate) fn add_handler(&mut self, handler: Rc<RefCell<dyn FSMHandler>>) {
+        self.handlers.add(handler);
+    }
+
+    pub(crate) fn remove_handler(&mut self, handler: &Rc<RefCell<dyn FSMHandler>>) {
+        self.handlers.remove(handler);
+    }
+
+    pub(crate) fn subscribe(&mut self, subscriber: Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.tracers.add(subscriber);
+    }
+
+    pub(crate) fn unsubscribe(&mut self, subscriber: &Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.tracers.remove(subscriber);
+    }
+
+    pub(crate) fn add_remaining_event(&mut self, event: Box<dyn Event>) {
+        self.events_to_process.push_back(event);
+    }
+
+    pub(crate) fn remaining_events_count(&self) -> usize {
+        self.events_to_process.len()
+    }
+
+    fn next_remaining_event(&mut self) -> Option<Box<dyn Event>> {
+        self.events_to_process.pop_front()
+    }
+
+    pub(crate) fn error(&self, err: &SharedFSMError) {
+        self.handlers.error(err);
+    }
+
+    /// Notifies the cancellation of the FSM, without reinitialising it.
+    /// An FSM that did not start has no trace span to close.
+    pub(crate) fn cancel(&self) {
+        if self.started {
+            self.trace(|timestamp| FSMTrace::Cancel { timestamp });
+            self.handlers.cancels();
+        }
+    }
+
+    pub(crate) fn reinit(&mut self) {
+        self.started = false;
+    }
+
+    /// Also forgets the remaining events.
+    pub(crate) fn full_reinit(&mut self) {
+        self.events_to_process.clear();
+        self.reinit();
+    }
+
+    pub(crate) fn uninstall(&mut self) {
+        self.handlers.clear();
+        self.tracers.clear();
+    }
+}
+
+/// The FSMs whose life cycle is an `FSMLifecycle`. Their `FSM::on_starting`, `FSM::on_updating`,
+/// `FSM::on_cancelling` and `FSM::on_terminating` delegate to the provided methods of this trait,
+/// which rely on their own `FSM::reinit`, `FSM::full_reinit` and `FSM::process`.
+pub(crate) trait LifecycleFSM: FSM {
+    fn lifecycle(&self) -> &FSMLifecycle;
+
+    fn lifecycle_mut(&mut self) -> &mut FSMLifecycle;
+
+    fn starts(&mut self) -> Result<(), CancelFSMException> {
+        let lifecycle = self.lifecycle_mut();
+        lifecycle.started = true;
+        lifecycle.trace(|timestamp| FSMTrace::Start { timestamp });
+        let res = lifecycle.handlers.starts();
+        if res.is_err() {
+            self.on_cancelling();
+        }
+        res
+    }
+
+    fn updates(&mut self) -> Result<(), CancelFSMException> {
+        let lifecycle = self.lifecycle();
+        if !lifecycle.started {
+            return Ok(());
+        }
+        let res = lifecycle.handlers.updates();
+        if res.is_err() {
+            self.on_cancelling();
+        }
+        res
+    }
+
+    fn cancels(&mut self) {
+        self.lifecycle().cancel();
+        self.full_reinit();
+    }
+
+    /// Once the FSM is reinitialised, it processes the events that waited for its termination.
+    fn stops(&mut self) -> Result<(), CancelFSMException> {
+        let lifecycle = self.lifecycle();
+        if lifecycle.started {
+            lifecycle.trace(|timestamp| FSMTrace::Terminate { timestamp });
+            let res = lifecycle.handlers.stops();
+            if res.is_err() {
+                self.on_cancelling();
+                return res;
+            }
+        }
+        self.reinit();
+        while let Some(event) = self.lifecycle_mut().next_remaining_event() {
+            self.process(event.as_ref());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Declares an `FSMImpl` in a single block: its states, its starting state and its transitions
+/// (with their guards, actions and timeouts).
+///
+/// States are declared with one of the kinds `init`, `std`, `terminal` or `cancelling`.
+/// The `init` kind names the initial state of the FSM, that every FSM has.
+/// Each declared state is bound to a local variable, so that a transition that refers to
+/// an undeclared state does not compile.
+///
+/// Transitions are declared as `src -> tgt` followed by:
+/// - `on [event types] when "label" (guard) action (action);` for a transition that processes events
+///   (`when`, its label and `action` are optional);
+/// - `on<Type> [event types] when "label" (guard) action (action);` for a transition that processes
+///   events of the concrete type `Type` only, given as such to its guard and action;
+/// - `timeout (delay) action (action);` for a timeout transition with a fixed delay in ms;
+/// - `timeout_with (delay_fn) action (action);` for a timeout transition whose delay is computed
+///   each time its source state is entered, from the data it captures (see `TimeoutDelay`);
+/// - `sub (fsm);` for a sub-FSM transition.
+///
+/// Guards and actions return a `Result`: an error is given to `FSM::on_error` (see `ErrorPolicy`).
+///
+/// An optional `clock: expr;` first line gives the clock of the FSM.
+///
+/// ```
+/// use interacto::dom::{EventType, MouseEventType};
+/// use interacto::fsm;
+///
+/// let fsm = fsm! {
+///     states {
+///         init: init,
+///         pressed: std,
+///         released: terminal,
+///         cancelled: cancelling,
+///     }
+///     starting: pressed;
+///     transitions {
+///         init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+///         pressed -> released on [EventType::Mouse(MouseEventType::Mouseup)]
+///             when "is release" (|e| Ok(e.event_type() == EventType::Mouse(MouseEventType::Mouseup)));
+///         pressed -> cancelled timeout (1000);
+///     }
+/// };
+/// ```
+///
+/// Referring to an undeclared state is a compilation error:
+/// ```compile_fail
+/// use interacto::dom::{EventType, MouseEventType};
+/// use interacto::fsm;
+///
+/// let fsm = fsm! {
+///     states {
+///         init: init,
+///         pressed: std,
+///     }
+///     transitions {
+///         init -> released on [EventType::Mouse(MouseEventType::Mouseup)];
+///     }
+/// };
+/// ```
+/// # Category
+/// FSM
+#[macro_export]
+macro_rules! fsm {
+    (
+        $(clock: $clock:expr;)?
+        states { $($state:ident : $kind:ident),* $(,)? }
+        $(starting: $starting:ident;)?
+        transitions { $($transitions:tt)* }
+    ) => {{
+        #[allow(unused_mut)]
+        let mut fsm = $crate::fsm!(@new $($clock)?);
+        $(
+            #[allow(unused_variables)]
+            let $state = $crate::fsm!(@state fsm, $kind, stringify!($state));
+        )*
+        $($crate::fsm::FSM::set_starting_state(&mut fsm, $starting);)?
+        $crate::fsm!(@transitions fsm; $($transitions)*);
+        fsm
+    }};
+
+    (@new) => {
+        $crate::r#impl::fsm::FSMImpl::new()
+    };
+    (@new $clock:expr) => {
+        $crate::r#impl::fsm::FSMImpl::with_clock($clock)
+    };
+
+    (@state $fsm:ident, init, $name:expr) => {
+        $crate::fsm::State::id($crate::fsm::FSM::init_state(&$fsm))
+    };
+    (@state $fsm:ident, std, $name:expr) => {
+        $fsm.add_std_state($name, false)
+    };
+    (@state $fsm:ident, terminal, $name:expr) => {
+        $fsm.add_terminal_state($name, false)
+    };
+    (@state $fsm:ident, cancelling, $name:expr) => {
+        $fsm.add_cancelling_state($name)
+    };
+
+    (@transitions $fsm:ident;) => {};
+    (@transitions $fsm:ident;
+        $src:ident -> $tgt:ident on [$($event:expr),+ $(,)?]
+        $(when $($label:literal)? ($guard:expr))? $(action ($action:expr))?;
+        $($rest:tt)*
+    ) => {
+        let tr = $crate::r#impl::fsm::TransitionBase::new(
+            $src,
+            $tgt,
+            $crate::collections::HashSet::from([$($event),+]),
+            $crate::fsm!(@action $($action)?),
+            $crate::fsm!(@guard $($guard)?),
+        );
+        $($(let tr = tr.with_guard_label($label);)?)?
+        $fsm.add_transition($crate::__private::Box::new(tr));
+        $crate::fsm!(@transitions $fsm; $($rest)*);
+    };
+    (@transitions $fsm:ident;
+        $src:ident -> $tgt:ident on<$ty:ty> [$($event:expr),+ $(,)?]
+        $(when $($label:literal)? ($guard:expr))? $(action ($action:expr))?;
+        $($rest:tt)*
+    ) => {
+        let tr = $crate::r#impl::fsm::TypedTransition::<$ty>::new(
+            $src,
+            $tgt,
+            $crate::collections::HashSet::from([$($event),+]),
+            $crate::fsm!(@typed_action $ty $(, $action)?),
+            $crate::fsm!(@typed_guard $ty $(, $guard)?),
+        );
+        $($(let tr = tr.with_guard_label($label);)?)?
+        $fsm.add_transition($crate::__private::Box::new(tr));
+        $crate::fsm!(@transitions $fsm; $($rest)*);
+    };
+    (@transitions $fsm:ident;
+        $src:ident -> $tgt:ident timeout ($delay:expr) $(action ($action:expr))?;
+        $($rest:tt)*
+    ) => {
+        $fsm.add_transition($crate::__private::Box::new(
+            $crate::r#impl::fsm::TimeoutTransition::with_delay(
+                $src,
+                $tgt,
+                $delay,
+                $crate::fsm!(@timeout_action $($action)?),
+            ),
+        ));
+        $crate::fsm!(@transitions $fsm; $($rest)*);
+    };
+    (@transitions $fsm:ident;
+        $src:ident -> $tgt:ident timeout_with ($delay:expr) $(action ($action:expr))?;
+        $($rest:tt)*
+    ) => {
+        $fsm.add_transition($crate::__private::Box::new($crate::r#impl::fsm::TimeoutTransition::new(
+            $src,
+            $tgt,
+            {
+                let delay: $crate::r#impl::fsm::TimeoutDelay = $crate::__private::Box::new($delay);
+                delay
+            },
+            $crate::fsm!(@timeout_action $($action)?),
+        )));
+        $crate::fsm!(@transitions $fsm; $($rest)*);
+    };
+    (@transitions $fsm:ident;
+        $src:ident -> $tgt:ident sub ($inner:expr);
+        $($rest:tt)*
+    ) => {
+        $fsm.add_transition($crate::__private::Box::new(
+            $crate::r#impl::fsm::SubFSMTransition::new($src, $tgt, $crate::__private::Box::new($inner)),
+        ));
+        $crate::fsm!(@transitions $fsm; $($rest)*);
+    };
+
+    (@action) => {
+        ::core::option::Option::None
+    };
+    (@action $action:expr) => {{
+        let action: $crate::r#impl::fsm::TransitionAction = $crate::__private::Box::new($action);
+        ::core::option::Option::Some(action)
+    }};
+    (@guard) => {
+        ::core::option::Option::None
+    };
+    (@guard $guard:expr) => {{
+        let guard: $crate::r#impl::fsm::TransitionGuard = $crate::__private::Box::new($guard);
+        ::core::option::Option::Some(guard)
+    }};
+    (@typed_action $ty:ty) => {
+        ::core::option::Option::None
+    };
+    (@typed_action $ty:ty, $action:expr) => {{
+        let action: $crate::r#impl::fsm::TypedAction<$ty> = $crate::__private::Box::new($action);
+        ::core::option::Option::Some(action)
+    }};
+    (@typed_guard $ty:ty) => {
+        ::core::option::Option::None
+    };
+    (@typed_guard $ty:ty, $guard:expr) => {{
+        let guard: $crate::r#impl::fsm::TypedGuard<$ty> = $crate::__private::Box::new($guard);
+        ::core::option::Option::Some(guard)
+    }};
+    (@timeout_action) => {
+        ::core::option::Option::None
+    };
+    (@timeout_action $action:expr) => {{
+        let action: $crate::r#impl::fsm::TimeoutAction = $crate::__private::Box::new($action);
+        ::core::option::Option::Some(action)
+    }};
+}
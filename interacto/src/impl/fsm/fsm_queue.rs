@@ -0,0 +1,204 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::dom::Event;
+use crate::fsm::FSM;
+
+/// A message posted to an FSM from another thread.
+/// # Category
+/// FSM
+pub enum FSMMessage {
+    /// An event to process.
+    Event(Box<dyn Event + Send>),
+    /// The current timeout transition of the FSM may have to be executed.
+    Timeout,
+}
+
+/// A queue of messages that other threads post to an FSM, and that the thread owning the FSM
+/// (usually the UI thread) drains to process them, since FSMs are neither `Send` nor `Sync`.
+///
+/// Messages are processed in the order the queue received them: messages posted by a same
+/// thread keep their posting order, and messages posted concurrently by several threads are
+/// interleaved in the order in which their posts completed.
+/// # Category
+/// FSM
+pub struct FSMEventQueue {
+    sender: Sender<FSMMessage>,
+    receiver: Receiver<FSMMessage>,
+    /// The number of messages posted and not received yet.
+    /// Senders count a message before posting it, so it is never lower than the number
+    /// of messages in the channel.
+    queued: Arc<AtomicUsize>,
+}
+
+impl FSMEventQueue {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            queued: Arc::default(),
+        }
+    }
+
+    /// A handle that posts messages into this queue, and that can be sent to other threads.
+    pub fn sender(&self) -> FSMEventSender {
+        FSMEventSender {
+            sender: self.sender.clone(),
+            queued: self.queued.clone(),
+        }
+    }
+
+    /// Processes the messages pending when called with the given FSM, in order.
+    /// The messages posted while draining (eg by the handlers of the FSM) are left
+    /// for the next drain. Returns the number of processed messages.
+    pub fn drain(&self, fsm: &mut dyn FSM) -> usize {
+        let pending = self.queued.load(Ordering::SeqCst);
+        let mut processed = 0;
+        while processed < pending {
+            let Ok(message) = self.receiver.try_recv() else {
+                break;
+            };
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            Self::dispatch(fsm, message);
+            processed += 1;
+        }
+        processed
+    }
+
+    /// Waits at most `timeout` for a message, then processes the pending messages with
+    /// the given FSM (see `drain`). Returns the number of processed messages.
+    pub fn wait_and_drain(&self, fsm: &mut dyn FSM, timeout: Duration) -> usize {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(message) => {
+                self.queued.fetch_sub(1, Ordering::SeqCst);
+                Self::dispatch(fsm, message);
+                1 + self.drain(fsm)
+            }
+            Err(_) => 0,
+        }
+    }
+
+    fn dispatch(fsm: &mut dyn FSM, message: FSMMessage) {
+        match message {
+            FSMMessage::Event(event) => {
+                fsm.process(event.as_ref());
+            }
+            FSMMessage::Timeout => {
+                fsm.check_timeout();
+            }
+        }
+    }
+}
+
+impl Default for FSMEventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Posts messages into an `FSMEventQueue` from any thread.
+/// # Category
+/// FSM
+#[derive(Clone)]
+pub struct FSMEventSender {
+    sender: Sender<FSMMessage>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl FSMEventSender {
+    fn post(&self, message: FSMMessage) -> bool {
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let posted = self.sender.send(message).is_ok();
+        if !posted {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+        }
+        posted
+    }
+
+    /// Posts an event to process.
+    /// Returns false if the queue does not exist anymore.
+    pub fn post_event(&self, event: Box<dyn Event + Send>) -> bool {
+        self.post(FSMMessage::Event(event))
+    }
+
+    /// Posts a timeout: once drained, the FSM executes its current timeout transition if its
+    /// delay elapsed following the clock of the FSM (see `FSM::check_timeout`).
+    /// So, timeouts posted too early or for a previous timeout transition are ignored.
+    /// Returns false if the queue does not exist anymore.
+    pub fn post_timeout(&self) -> bool {
+        self.post(FSMMessage::Timeout)
+    }
+}
+
+/// Posts timeouts into an `FSMEventQueue` once their delay elapsed, from a single
+/// timer thread whatever the number of scheduled timeouts.
+/// UI platforms schedule a timeout using `FSM::next_timeout` after each drain.
+/// The timer thread stops once the timer is dropped or the queue does not exist anymore.
+/// # Category
+/// FSM
+pub struct FSMTimer {
+    deadlines: Sender<Instant>,
+}
+
+impl FSMTimer {
+    /// Spawns the timer thread, that posts its timeouts with the given sender.
+    pub fn new(sender: FSMEventSender) -> Self {
+        let (deadlines, scheduled) = mpsc::channel();
+        thread::spawn(move || Self::run(&sender, &scheduled));
+        Self { deadlines }
+    }
+
+    /// Schedules a timeout to post once the given delay elapsed.
+    /// Returns false if the timer thread stopped.
+    pub fn schedule(&self, delay: Duration) -> bool {
+        self.deadlines.send(Instant::now() + delay).is_ok()
+    }
+
+    fn run(sender: &FSMEventSender, scheduled: &Receiver<Instant>) {
+        let mut deadlines: BinaryHeap<Reverse<Instant>> = BinaryHeap::new();
+        loop {
+            let next = match deadlines.peek() {
+                Some(Reverse(deadline)) => {
+                    scheduled.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => scheduled.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match next {
+                Ok(deadline) => deadlines.push(Reverse(deadline)),
+                Err(RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    while deadlines
+                        .peek()
+                        .is_some_and(|Reverse(deadline)| *deadline <= now)
+                    {
+                        deadlines.pop();
+                        if !sender.post_timeout() {
+                            return;
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::fsm::{CancelFSMException, FSMError, FSMHandler, SharedFSMError};
+
+/// A notification sent by an FSM to its handlers.
+#[derive(Debug, Clone)]
+pub(crate) enum FSMNotification {
+    Start,
+    Update,
+    Stop,
+    Cancel,
+    Error(SharedFSMError),
+}
+
+/// An FSM handler that records the notifications of the FSM it observes,
+/// so that an enclosing FSM can react to them once the observed FSM processed an event.
+#[derive(Default)]
+pub(crate) struct FSMRecorder {
+    notifications: Vec<FSMNotification>,
+}
+
+impl FSMRecorder {
+    /// Takes the notifications recorded so far.
+    pub(crate) fn take(&mut self) -> Vec<FSMNotification> {
+        mem::take(&mut self.notifications)
+    }
+}
+
+impl FSMHandler for FSMRecorder {
+    fn fsm_starts(&mut self) -> Result<(), CancelFSMException> {
+        self.notifications.push(FSMNotification::Start);
+        Ok(())
+    }
+
+    fn fsm_updates(&mut self) -> Result<(), CancelFSMException> {
+        self.notifications.push(FSMNotification::Update);
+        Ok(())
+    }
+
+    fn fsm_stops(&mut self) -> Result<(), CancelFSMException> {
+        self.notifications.push(FSMNotification::Stop);
+        Ok(())
+    }
+
+    fn fsm_cancels(&mut self) {
+        self.notifications.push(FSMNotification::Cancel);
+    }
+
+    /// Only called when the error is not shared: its message is then recorded.
+    fn fsm_error(&mut self, err: &(dyn core::error::Error + Send + Sync)) {
+        let err: FSMError = err.to_string().into();
+        self.notifications
+            .push(FSMNotification::Error(Arc::from(err)));
+    }
+
+    fn fsm_shared_error(&mut self, err: &SharedFSMError) {
+        self.notifications.push(FSMNotification::Error(err.clone()));
+    }
+
+    fn pre_fsm_start(&mut self) {}
+
+    fn pre_fsm_update(&mut self) {}
+
+    fn pre_fsm_stop(&mut self) {}
+}
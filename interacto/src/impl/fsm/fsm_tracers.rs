@@ -0,0 +1,58 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::fsm::{FSMTrace, FSMTraceSubscriber};
+
+/// The subscribers to the execution of an FSM.
+#[derive(Default)]
+pub(crate) struct FSMTracers {
+    subscribers: Vec<Rc<RefCell<dyn FSMTraceSubscriber>>>,
+}
+
+impl FSMTracers {
+    pub(crate) fn add(&mut self, subscriber: Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub(crate) fn remove(&mut self, subscriber: &Rc<RefCell<dyn FSMTraceSubscriber>>) {
+        self.subscribers.retain(|s| !Rc::ptr_eq(s, subscriber));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.subscribers.clear();
+    }
+
+    /// Sends a trace record to the subscribers, and prints it if `log` is true.
+    /// The record is only built if someone reads it.
+    pub(crate) fn emit(&self, log: bool, trace: impl FnOnce() -> FSMTrace) {
+        if self.subscribers.is_empty() && !log {
+            return;
+        }
+        let trace = trace();
+        // Without `std`, there is no standard output to print to: the
+        // subscribers are the only way to read the traces.
+        #[cfg(feature = "std")]
+        if log {
+            eprintln!("{trace}");
+        }
+        // Iterating over a copy, so that subscribers can unsubscribe while notified.
+        for subscriber in self.subscribers.clone() {
+            subscriber.borrow_mut().on_trace(&trace);
+        }
+    }
+}
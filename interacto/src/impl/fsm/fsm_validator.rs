@@ -0,0 +1,299 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::collections::{HashMap, HashSet};
+use crate::dom::EventType;
+use crate::fsm::{
+    CompositeState, ConcurrentFSM, InputState, OutputState, State, StateId, StateKind, Transition,
+    VisitorFSM, FSM,
+};
+
+/// A structural defect of an FSM, as reported by `FSMValidator`.
+/// States are identified by their names.
+/// # Category
+/// FSM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FSMDefect {
+    /// A state that cannot be reached from the initial state.
+    UnreachableState(String),
+    /// A state from which no terminal or cancelling state can be reached.
+    NoPathToEnd(String),
+    /// A transition (given by its source and target states) that accepts no event.
+    NoAcceptedEvent { src: String, tgt: String },
+    /// A starting state that is not one of the states of its FSM.
+    StartingStateNotInFSM(String),
+    /// Several unguarded transitions that leave the same state on the same type of event.
+    AmbiguousTransitions {
+        state: String,
+        event_type: EventType,
+    },
+}
+
+impl fmt::Display for FSMDefect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnreachableState(state) => write!(f, "state '{state}' is unreachable"),
+            Self::NoPathToEnd(state) => {
+                write!(
+                    f,
+                    "no terminal or cancelling state can be reached from state '{state}'"
+                )
+            }
+            Self::NoAcceptedEvent { src, tgt } => {
+                write!(f, "the transition from '{src}' to '{tgt}' accepts no event")
+            }
+            Self::StartingStateNotInFSM(state) => {
+                write!(f, "the starting state '{state}' is not a state of the FSM")
+            }
+            Self::AmbiguousTransitions { state, event_type } => write!(
+                f,
+                "several unguarded transitions leave state '{state}' on {event_type:?}"
+            ),
+        }
+    }
+}
+
+/// A visitor that checks the structure of FSMs (including their sub-FSMs
+/// and the FSMs of concurrent FSMs) and collects their defects.
+/// Entering a composite state enters its initial child, and the children of a composite state
+/// can leave it through its transitions: both are considered when looking for the states
+/// that can be reached or that lead to an end.
+/// # Category
+/// FSM
+#[derive(Default)]
+pub struct FSMValidator {
+    defects: Vec<FSMDefect>,
+    frames: Vec<Frame>,
+}
+
+/// The structure of the FSM being visited.
+#[derive(Default)]
+struct Frame {
+    states: Vec<(StateId, String, StateKind)>,
+    edges: Vec<(StateId, StateId)>,
+    /// The number of unguarded transitions per source state and accepted event type.
+    unguarded: HashMap<(StateId, EventType), usize>,
+}
+
+impl FSMValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates the given FSM.
+    /// # Errors
+    /// The defects of the FSM, if it has some.
+    pub fn validate(fsm: &dyn FSM) -> Result<(), Vec<FSMDefect>> {
+        let mut validator = Self::new();
+        fsm.accept_visitor(&mut validator);
+        if validator.defects.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.defects)
+        }
+    }
+
+    /// The defects found in the visited FSMs.
+    pub fn defects(&self) -> &[FSMDefect] {
+        &self.defects
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames
+            .last_mut()
+            .expect("states and transitions are visited through their FSM")
+    }
+
+    fn add_state(&mut self, state: &dyn State) {
+        let name = state.name().to_string();
+        self.frame().states.push((state.id(), name, state.kind()));
+    }
+
+    /// Registers a transition that is executed on events.
+    fn add_event_transition(&mut self, transition: &dyn Transition) {
+        let frame = self.frame();
+        frame.edges.push((transition.src(), transition.target()));
+        let events = transition.get_accepted_events();
+        if events.is_empty() {
+            let src = frame.name(transition.src());
+            let tgt = frame.name(transition.target());
+            self.defects.push(FSMDefect::NoAcceptedEvent { src, tgt });
+        } else if !transition.is_guarded() && !transition.has_filter() {
+            for event_type in events {
+                *frame
+                    .unguarded
+                    .entry((transition.src(), *event_type))
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    fn check(&mut self, frame: Frame) {
+        let reachable = frame.closure(StateId(0), |(src, tgt)| (src, tgt));
+        let mut to_end = HashSet::new();
+        for (id, _, kind) in &frame.states {
+            if matches!(kind, StateKind::Terminal | StateKind::Cancelling) {
+                to_end.extend(frame.closure(*id, |(src, tgt)| (tgt, src)));
+            }
+        }
+        for (id, name, kind) in &frame.states {
+            if !reachable.contains(id) {
+                self.defects.push(FSMDefect::UnreachableState(name.clone()));
+            }
+            if *kind != StateKind::Cancelling
+                && *kind != StateKind::Terminal
+                && !to_end.contains(id)
+            {
+                self.defects.push(FSMDefect::NoPathToEnd(name.clone()));
+            }
+        }
+        let mut ambiguous: Vec<(StateId, EventType)> = frame
+            .unguarded
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(key, _)| *key)
+            .collect();
+        ambiguous.sort_by_key(|(id, event_type)| (*id, format!("{event_type:?}")));
+        for (id, event_type) in ambiguous {
+            self.defects.push(FSMDefect::AmbiguousTransitions {
+                state: frame.name(id),
+                event_type,
+            });
+        }
+    }
+}
+
+impl Frame {
+    fn name(&self, id: StateId) -> String {
+        self.states
+            .iter()
+            .find(|(state, _, _)| *state == id)
+            .map_or_else(|| format!("#{}", id.index()), |(_, name, _)| name.clone())
+    }
+
+    /// The states reachable from the given one following the (possibly reversed) edges.
+    fn closure(
+        &self,
+        from: StateId,
+        orient: impl Fn((StateId, StateId)) -> (StateId, StateId),
+    ) -> HashSet<StateId> {
+        let mut visited = HashSet::from([from]);
+        let mut to_visit = vec![from];
+        while let Some(state) = to_visit.pop() {
+            for (src, tgt) in self.edges.iter().map(|edge| orient(*edge)) {
+                if src == state && visited.insert(tgt) {
+                    to_visit.push(tgt);
+                }
+            }
+        }
+        visited
+    }
+}
+
+impl VisitorFSM for FSMValidator {
+    fn visit_fsm(&mut self, fsm: &dyn FSM) {
+        let starting = fsm.starting_state();
+        let states = fsm.states();
+        if !states
+            .iter()
+            .any(|state| core::ptr::addr_eq(*state, starting))
+        {
+            self.defects.push(FSMDefect::StartingStateNotInFSM(
+                starting.name().to_string(),
+            ));
+        }
+        self.frames.push(Frame::default());
+        for state in &states {
+            state.accept_visitor(self);
+        }
+        for state in &states {
+            for transition in state
+                .as_output()
+                .map(|s| s.transitions())
+                .unwrap_or_default()
+            {
+                transition.accept_visitor(self);
+            }
+        }
+        let frame = self.frames.pop().expect("pushed above");
+        self.check(frame);
+    }
+
+    fn visit_and_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        for conc_fsm in fsm.conc_fsms() {
+            conc_fsm.accept_visitor(self);
+        }
+    }
+
+    fn visit_xor_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        for conc_fsm in fsm.conc_fsms() {
+            conc_fsm.accept_visitor(self);
+        }
+    }
+
+    fn visit_state(&mut self, state: &dyn OutputState) {
+        self.add_state(state);
+    }
+
+    fn visit_init_state(&mut self, state: &dyn OutputState) {
+        self.add_state(state);
+    }
+
+    fn visit_cancelling_state(&mut self, state: &dyn InputState) {
+        self.add_state(state);
+    }
+
+    fn visit_terminal_state(&mut self, state: &dyn InputState) {
+        self.add_state(state);
+    }
+
+    fn visit_composite_state(&mut self, state: &dyn CompositeState) {
+        self.add_state(state);
+        let frame = self.frame();
+        if let Some(child) = state.initial_child() {
+            frame.edges.push((state.id(), child));
+        }
+        for child in state.children() {
+            frame.edges.push((*child, state.id()));
+        }
+    }
+
+    fn visit_history_state(&mut self, state: &dyn InputState) {
+        self.add_state(state);
+    }
+
+    fn visit_transition(&mut self, transition: &dyn Transition) {
+        self.add_event_transition(transition);
+    }
+
+    fn visit_timeout_transition(&mut self, transition: &dyn Transition) {
+        self.frame()
+            .edges
+            .push((transition.src(), transition.target()));
+    }
+
+    fn visit_sub_fsm_transition(&mut self, transition: &dyn Transition) {
+        self.add_event_transition(transition);
+        if let Some(inner) = transition.inner_fsm() {
+            inner.accept_visitor(self);
+        }
+    }
+}
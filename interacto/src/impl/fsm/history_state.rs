@@ -0,0 +1,76 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::fsm::{InputState, State, StateId, StateKind, VisitorFSM};
+use alloc::string::String;
+use alloc::string::ToString;
+
+/// A history pseudo-state of a composite state.
+/// Entering it enters again the states that were active when its composite state was last left:
+/// only the child of the composite state for a shallow history, the innermost state for a deep one.
+/// If the composite state has never been left, entering the history state enters the composite state.
+/// # Category
+/// FSM
+pub struct HistoryState {
+    name: String,
+    id: StateId,
+    composite: StateId,
+    deep: bool,
+}
+
+impl HistoryState {
+    pub fn new(name: &str, id: StateId, composite: StateId, deep: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            id,
+            composite,
+            deep,
+        }
+    }
+
+    /// The composite state whose history this state restores.
+    pub fn composite(&self) -> StateId {
+        self.composite
+    }
+}
+
+impl State for HistoryState {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> StateId {
+        self.id
+    }
+
+    fn kind(&self) -> StateKind {
+        if self.deep {
+            StateKind::DeepHistory
+        } else {
+            StateKind::ShallowHistory
+        }
+    }
+
+    fn as_input(&self) -> Option<&dyn InputState> {
+        Some(self)
+    }
+
+    fn uninstall(&mut self) {}
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_history_state(self);
+    }
+}
+
+impl InputState for HistoryState {}
@@ -0,0 +1,81 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::fsm::{OutputState, State, StateId, StateKind, Transition, VisitorFSM};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// The initial state of an FSM.
+/// An FSM cannot go back to its initial state through a transition:
+/// it goes back to it when reinitialised.
+/// # Category
+/// FSM
+pub struct InitState {
+    name: String,
+    id: StateId,
+    transitions: Vec<Box<dyn Transition>>,
+}
+
+impl InitState {
+    pub fn new(name: &str, id: StateId) -> Self {
+        Self {
+            name: name.to_string(),
+            id,
+            transitions: Vec::new(),
+        }
+    }
+}
+
+impl State for InitState {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> StateId {
+        self.id
+    }
+
+    fn kind(&self) -> StateKind {
+        StateKind::Init
+    }
+
+    fn as_output(&self) -> Option<&dyn OutputState> {
+        Some(self)
+    }
+
+    fn as_output_mut(&mut self) -> Option<&mut dyn OutputState> {
+        Some(self)
+    }
+
+    fn uninstall(&mut self) {
+        self.transitions.iter_mut().for_each(|tr| tr.uninstall());
+        self.transitions.clear();
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_init_state(self);
+    }
+}
+
+impl OutputState for InitState {
+    fn transitions(&self) -> &[Box<dyn Transition>] {
+        &self.transitions
+    }
+
+    fn transitions_mut(&mut self) -> &mut Vec<Box<dyn Transition>> {
+        &mut self.transitions
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::dom::{EventType, KeyEventType, KeyboardEvent, Modifiers};
+use crate::fsm::StateId;
+use alloc::format;
+use alloc::string::ToString;
+
+use super::filtered_transition::{filtered_transition_impl, FilteredTransition};
+use super::{TypedAction, TypedGuard};
+
+/// A transition on keyboard events, that can be restricted to a key value,
+/// to a physical key (ie a code) and to modifiers.
+/// Its action and its guard are given the processed events as keyboard events.
+/// # Category
+/// FSM
+pub struct KeyTransition(FilteredTransition);
+
+impl KeyTransition {
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        event_types: impl IntoIterator<Item = KeyEventType>,
+        action: Option<TypedAction<dyn KeyboardEvent>>,
+        guard: Option<TypedGuard<dyn KeyboardEvent>>,
+    ) -> Self {
+        Self(FilteredTransition::with_family(
+            src_state,
+            tgt_state,
+            event_types.into_iter().map(EventType::Key).collect(),
+            action,
+            guard,
+            |event| event.as_keyboard_event(),
+        ))
+    }
+
+    /// Restricts the transition to the events of the given key value (eg `"a"`, `"Enter"`).
+    pub fn with_key(mut self, key: &str) -> Self {
+        let key = key.to_string();
+        self.0.add_filter::<dyn KeyboardEvent>(
+            format!("key {key:?}"),
+            |event| event.as_keyboard_event(),
+            move |event| event.key() == key,
+        );
+        self
+    }
+
+    /// Restricts the transition to the events of the given physical key (eg `"KeyA"`, `"Enter"`).
+    pub fn with_code(mut self, code: &str) -> Self {
+        let code = code.to_string();
+        self.0.add_filter::<dyn KeyboardEvent>(
+            format!("code {code:?}"),
+            |event| event.as_keyboard_event(),
+            move |event| event.code() == code,
+        );
+        self
+    }
+
+    /// Restricts the transition to the events that occurred with exactly the given modifiers.
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.0.add_filter::<dyn KeyboardEvent>(
+            format!("{modifiers:?}"),
+            |event| event.as_keyboard_event(),
+            move |event| event.modifiers() == modifiers,
+        );
+        self
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    pub fn with_guard_label(mut self, label: &str) -> Self {
+        self.0.set_guard_label(label);
+        self
+    }
+}
+
+filtered_transition_impl!(KeyTransition);
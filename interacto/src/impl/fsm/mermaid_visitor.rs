@@ -0,0 +1,279 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::fsm::{
+    CompositeState, ConcurrentFSM, InputState, OutputState, State, StateId, Transition, VisitorFSM,
+    FSM,
+};
+use crate::r#impl::fsm::fsm_labels::{
+    child_states, timeout_label, top_level_states, transition_label,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A visitor that renders an FSM as a Mermaid state diagram.
+/// Edges are labelled with the events their transitions accept and with their guard labels.
+/// The starting state of each FSM has the `starting` class.
+/// Sub-FSMs, concurrent FSMs and composite states are rendered as composite states.
+/// History states are rendered as states named after them (eg `H` or `H*`).
+/// # Category
+/// FSM
+#[derive(Default)]
+pub struct MermaidVisitor {
+    output: String,
+    depth: usize,
+    fsm_count: usize,
+    frames: Vec<Frame>,
+}
+
+/// The FSM being visited.
+struct Frame {
+    fsm: usize,
+    starting: StateId,
+}
+
+impl MermaidVisitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The Mermaid diagram of the visited FSM.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    /// Starts the diagram if the visited FSM is not nested in another one.
+    /// Returns true if it has been started.
+    fn open_diagram(&mut self) -> bool {
+        if self.depth > 0 {
+            return false;
+        }
+        self.line("stateDiagram-v2");
+        self.depth += 1;
+        self.line("classDef starting stroke-width:3px,font-weight:bold");
+        self.line("classDef cancelling fill:#f99");
+        true
+    }
+
+    fn open_composite(&mut self, id: usize, label: &str) {
+        self.line(&format!("state \"{}\" as f{id} {{", escape(label)));
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn state(&mut self, state: &dyn State) -> String {
+        let node = self.node(state);
+        self.line(&format!("state \"{}\" as {node}", escape(state.name())));
+        self.check_starting(state, &node);
+        node
+    }
+
+    fn node(&self, state: &dyn State) -> String {
+        let frame = self
+            .frames
+            .last()
+            .expect("states are visited through their FSM");
+        node_id(frame.fsm, state.id())
+    }
+
+    fn check_starting(&mut self, state: &dyn State, node: &str) {
+        let frame = self
+            .frames
+            .last()
+            .expect("states are visited through their FSM");
+        if frame.starting == state.id() {
+            self.line(&format!("class {node} starting"));
+        }
+    }
+
+    /// Visits the given state, then its children in the block of the state if it is composite.
+    fn visit_nested_state(&mut self, states: &[&dyn State], state: &dyn State) {
+        state.accept_visitor(self);
+        if state.as_composite().is_some() {
+            for child in child_states(states, state) {
+                self.visit_nested_state(states, child);
+            }
+            // Opened by visit_composite_state.
+            self.close();
+            let node = self.node(state);
+            self.check_starting(state, &node);
+        }
+    }
+
+    fn edge(&mut self, src: &str, tgt: &str, label: &str) {
+        if label.is_empty() {
+            self.line(&format!("{src} --> {tgt}"));
+        } else {
+            self.line(&format!("{src} --> {tgt} : {}", escape(label)));
+        }
+    }
+
+    fn transition(&mut self, transition: &dyn Transition, label: &str) {
+        let fsm = self
+            .frames
+            .last()
+            .expect("transitions are visited through their FSM")
+            .fsm;
+        let src = node_id(fsm, transition.src());
+        let tgt = node_id(fsm, transition.target());
+        self.edge(&src, &tgt, label);
+    }
+
+    fn visit_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM, label: &str) {
+        let id = self.fsm_count;
+        self.fsm_count += 1;
+        let top = self.open_diagram();
+        if top {
+            self.line(&format!("[*] --> f{id}"));
+            self.open_composite(id, label);
+        }
+        for (i, conc_fsm) in fsm.conc_fsms().into_iter().enumerate() {
+            if i > 0 {
+                self.line("--");
+            }
+            conc_fsm.accept_visitor(self);
+        }
+        if top {
+            self.close();
+            self.depth -= 1;
+        }
+    }
+}
+
+fn node_id(fsm: usize, state: StateId) -> String {
+    format!("f{fsm}_s{}", state.index())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', " ")
+}
+
+impl VisitorFSM for MermaidVisitor {
+    fn visit_fsm(&mut self, fsm: &dyn FSM) {
+        let id = self.fsm_count;
+        self.fsm_count += 1;
+        let top = self.open_diagram();
+        self.frames.push(Frame {
+            fsm: id,
+            starting: fsm.starting_state().id(),
+        });
+        let states = fsm.states();
+        for state in top_level_states(&states) {
+            self.visit_nested_state(&states, state);
+        }
+        for state in fsm.states() {
+            for transition in state
+                .as_output()
+                .map(|s| s.transitions())
+                .unwrap_or_default()
+            {
+                transition.accept_visitor(self);
+            }
+        }
+        self.frames.pop();
+        if top {
+            self.depth -= 1;
+        }
+    }
+
+    fn visit_and_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        self.visit_concurrent_fsm(fsm, "AND");
+    }
+
+    fn visit_xor_concurrent_fsm(&mut self, fsm: &dyn ConcurrentFSM) {
+        self.visit_concurrent_fsm(fsm, "XOR");
+    }
+
+    fn visit_state(&mut self, state: &dyn OutputState) {
+        self.state(state);
+    }
+
+    fn visit_init_state(&mut self, state: &dyn OutputState) {
+        let node = self.state(state);
+        self.edge("[*]", &node, "");
+    }
+
+    fn visit_cancelling_state(&mut self, state: &dyn InputState) {
+        let node = self.state(state);
+        self.line(&format!("class {node} cancelling"));
+        self.edge(&node, "[*]", "");
+    }
+
+    fn visit_terminal_state(&mut self, state: &dyn InputState) {
+        let node = self.state(state);
+        self.edge(&node, "[*]", "");
+    }
+
+    /// Opens the block of the state, closed once its children are visited.
+    fn visit_composite_state(&mut self, state: &dyn CompositeState) {
+        let node = self.node(state);
+        self.line(&format!("state \"{}\" as {node} {{", escape(state.name())));
+        self.depth += 1;
+        if let Some(child) = state.initial_child() {
+            let frame = self
+                .frames
+                .last()
+                .expect("states are visited through their FSM");
+            let child = node_id(frame.fsm, child);
+            self.edge("[*]", &child, "");
+        }
+    }
+
+    fn visit_history_state(&mut self, state: &dyn InputState) {
+        self.state(state);
+    }
+
+    fn visit_transition(&mut self, transition: &dyn Transition) {
+        self.transition(transition, &transition_label(transition));
+    }
+
+    fn visit_timeout_transition(&mut self, transition: &dyn Transition) {
+        self.transition(transition, &timeout_label(transition));
+    }
+
+    fn visit_sub_fsm_transition(&mut self, transition: &dyn Transition) {
+        let Some(inner) = transition.inner_fsm() else {
+            return;
+        };
+        let fsm = self
+            .frames
+            .last()
+            .expect("transitions are visited through their FSM")
+            .fsm;
+        let inner_id = self.fsm_count;
+        let composite = format!("f{inner_id}");
+        self.edge(
+            &node_id(fsm, transition.src()),
+            &composite,
+            &transition_label(transition),
+        );
+        self.edge(&composite, &node_id(fsm, transition.target()), "");
+        self.open_composite(inner_id, "sub-FSM");
+        inner.accept_visitor(self);
+        self.close();
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::dom::{EventType, MouseEvent, MouseEventType};
+use crate::fsm::StateId;
+use alloc::format;
+
+use super::filtered_transition::{filtered_transition_impl, FilteredTransition};
+use super::{TypedAction, TypedGuard};
+
+/// A transition on mouse events, that can be restricted to a button.
+/// Its action and its guard are given the processed events as mouse events.
+/// # Category
+/// FSM
+pub struct MouseTransition(FilteredTransition);
+
+impl MouseTransition {
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        event_types: impl IntoIterator<Item = MouseEventType>,
+        action: Option<TypedAction<dyn MouseEvent>>,
+        guard: Option<TypedGuard<dyn MouseEvent>>,
+    ) -> Self {
+        Self(FilteredTransition::with_family(
+            src_state,
+            tgt_state,
+            event_types.into_iter().map(EventType::Mouse).collect(),
+            action,
+            guard,
+            |event| event.as_mouse_event(),
+        ))
+    }
+
+    /// Restricts the transition to the events of the given button
+    /// (0 for the main button, 1 for the auxiliary one, 2 for the secondary one).
+    pub fn with_button(mut self, button: i16) -> Self {
+        self.0.add_filter::<dyn MouseEvent>(
+            format!("button {button}"),
+            |event| event.as_mouse_event(),
+            move |event| event.button() == button,
+        );
+        self
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    pub fn with_guard_label(mut self, label: &str) -> Self {
+        self.0.set_guard_label(label);
+        self
+    }
+}
+
+filtered_transition_impl!(MouseTransition);
@@ -0,0 +1,85 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{FSMError, StateId, Transition, VisitorFSM, FSM};
+
+/// A transition that enters an inner FSM.
+/// The FSM that contains the transition gives the events to an instance of the inner FSM:
+/// it reaches the target state of the transition when this instance terminates,
+/// and cancels when this instance cancels. The inner FSM itself is never run.
+/// # Category
+/// FSM
+pub struct SubFSMTransition {
+    src: StateId,
+    tgt: StateId,
+    fsm: Box<dyn FSM>,
+    accepted_events: HashSet<EventType>,
+}
+
+impl SubFSMTransition {
+    pub fn new(src_state: StateId, tgt_state: StateId, mut fsm: Box<dyn FSM>) -> Self {
+        fsm.set_inner(true);
+        let accepted_events = fsm.accepted_events();
+        Self {
+            src: src_state,
+            tgt: tgt_state,
+            fsm,
+            accepted_events,
+        }
+    }
+}
+
+impl Transition for SubFSMTransition {
+    fn src(&self) -> StateId {
+        self.src
+    }
+
+    fn target(&self) -> StateId {
+        self.tgt
+    }
+
+    /// The inner FSM processes the events, not the transition.
+    fn execute(&self, _event: &dyn Event) -> Result<Option<StateId>, FSMError> {
+        Ok(None)
+    }
+
+    fn guard(&self, _event: &dyn Event) -> Result<bool, FSMError> {
+        Ok(true)
+    }
+
+    /// The transition accepts the events that make the inner FSM leave its initial state.
+    fn accept(&self, event: &dyn Event) -> bool {
+        self.fsm.accepts(event)
+    }
+
+    fn inner_fsm(&self) -> Option<&dyn FSM> {
+        Some(self.fsm.as_ref())
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_sub_fsm_transition(self);
+    }
+
+    fn get_accepted_events(&self) -> &HashSet<EventType> {
+        &self.accepted_events
+    }
+
+    fn uninstall(&mut self) {
+        self.fsm.uninstall();
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::fsm::{InputState, State, StateId, StateKind, VisitorFSM};
+use alloc::string::String;
+use alloc::string::ToString;
+
+/// A state that terminates the FSM when entered.
+/// # Category
+/// FSM
+pub struct TerminalState {
+    name: String,
+    id: StateId,
+}
+
+impl TerminalState {
+    pub fn new(name: &str, id: StateId) -> Self {
+        Self {
+            name: name.to_string(),
+            id,
+        }
+    }
+}
+
+impl State for TerminalState {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> StateId {
+        self.id
+    }
+
+    fn kind(&self) -> StateKind {
+        StateKind::Terminal
+    }
+
+    fn as_input(&self) -> Option<&dyn InputState> {
+        Some(self)
+    }
+
+    fn uninstall(&mut self) {}
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_terminal_state(self);
+    }
+}
+
+impl InputState for TerminalState {}
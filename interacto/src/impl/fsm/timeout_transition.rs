@@ -0,0 +1,125 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{FSMError, StateId, Transition, VisitorFSM};
+
+/// The action executed by a timeout transition.
+/// An error prevents the transition from being executed.
+pub type TimeoutAction = Box<dyn Fn() -> Result<(), FSMError>>;
+
+/// Computes the delay (in ms) of a timeout transition when its source state is entered.
+/// As the actions of the transitions, it reads the current data of the interaction
+/// by capturing it (eg an `Rc<RefCell<_>>` that the actions update).
+pub type TimeoutDelay = Box<dyn Fn() -> u64>;
+
+/// A transition executed when its delay elapsed after having entered its source state.
+/// Its delay can be fixed or computed each time its source state is entered.
+/// # Category
+/// FSM
+pub struct TimeoutTransition {
+    src: StateId,
+    tgt: StateId,
+    timeout: TimeoutDelay,
+    /// The delay, if it is fixed rather than computed.
+    fixed_delay: Option<u64>,
+    action: TimeoutAction,
+    accepted_events: HashSet<EventType>,
+}
+
+impl TimeoutTransition {
+    /// Creates a timeout transition whose delay (in ms) is computed by `timeout`
+    /// each time its source state is entered.
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        timeout: TimeoutDelay,
+        action: Option<TimeoutAction>,
+    ) -> Self {
+        Self {
+            src: src_state,
+            tgt: tgt_state,
+            timeout,
+            fixed_delay: None,
+            action: action.unwrap_or_else(|| Box::new(|| Ok(()))),
+            accepted_events: HashSet::new(),
+        }
+    }
+
+    /// Creates a timeout transition with a fixed delay (in ms).
+    pub fn with_delay(
+        src_state: StateId,
+        tgt_state: StateId,
+        delay: u64,
+        action: Option<TimeoutAction>,
+    ) -> Self {
+        Self {
+            fixed_delay: Some(delay),
+            ..Self::new(src_state, tgt_state, Box::new(move || delay), action)
+        }
+    }
+}
+
+impl Transition for TimeoutTransition {
+    fn src(&self) -> StateId {
+        self.src
+    }
+
+    fn target(&self) -> StateId {
+        self.tgt
+    }
+
+    fn execute(&self, _event: &dyn Event) -> Result<Option<StateId>, FSMError> {
+        Ok(None)
+    }
+
+    fn guard(&self, _event: &dyn Event) -> Result<bool, FSMError> {
+        Ok(true)
+    }
+
+    fn is_timeout(&self) -> bool {
+        true
+    }
+
+    fn timeout_delay(&self) -> Option<u64> {
+        Some((self.timeout)())
+    }
+
+    fn fixed_timeout_delay(&self) -> Option<u64> {
+        self.fixed_delay
+    }
+
+    fn execute_on_timeout(&self) -> Result<Option<StateId>, FSMError> {
+        (self.action)()?;
+        Ok(Some(self.tgt))
+    }
+
+    /// A timeout transition does not process events.
+    fn accept(&self, _event: &dyn Event) -> bool {
+        false
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_timeout_transition(self);
+    }
+
+    fn get_accepted_events(&self) -> &HashSet<EventType> {
+        &self.accepted_events
+    }
+
+    fn uninstall(&mut self) {}
+}
@@ -0,0 +1,64 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::dom::{EventType, TouchEvent, TouchEventType};
+use crate::fsm::StateId;
+use alloc::format;
+
+use super::filtered_transition::{filtered_transition_impl, FilteredTransition};
+use super::{TypedAction, TypedGuard};
+
+/// A transition on touch events, that can be restricted to a touch point.
+/// Its action and its guard are given the processed events as touch events.
+/// # Category
+/// FSM
+pub struct TouchTransition(FilteredTransition);
+
+impl TouchTransition {
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        event_types: impl IntoIterator<Item = TouchEventType>,
+        action: Option<TypedAction<dyn TouchEvent>>,
+        guard: Option<TypedGuard<dyn TouchEvent>>,
+    ) -> Self {
+        Self(FilteredTransition::with_family(
+            src_state,
+            tgt_state,
+            event_types.into_iter().map(EventType::Touch).collect(),
+            action,
+            guard,
+            |event| event.as_touch_event(),
+        ))
+    }
+
+    /// Restricts the transition to the events in which the touch point
+    /// of the given identifier changed.
+    pub fn with_touch_id(mut self, identifier: i32) -> Self {
+        self.0.add_filter::<dyn TouchEvent>(
+            format!("touch {identifier}"),
+            |event| event.as_touch_event(),
+            move |event| event.changed_touch(identifier).is_some(),
+        );
+        self
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    pub fn with_guard_label(mut self, label: &str) -> Self {
+        self.0.set_guard_label(label);
+        self
+    }
+}
+
+filtered_transition_impl!(TouchTransition);
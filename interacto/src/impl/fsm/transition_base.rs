@@ -0,0 +1,123 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::string::ToString;
+
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{FSMError, StateId, Transition, VisitorFSM};
+
+/// The action executed by a transition.
+/// An error prevents the transition from being executed.
+pub type TransitionAction = Box<dyn Fn(&dyn Event) -> Result<(), FSMError>>;
+
+/// The guard that a transition must respect to be executed.
+pub type TransitionGuard = Box<dyn Fn(&dyn Event) -> Result<bool, FSMError>>;
+
+/// The base implementation of a transition: it is executed when
+/// it accepts the type of the processed event and when its guard is respected.
+/// # Category
+/// FSM
+pub struct TransitionBase {
+    src: StateId,
+    tgt: StateId,
+    accepted_events: HashSet<EventType>,
+    action: TransitionAction,
+    guard: TransitionGuard,
+    guarded: bool,
+    guard_label: Option<String>,
+}
+
+impl TransitionBase {
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        accepted_events: HashSet<EventType>,
+        action: Option<TransitionAction>,
+        guard: Option<TransitionGuard>,
+    ) -> Self {
+        let guarded = guard.is_some();
+        let guard_label = guarded.then(|| "guard".to_string());
+        let action_fn = action.unwrap_or_else(|| Box::new(|_: &dyn Event| Ok(())));
+        let guard_fn = guard.unwrap_or_else(|| Box::new(|_: &dyn Event| Ok(true)));
+        Self {
+            src: src_state,
+            tgt: tgt_state,
+            accepted_events,
+            action: action_fn,
+            guard: guard_fn,
+            guarded,
+            guard_label,
+        }
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    /// Guards without label are described as `guard`.
+    pub fn with_guard_label(mut self, label: &str) -> Self {
+        self.set_guard_label(label);
+        self
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    pub fn set_guard_label(&mut self, label: &str) {
+        self.guard_label = Some(label.to_string());
+    }
+}
+
+impl Transition for TransitionBase {
+    fn src(&self) -> StateId {
+        self.src
+    }
+
+    fn target(&self) -> StateId {
+        self.tgt
+    }
+
+    fn execute(&self, event: &dyn Event) -> Result<Option<StateId>, FSMError> {
+        if self.accept(event) && self.guard(event)? {
+            (self.action)(event)?;
+            Ok(Some(self.tgt))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn guard(&self, event: &dyn Event) -> Result<bool, FSMError> {
+        (self.guard)(event)
+    }
+
+    fn guard_label(&self) -> Option<&str> {
+        self.guard_label.as_deref()
+    }
+
+    fn is_guarded(&self) -> bool {
+        self.guarded
+    }
+
+    fn accept(&self, event: &dyn Event) -> bool {
+        self.accepted_events.contains(&event.event_type())
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_transition(self);
+    }
+
+    fn get_accepted_events(&self) -> &HashSet<EventType> {
+        &self.accepted_events
+    }
+
+    fn uninstall(&mut self) {}
+}
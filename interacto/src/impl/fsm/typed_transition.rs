@@ -0,0 +1,116 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+
+use crate::collections::HashSet;
+use crate::dom::{Event, EventType};
+use crate::fsm::{FSMError, StateId, Transition, VisitorFSM};
+
+use super::{TransitionAction, TransitionBase, TransitionGuard};
+
+/// The action executed by a transition typed with the event type `E`.
+pub type TypedAction<E> = Box<dyn Fn(&E) -> Result<(), FSMError>>;
+
+/// The guard of a transition typed with the event type `E`.
+pub type TypedGuard<E> = Box<dyn Fn(&E) -> Result<bool, FSMError>>;
+
+/// A transition that processes events of the concrete type `E` only:
+/// its action and its guard are given the processed event as an `E`.
+/// Events of other types are not accepted, even if their `EventType` is.
+/// # Category
+/// FSM
+pub struct TypedTransition<E: Event + 'static> {
+    base: TransitionBase,
+    event: PhantomData<fn(&E)>,
+}
+
+impl<E: Event + 'static> TypedTransition<E> {
+    pub fn new(
+        src_state: StateId,
+        tgt_state: StateId,
+        accepted_events: HashSet<EventType>,
+        action: Option<TypedAction<E>>,
+        guard: Option<TypedGuard<E>>,
+    ) -> Self {
+        let action: Option<TransitionAction> = action.map(|action| -> TransitionAction {
+            Box::new(move |event| event.downcast_ref::<E>().map_or(Ok(()), &action))
+        });
+        let guard: Option<TransitionGuard> = guard.map(|guard| -> TransitionGuard {
+            Box::new(move |event| event.downcast_ref::<E>().map_or(Ok(false), &guard))
+        });
+        Self {
+            base: TransitionBase::new(src_state, tgt_state, accepted_events, action, guard),
+            event: PhantomData,
+        }
+    }
+
+    /// Sets the label that describes the guard of the transition.
+    pub fn with_guard_label(mut self, label: &str) -> Self {
+        self.base = self.base.with_guard_label(label);
+        self
+    }
+}
+
+impl<E: Event + 'static> Transition for TypedTransition<E> {
+    fn src(&self) -> StateId {
+        self.base.src()
+    }
+
+    fn target(&self) -> StateId {
+        self.base.target()
+    }
+
+    fn execute(&self, event: &dyn Event) -> Result<Option<StateId>, FSMError> {
+        if event.is::<E>() {
+            self.base.execute(event)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn guard(&self, event: &dyn Event) -> Result<bool, FSMError> {
+        Ok(event.is::<E>() && self.base.guard(event)?)
+    }
+
+    fn guard_label(&self) -> Option<&str> {
+        self.base.guard_label()
+    }
+
+    fn is_guarded(&self) -> bool {
+        self.base.is_guarded()
+    }
+
+    /// The events of other concrete types than `E` are filtered out.
+    fn has_filter(&self) -> bool {
+        true
+    }
+
+    fn accept(&self, event: &dyn Event) -> bool {
+        event.is::<E>() && self.base.accept(event)
+    }
+
+    fn accept_visitor(&self, visitor: &mut dyn VisitorFSM) {
+        visitor.visit_transition(self);
+    }
+
+    fn get_accepted_events(&self) -> &HashSet<EventType> {
+        self.base.get_accepted_events()
+    }
+
+    fn uninstall(&mut self) {
+        self.base.uninstall();
+    }
+}
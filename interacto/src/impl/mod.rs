@@ -1 +1,2 @@
-mod fsm;
\ No newline at end of file
+pub mod clock;
+pub mod fsm;
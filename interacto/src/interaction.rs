@@ -22,16 +22,16 @@ pub trait InteractionData {}
 /// Visitor interaction trait.
 pub trait VisitorInteraction {}
 
-/// Finite State Machine trait.
-
 /// The concept of user interaction.
 pub trait Interaction<D: InteractionData> {
     /// Sets whether the user interaction will stop immediately the propagation
     /// of events processed by this user interaction to others listeners.
+    /// Out of a browser, see `dom::DispatchContext::stop_immediate_propagation`.
     fn set_stop_immediate_propagation(&mut self, stop: bool);
 
     /// Sets whether the default behavior associated to the event
     /// will be executed.
+    /// Out of a browser, see `dom::DispatchContext::prevent_default`.
     fn set_prevent_default(&mut self, prevent: bool);
 
     /// The FSM of the user interaction.
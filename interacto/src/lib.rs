@@ -11,19 +11,41 @@
  * You should have received a copy of the GNU General Public License
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
+// Without the `std` feature, the FSMs, commands and undo are available on top
+// of `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// The paths the exported macros expand to, so that they also work in crates
+// that do not link `std`.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+}
+
 pub mod command;
 pub mod undoble;
+#[cfg(feature = "std")]
 pub mod anoncmd;
-pub mod binder;
+// The binder (src/binder.rs), which builds bindings fluently, is still being ported
+// and does not build yet: it stays out of the module tree until it does.
+#[cfg(feature = "std")]
 pub mod binding;
+#[cfg(feature = "std")]
 pub mod checker;
+#[cfg(feature = "std")]
 pub mod interaction;
+pub mod collections;
 pub mod fsm;
+pub mod clock;
 pub mod dom;
 pub mod r#impl;
 pub mod undohistory;
 pub mod undo;
 pub mod linearhistory;
+#[cfg(all(feature = "std", any(test, feature = "proptest", feature = "arbitrary")))]
+pub mod testing;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test;
\ No newline at end of file
@@ -12,7 +12,13 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{undoble::Undoable, undohistory::{UndoHistory, UndoHistoryBase}};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::{
+    undoble::Undoable,
+    undohistory::{UndoHistory, UndoHistoryBase},
+};
 
 pub struct LinearHistoryImpl {
     /**
@@ -44,46 +50,70 @@ impl LinearHistoryImpl {
     }
 }
 
+impl Default for LinearHistoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UndoHistoryBase for LinearHistoryImpl {
-    fn undo(&self) {
-        todo!()
+    fn undo(&mut self) {
+        if let Some(mut undoable) = self.undos.pop() {
+            undoable.undo();
+            self.redos.push(undoable);
+        }
     }
 
-    fn redo(&self) {
-        todo!()
+    fn redo(&mut self) {
+        if let Some(mut undoable) = self.redos.pop() {
+            undoable.redo();
+            self.undos.push(undoable);
+        }
     }
 
-    fn clear(&self) {
-        todo!()
+    fn clear(&mut self) {
+        self.undos.clear();
+        self.redos.clear();
     }
 
-    fn add(&self, undoable: Box<dyn Undoable>) {
-        todo!()
+    fn add(&mut self, undoable: Box<dyn Undoable>) {
+        if self.size_max > 0 {
+            if self.undos.len() as u64 == self.size_max {
+                self.undos.remove(0);
+            }
+            self.undos.push(undoable);
+            self.redos.clear();
+        }
     }
 
     fn get_last_undo(&self) -> Option<&dyn Undoable> {
-        todo!()
+        self.undos.last().map(|undoable| undoable.as_ref())
     }
 
-    fn get_last_redo(&self) -> Option<Box<dyn Undoable>> {
-        todo!()
+    fn get_last_redo(&self) -> Option<&dyn Undoable> {
+        self.redos.last().map(|undoable| undoable.as_ref())
     }
 }
 
 impl UndoHistory for LinearHistoryImpl {
-    fn get_undo(&self) -> Vec<Box<dyn Undoable>> {
-        todo!()
+    fn get_undo(&self) -> &[Box<dyn Undoable>] {
+        &self.undos
     }
 
-    fn get_redo(&self) -> Vec<Box<dyn Undoable>> {
-        todo!()
+    fn get_redo(&self) -> &[Box<dyn Undoable>] {
+        &self.redos
     }
 
     fn get_size_max(&self) -> u64 {
-        todo!()
+        self.size_max
     }
 
     fn set_size_max(&mut self, max: u64) {
-        todo!()
+        let removed = self
+            .undos
+            .len()
+            .saturating_sub(max.try_into().unwrap_or(usize::MAX));
+        self.undos.drain(..removed);
+        self.size_max = max;
     }
 }
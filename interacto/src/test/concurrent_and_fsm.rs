@@ -0,0 +1,183 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use crate::dom::{EventType, TouchEventType};
+use crate::fsm::{ConcurrentFSM, FSM};
+use crate::r#impl::fsm::{ConcurrentAndFSM, FSMImpl};
+
+use super::fsm::{transition, StubEvent, StubFSMHandler};
+
+fn touch_down() -> StubEvent {
+    StubEvent::new(EventType::Touch(TouchEventType::Touchstart))
+}
+
+fn touch_up() -> StubEvent {
+    StubEvent::new(EventType::Touch(TouchEventType::Touchend))
+}
+
+/// init -down-> pressed -up-> released (terminal)
+///              pressed -key-> cancelled (cancelling)
+fn press_release(down: &StubEvent, up: &StubEvent) -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    let released = fsm.add_terminal_state("released", false);
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    fsm.add_transition(transition(init, pressed, down.event_type));
+    fsm.add_transition(transition(pressed, released, up.event_type));
+    fsm.add_transition(transition(pressed, cancelled, StubEvent::key().event_type));
+    fsm
+}
+
+fn two_fsms() -> ConcurrentAndFSM<FSMImpl> {
+    ConcurrentAndFSM::new(vec![
+        press_release(&StubEvent::press(), &StubEvent::release()),
+        press_release(&touch_down(), &touch_up()),
+    ])
+}
+
+#[test]
+fn sub_fsms_are_inner() {
+    let fsm = two_fsms();
+    assert_eq!(fsm.conc_fsms().len(), 2);
+    assert_eq!(fsm.conc_fsms().iter().all(|f| f.is_inner()), true);
+}
+
+#[test]
+fn not_started_at_creation() {
+    assert_eq!(two_fsms().started(), false);
+}
+
+#[test]
+fn starts_when_first_fsm_starts() {
+    let mut fsm = two_fsms();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.process(&StubEvent::press()), true);
+    assert_eq!(fsm.started(), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update"]);
+}
+
+#[test]
+fn second_fsm_starting_updates() {
+    let mut fsm = two_fsms();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&touch_down());
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "update"]);
+}
+
+#[test]
+fn does_not_stop_while_one_fsm_runs() {
+    let mut fsm = two_fsms();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.started(), true);
+    assert_eq!(handler.borrow().calls.contains(&"stop"), false);
+}
+
+#[test]
+fn stops_when_all_fsms_stopped() {
+    let mut fsm = two_fsms();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::release());
+    fsm.process(&touch_up());
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "update", "stop"]
+    );
+}
+
+#[test]
+fn cancels_when_one_fsm_cancels() {
+    let mut fsm = two_fsms();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::key());
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "update", "cancel"]
+    );
+    assert_eq!(fsm.conc_fsms().iter().any(|f| f.started()), false);
+}
+
+#[test]
+fn cancelling_reinits_the_other_fsms() {
+    let mut fsm = ConcurrentAndFSM::new(vec![
+        press_release(&StubEvent::press(), &StubEvent::release()),
+        press_release(&touch_down(), &StubEvent::key()),
+    ]);
+    fsm.process(&StubEvent::press());
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::key());
+    let subs = fsm.conc_fsms();
+    assert_eq!(subs[1].current_state().id(), subs[1].init_state().id());
+}
+
+#[test]
+fn secondary_fsm_not_required_for_stopping() {
+    let mut fsm = ConcurrentAndFSM::with_secondary(
+        vec![press_release(&StubEvent::press(), &StubEvent::release())],
+        vec![press_release(&touch_down(), &touch_up())],
+    );
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "update", "stop"]
+    );
+    assert_eq!(fsm.secondary_fsms().len(), 1);
+}
+
+#[test]
+fn conc_fsms_include_secondary_fsms() {
+    let fsm = ConcurrentAndFSM::with_secondary(
+        vec![press_release(&StubEvent::press(), &StubEvent::release())],
+        vec![press_release(&touch_down(), &touch_up())],
+    );
+    assert_eq!(fsm.conc_fsms().len(), 2);
+    assert_eq!(fsm.required_fsms().len(), 1);
+    assert_eq!(fsm.conc_fsms()[1].is_inner(), true);
+}
+
+#[test]
+fn handler_cancelling_on_start_reinits_fsms() {
+    let mut fsm = two_fsms();
+    let handler = StubFSMHandler::new();
+    handler.borrow_mut().cancel_on_start = true;
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    assert_eq!(handler.borrow().calls, vec!["start", "cancel"]);
+    assert_eq!(fsm.conc_fsms()[0].started(), false);
+}
+
+#[test]
+#[should_panic]
+fn requires_one_fsm() {
+    ConcurrentAndFSM::<FSMImpl>::new(Vec::new());
+}
@@ -0,0 +1,210 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::{Event, EventType, MouseEventType, TouchEventType};
+use crate::fsm::{ConcurrentFSM, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{ConcurrentXorFSM, FSMImpl, TimeoutTransition, TransitionBase};
+
+use super::fsm::{transition, StubEvent, StubFSMHandler};
+
+fn click() -> StubEvent {
+    StubEvent::new(EventType::Mouse(MouseEventType::Click))
+}
+
+fn touch_down() -> StubEvent {
+    StubEvent::new(EventType::Touch(TouchEventType::Touchstart))
+}
+
+fn touch_up() -> StubEvent {
+    StubEvent::new(EventType::Touch(TouchEventType::Touchend))
+}
+
+/// init -click-> clicked (terminal)
+fn click_fsm() -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let clicked = fsm.add_terminal_state("clicked", false);
+    fsm.add_transition(transition(init, clicked, click().event_type));
+    fsm
+}
+
+/// init -down-> touched -up-> tapped (terminal)
+///              touched -key-> cancelled (cancelling)
+fn tap_fsm() -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let touched = fsm.add_std_state("touched", false);
+    let tapped = fsm.add_terminal_state("tapped", false);
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    fsm.add_transition(transition(init, touched, touch_down().event_type));
+    fsm.add_transition(transition(touched, tapped, touch_up().event_type));
+    fsm.add_transition(transition(touched, cancelled, StubEvent::key().event_type));
+    fsm
+}
+
+fn click_or_tap() -> ConcurrentXorFSM<FSMImpl> {
+    ConcurrentXorFSM::new(vec![click_fsm(), tap_fsm()])
+}
+
+#[test]
+fn no_current_fsm_at_creation() {
+    let fsm = click_or_tap();
+    assert_eq!(fsm.current_fsm().is_none(), true);
+    assert_eq!(fsm.conc_fsms().len(), 2);
+}
+
+#[test]
+fn first_alternative_runs() {
+    let mut fsm = click_or_tap();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.process(&click()), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "stop"]);
+    assert_eq!(fsm.current_fsm().is_none(), true);
+}
+
+#[test]
+fn second_alternative_runs() {
+    let mut fsm = click_or_tap();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&touch_down());
+    assert_eq!(fsm.started(), true);
+    fsm.process(&touch_up());
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+    assert_eq!(fsm.started(), false);
+}
+
+#[test]
+fn other_alternatives_ignored_while_one_runs() {
+    let mut fsm = click_or_tap();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&touch_down());
+    assert_eq!(fsm.process(&click()), false);
+    assert_eq!(handler.borrow().calls, vec!["start", "update"]);
+}
+
+#[test]
+fn first_fsm_processing_event_wins() {
+    let mut fsm = ConcurrentXorFSM::new(vec![tap_fsm(), tap_fsm()]);
+    fsm.process(&touch_down());
+    let subs = fsm.conc_fsms();
+    assert_eq!(subs[0].started(), true);
+    assert_eq!(subs[1].started(), false);
+    assert_eq!(subs[1].current_state().id(), subs[1].init_state().id());
+}
+
+#[test]
+fn losing_alternatives_do_not_process_event() {
+    let mut loser = tap_fsm();
+    let loser_handler = StubFSMHandler::new();
+    loser.add_handler(loser_handler.clone());
+    let mut fsm = ConcurrentXorFSM::new(vec![tap_fsm(), loser]);
+    fsm.process(&touch_down());
+    fsm.process(&touch_up());
+    assert_eq!(loser_handler.borrow().calls.is_empty(), true);
+}
+
+#[test]
+fn alternative_rejecting_event_is_not_elected() {
+    let mut guarded = FSMImpl::new();
+    let init = guarded.init_state().id();
+    let clicked = guarded.add_terminal_state("clicked", false);
+    guarded.add_transition(Box::new(TransitionBase::new(
+        init,
+        clicked,
+        HashSet::from([click().event_type]),
+        None,
+        Some(Box::new(|_: &dyn Event| Ok(false))),
+    )));
+    let mut fsm = ConcurrentXorFSM::new(vec![guarded, click_fsm()]);
+    assert_eq!(fsm.accepts(&click()), true);
+    assert_eq!(fsm.process(&click()), true);
+    assert_eq!(fsm.conc_fsms()[0].current_state().id(), init);
+}
+
+#[test]
+fn failing_guard_of_alternative_reported_once() {
+    let guard_calls = Rc::new(Cell::new(0));
+    let calls = guard_calls.clone();
+    let mut failing = FSMImpl::new();
+    let init = failing.init_state().id();
+    let clicked = failing.add_terminal_state("clicked", false);
+    failing.add_transition(Box::new(TransitionBase::new(
+        init,
+        clicked,
+        HashSet::from([click().event_type]),
+        None,
+        Some(Box::new(move |_: &dyn Event| {
+            calls.set(calls.get() + 1);
+            Err("no guard".into())
+        })),
+    )));
+    let mut fsm = ConcurrentXorFSM::new(vec![failing, click_fsm()]);
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.process(&click()), true);
+    assert_eq!(guard_calls.get(), 1);
+    assert_eq!(handler.borrow().calls, vec!["error", "start", "stop"]);
+    assert_eq!(handler.borrow().errors[0].to_string(), "no guard");
+}
+
+#[test]
+fn cancels_when_current_fsm_cancels() {
+    let mut fsm = click_or_tap();
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::key());
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "cancel"]);
+    assert_eq!(fsm.current_fsm().is_none(), true);
+    assert_eq!(fsm.process(&click()), true);
+}
+
+#[test]
+fn alternatives_open_again_when_current_fsm_reinits_silently() {
+    let mut tap = tap_fsm();
+    let tapped = tap.states()[2].id();
+    tap.set_starting_state(tapped);
+    let mut fsm = ConcurrentXorFSM::new(vec![click_fsm(), tap]);
+    fsm.process(&touch_down());
+    fsm.process(&StubEvent::key());
+    assert_eq!(fsm.current_fsm().is_none(), true);
+}
+
+#[test]
+fn timeout_of_current_fsm() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut held = FSMImpl::with_clock(clock.clone());
+    let init = held.init_state().id();
+    let touched = held.add_std_state("touched", false);
+    let timeouted = held.add_terminal_state("timeouted", false);
+    held.add_transition(transition(init, touched, touch_down().event_type));
+    held.add_transition(Box::new(TimeoutTransition::with_delay(
+        touched, timeouted, 100, None,
+    )));
+    let mut fsm = ConcurrentXorFSM::new(vec![click_fsm(), held]);
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&touch_down());
+    assert_eq!(fsm.next_timeout(), Some(100));
+    clock.advance(100);
+    assert_eq!(fsm.check_timeout(), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
@@ -0,0 +1,337 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::dom::{
+    DispatchContext, Event, EventDispatcher, EventPhase, EventType, FocusEventType, KeyEventType,
+    MouseEventType, Node, NodeId, NodeTree,
+};
+use crate::test::fsm::StubEvent;
+
+const ROOT: NodeId = NodeId(1);
+const PANEL: NodeId = NodeId(2);
+const BUTTON: NodeId = NodeId(3);
+const OTHER: NodeId = NodeId(4);
+
+struct StubNode {
+    id: NodeId,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl Node for StubNode {
+    fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+struct StubTree {
+    nodes: HashMap<NodeId, StubNode>,
+}
+
+impl StubTree {
+    /// root -> (panel -> button), other
+    fn new() -> Self {
+        let mut tree = Self {
+            nodes: HashMap::new(),
+        };
+        tree.add(ROOT, None, vec![PANEL, OTHER]);
+        tree.add(PANEL, Some(ROOT), vec![BUTTON]);
+        tree.add(BUTTON, Some(PANEL), vec![]);
+        tree.add(OTHER, Some(ROOT), vec![]);
+        tree
+    }
+
+    fn add(&mut self, id: NodeId, parent: Option<NodeId>, children: Vec<NodeId>) {
+        self.nodes.insert(
+            id,
+            StubNode {
+                id,
+                parent,
+                children,
+            },
+        );
+    }
+}
+
+impl NodeTree for StubTree {
+    fn node(&self, id: NodeId) -> Option<&dyn Node> {
+        self.nodes.get(&id).map(|node| node as &dyn Node)
+    }
+}
+
+type Calls = Rc<RefCell<Vec<(&'static str, NodeId, EventPhase)>>>;
+
+fn click() -> StubEvent {
+    StubEvent::new(EventType::Mouse(MouseEventType::Click))
+}
+
+fn logger(
+    calls: &Calls,
+    name: &'static str,
+) -> impl FnMut(&dyn Event, &mut DispatchContext) + 'static {
+    let calls = calls.clone();
+    move |_, context| {
+        calls
+            .borrow_mut()
+            .push((name, context.current_target(), context.phase()))
+    }
+}
+
+fn dispatcher_on_all_nodes(calls: &Calls, event_type: EventType) -> EventDispatcher {
+    let mut dispatcher = EventDispatcher::new();
+    for node in [ROOT, PANEL, BUTTON, OTHER] {
+        dispatcher.add_listener(node, event_type, true, logger(calls, "capture"));
+        dispatcher.add_listener(node, event_type, false, logger(calls, "bubble"));
+    }
+    dispatcher
+}
+
+#[test]
+fn propagation_path() {
+    let tree = StubTree::new();
+    assert_eq!(tree.propagation_path(BUTTON), vec![BUTTON, PANEL, ROOT]);
+    assert_eq!(tree.propagation_path(ROOT), vec![ROOT]);
+    assert_eq!(tree.propagation_path(NodeId(42)).is_empty(), true);
+}
+
+#[test]
+fn propagation_path_stops_on_cycle() {
+    let mut tree = StubTree::new();
+    tree.add(ROOT, Some(BUTTON), vec![PANEL, OTHER]);
+    assert_eq!(tree.propagation_path(BUTTON), vec![BUTTON, PANEL, ROOT]);
+}
+
+#[test]
+fn contains() {
+    let tree = StubTree::new();
+    assert_eq!(tree.contains(ROOT, BUTTON), true);
+    assert_eq!(tree.contains(BUTTON, BUTTON), true);
+    assert_eq!(tree.contains(OTHER, BUTTON), false);
+    assert_eq!(tree.contains(BUTTON, ROOT), false);
+}
+
+#[test]
+fn node_navigation() {
+    let tree = StubTree::new();
+    let panel = tree.node(PANEL).unwrap();
+    assert_eq!(panel.parent(), Some(ROOT));
+    assert_eq!(panel.children(), &[BUTTON]);
+    assert_eq!(tree.node(ROOT).unwrap().parent(), None);
+}
+
+#[test]
+fn capture_target_bubble_order() {
+    let calls = Calls::default();
+    let mut dispatcher = dispatcher_on_all_nodes(&calls, click().event_type());
+    let outcome = dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(
+        *calls.borrow(),
+        vec![
+            ("capture", ROOT, EventPhase::Capturing),
+            ("capture", PANEL, EventPhase::Capturing),
+            ("capture", BUTTON, EventPhase::AtTarget),
+            ("bubble", BUTTON, EventPhase::AtTarget),
+            ("bubble", PANEL, EventPhase::Bubbling),
+            ("bubble", ROOT, EventPhase::Bubbling),
+        ]
+    );
+    assert_eq!(outcome.listeners_called, 6);
+    assert_eq!(outcome.default_prevented, false);
+    assert_eq!(outcome.propagation_stopped, false);
+}
+
+#[test]
+fn listeners_of_other_event_types_not_called() {
+    let calls = Calls::default();
+    let mut dispatcher = dispatcher_on_all_nodes(&calls, EventType::Key(KeyEventType::Keydown));
+    let outcome = dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(calls.borrow().is_empty(), true);
+    assert_eq!(outcome.listeners_called, 0);
+}
+
+#[test]
+fn non_bubbling_event() {
+    let focus = StubEvent::new(EventType::Focus(FocusEventType::Focus));
+    let calls = Calls::default();
+    let mut dispatcher = dispatcher_on_all_nodes(&calls, focus.event_type());
+    dispatcher.dispatch(&StubTree::new(), BUTTON, &focus);
+    assert_eq!(
+        *calls.borrow(),
+        vec![
+            ("capture", ROOT, EventPhase::Capturing),
+            ("capture", PANEL, EventPhase::Capturing),
+            ("capture", BUTTON, EventPhase::AtTarget),
+            ("bubble", BUTTON, EventPhase::AtTarget),
+        ]
+    );
+}
+
+#[test]
+fn context_target() {
+    let targets = Rc::new(RefCell::new(Vec::new()));
+    let mut dispatcher = EventDispatcher::new();
+    let seen = targets.clone();
+    dispatcher.add_listener(ROOT, click().event_type(), false, move |_, context| {
+        seen.borrow_mut()
+            .push((context.target(), context.current_target()))
+    });
+    dispatcher.dispatch(&StubTree::new(), OTHER, &click());
+    assert_eq!(*targets.borrow(), vec![(OTHER, ROOT)]);
+}
+
+#[test]
+fn stop_propagation_ends_after_current_node() {
+    let calls = Calls::default();
+    let mut dispatcher = EventDispatcher::new();
+    let event_type = click().event_type();
+    dispatcher.add_listener(PANEL, event_type, true, |_, context| {
+        context.stop_propagation()
+    });
+    dispatcher.add_listener(PANEL, event_type, true, logger(&calls, "panel"));
+    dispatcher.add_listener(BUTTON, event_type, false, logger(&calls, "button"));
+    dispatcher.add_listener(ROOT, event_type, false, logger(&calls, "root"));
+    let outcome = dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(
+        *calls.borrow(),
+        vec![("panel", PANEL, EventPhase::Capturing)]
+    );
+    assert_eq!(outcome.propagation_stopped, true);
+    assert_eq!(outcome.listeners_called, 2);
+}
+
+#[test]
+fn stop_propagation_at_target_capture_skips_target_bubble_listeners() {
+    let calls = Calls::default();
+    let mut dispatcher = EventDispatcher::new();
+    let event_type = click().event_type();
+    dispatcher.add_listener(BUTTON, event_type, false, logger(&calls, "bubble"));
+    dispatcher.add_listener(BUTTON, event_type, true, |_, context| {
+        context.stop_propagation()
+    });
+    dispatcher.add_listener(ROOT, event_type, false, logger(&calls, "root"));
+    let outcome = dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(calls.borrow().is_empty(), true);
+    assert_eq!(outcome.propagation_stopped, true);
+    assert_eq!(outcome.listeners_called, 1);
+}
+
+#[test]
+fn stop_immediate_propagation() {
+    let calls = Calls::default();
+    let mut dispatcher = EventDispatcher::new();
+    let event_type = click().event_type();
+    dispatcher.add_listener(BUTTON, event_type, false, logger(&calls, "first"));
+    dispatcher.add_listener(BUTTON, event_type, false, |_, context| {
+        context.stop_immediate_propagation()
+    });
+    dispatcher.add_listener(BUTTON, event_type, false, logger(&calls, "last"));
+    dispatcher.add_listener(PANEL, event_type, false, logger(&calls, "panel"));
+    let outcome = dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(
+        *calls.borrow(),
+        vec![("first", BUTTON, EventPhase::AtTarget)]
+    );
+    assert_eq!(outcome.propagation_stopped, true);
+    assert_eq!(outcome.listeners_called, 2);
+}
+
+#[test]
+fn prevent_default_does_not_stop_propagation() {
+    let calls = Calls::default();
+    let mut dispatcher = dispatcher_on_all_nodes(&calls, click().event_type());
+    dispatcher.add_listener(BUTTON, click().event_type(), true, |_, context| {
+        context.prevent_default();
+        assert_eq!(context.is_default_prevented(), true);
+        assert_eq!(context.is_propagation_stopped(), false);
+    });
+    let outcome = dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(calls.borrow().len(), 6);
+    assert_eq!(outcome.default_prevented, true);
+    assert_eq!(outcome.propagation_stopped, false);
+}
+
+#[test]
+fn flags_reset_between_dispatches() {
+    let mut dispatcher = EventDispatcher::new();
+    let event_type = click().event_type();
+    dispatcher.add_listener(OTHER, event_type, false, |_, context| {
+        context.prevent_default();
+        context.stop_propagation();
+    });
+    let tree = StubTree::new();
+    assert_eq!(
+        dispatcher
+            .dispatch(&tree, OTHER, &click())
+            .default_prevented,
+        true
+    );
+    let outcome = dispatcher.dispatch(&tree, BUTTON, &click());
+    assert_eq!(outcome.default_prevented, false);
+    assert_eq!(outcome.propagation_stopped, false);
+}
+
+#[test]
+fn remove_listener() {
+    let calls = Calls::default();
+    let mut dispatcher = EventDispatcher::new();
+    let event_type = click().event_type();
+    let id = dispatcher.add_listener(BUTTON, event_type, false, logger(&calls, "removed"));
+    dispatcher.add_listener(BUTTON, event_type, false, logger(&calls, "kept"));
+    assert_eq!(dispatcher.remove_listener(id), true);
+    assert_eq!(dispatcher.remove_listener(id), false);
+    dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(
+        *calls.borrow(),
+        vec![("kept", BUTTON, EventPhase::AtTarget)]
+    );
+}
+
+#[test]
+fn remove_listeners_of() {
+    let calls = Calls::default();
+    let mut dispatcher = dispatcher_on_all_nodes(&calls, click().event_type());
+    dispatcher.remove_listeners_of(PANEL);
+    dispatcher.remove_listeners_of(ROOT);
+    dispatcher.dispatch(&StubTree::new(), BUTTON, &click());
+    assert_eq!(calls.borrow().len(), 2);
+}
+
+#[test]
+fn unknown_target_receives_event_without_propagation() {
+    let calls = Calls::default();
+    let mut dispatcher = dispatcher_on_all_nodes(&calls, click().event_type());
+    let detached = NodeId(42);
+    dispatcher.add_listener(
+        detached,
+        click().event_type(),
+        false,
+        logger(&calls, "detached"),
+    );
+    dispatcher.dispatch(&StubTree::new(), detached, &click());
+    assert_eq!(
+        *calls.borrow(),
+        vec![("detached", detached, EventPhase::AtTarget)]
+    );
+}
@@ -0,0 +1,177 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use crate::dom::{
+    DeltaMode, Event, EventType, KeyEventType, KeyLocation, KeyboardEventImpl, Modifiers,
+    MouseEventImpl, MouseEventType, NodeId, Point, PointerData, Touch, TouchEventImpl,
+    TouchEventType, WheelEventImpl,
+};
+use crate::test::fsm::{StubEvent, StubMouseEvent, StubTouchEvent};
+
+struct PointerStub {
+    x: i32,
+}
+
+impl Event for PointerStub {
+    fn event_type(&self) -> EventType {
+        EventType::Mouse(MouseEventType::Mousemove)
+    }
+}
+
+#[test]
+fn is() {
+    let event: &dyn Event = &PointerStub { x: 1 };
+    assert_eq!(event.is::<PointerStub>(), true);
+    assert_eq!(event.is::<StubEvent>(), false);
+}
+
+#[test]
+fn downcast_ref() {
+    let event: Box<dyn Event> = Box::new(PointerStub { x: 3 });
+    assert_eq!(event.downcast_ref::<PointerStub>().map(|e| e.x), Some(3));
+    assert_eq!(event.downcast_ref::<StubEvent>().is_none(), true);
+}
+
+#[test]
+fn event_type() {
+    let event: &dyn Event = &PointerStub { x: 1 };
+    assert_eq!(
+        event.event_type(),
+        EventType::Mouse(MouseEventType::Mousemove)
+    );
+}
+
+#[test]
+fn default_timestamp_and_target() {
+    let event: &dyn Event = &PointerStub { x: 1 };
+    assert_eq!(event.timestamp(), 0);
+    assert_eq!(event.target(), None);
+}
+
+#[test]
+fn mouse_event_impl() {
+    let modifiers = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+    let pointer = PointerData::new(Point::new(10.0, 20.0))
+        .with_button(2)
+        .with_buttons(2)
+        .with_modifiers(modifiers)
+        .with_timestamp(42)
+        .with_target(NodeId(3));
+    let event: Box<dyn Event> = Box::new(MouseEventImpl::new(MouseEventType::Mousedown, pointer));
+    let mouse = event.as_mouse_event().unwrap();
+    assert_eq!(
+        event.event_type(),
+        EventType::Mouse(MouseEventType::Mousedown)
+    );
+    assert_eq!(event.timestamp(), 42);
+    assert_eq!(event.target(), Some(NodeId(3)));
+    assert_eq!(mouse.position(), Point::new(10.0, 20.0));
+    assert_eq!(mouse.button(), 2);
+    assert_eq!(mouse.buttons(), 2);
+    assert_eq!(mouse.modifiers(), modifiers);
+    assert_eq!(mouse.pointer_id(), 1);
+    assert_eq!(event.as_wheel_event().is_none(), true);
+    assert_eq!(event.as_touch_event().is_none(), true);
+}
+
+#[test]
+fn pointer_data_defaults() {
+    let pointer = PointerData::new(Point::default());
+    assert_eq!(pointer.button, 0);
+    assert_eq!(pointer.buttons, 0);
+    assert_eq!(pointer.modifiers, Modifiers::NONE);
+    assert_eq!(pointer.pointer_id, 1);
+    assert_eq!(pointer.target, None);
+}
+
+#[test]
+fn wheel_event_impl() {
+    let pointer = PointerData::new(Point::new(1.0, 2.0)).with_pointer_id(4);
+    let event: Box<dyn Event> = Box::new(
+        WheelEventImpl::new(pointer, 3.0, -5.0)
+            .with_delta_z(1.0)
+            .with_delta_mode(DeltaMode::Line),
+    );
+    let wheel = event.as_wheel_event().unwrap();
+    assert_eq!(event.event_type(), EventType::Wheel);
+    assert_eq!(wheel.delta_x(), 3.0);
+    assert_eq!(wheel.delta_y(), -5.0);
+    assert_eq!(wheel.delta_z(), 1.0);
+    assert_eq!(wheel.delta_mode(), DeltaMode::Line);
+    assert_eq!(wheel.position(), Point::new(1.0, 2.0));
+    assert_eq!(event.as_mouse_event().map(|m| m.pointer_id()), Some(4));
+}
+
+#[test]
+fn keyboard_event_impl() {
+    let event: Box<dyn Event> = Box::new(
+        KeyboardEventImpl::new(KeyEventType::Keydown, "a", "KeyA")
+            .with_repeat(true)
+            .with_location(KeyLocation::Left)
+            .with_timestamp(7),
+    );
+    let key = event.as_keyboard_event().unwrap();
+    assert_eq!(key.key(), "a");
+    assert_eq!(key.code(), "KeyA");
+    assert_eq!(key.repeat(), true);
+    assert_eq!(key.location(), KeyLocation::Left);
+    assert_eq!(key.modifiers(), Modifiers::NONE);
+    assert_eq!(event.timestamp(), 7);
+    assert_eq!(event.as_mouse_event().is_none(), true);
+}
+
+#[test]
+fn touch_event_impl_touches() {
+    let first = Touch::new(1).with_position(Point::new(5.0, 6.0));
+    let second = Touch::new(2).with_target(NodeId(8));
+    let event = TouchEventImpl::new(TouchEventType::Touchstart, vec![second.clone()])
+        .with_touches(vec![first.clone(), second.clone()]);
+    let touch = Event::as_touch_event(&event).unwrap();
+    assert_eq!(touch.changed_touches(), std::slice::from_ref(&second));
+    assert_eq!(touch.touches(), &[first, second]);
+    assert_eq!(
+        touch.changed_touch(2).and_then(|t| t.target),
+        Some(NodeId(8))
+    );
+    assert_eq!(touch.changed_touch(1).is_none(), true);
+}
+
+#[test]
+fn touch_event_impl_touchend_has_no_touches() {
+    let event = TouchEventImpl::new(TouchEventType::Touchend, vec![Touch::new(1)]);
+    let touch = Event::as_touch_event(&event).unwrap();
+    assert_eq!(touch.changed_touches().len(), 1);
+    assert_eq!(touch.touches().is_empty(), true);
+}
+
+#[test]
+fn stub_events_defaults() {
+    let mouse = StubMouseEvent {
+        event_type: MouseEventType::Mouseup,
+        button: 0,
+    };
+    let mouse = Event::as_mouse_event(&mouse).unwrap();
+    assert_eq!(mouse.position(), Point::default());
+    assert_eq!(mouse.buttons(), 0);
+    assert_eq!(mouse.pointer_id(), 1);
+    let touch = StubTouchEvent::new(TouchEventType::Touchmove, 3);
+    let touch = Event::as_touch_event(&touch).unwrap();
+    assert_eq!(touch.touches(), touch.changed_touches());
+    assert_eq!(
+        touch.changed_touch(3).map(|t| t.position),
+        Some(Point::default())
+    );
+}
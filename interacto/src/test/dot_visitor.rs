@@ -0,0 +1,150 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::rc::Rc;
+
+use crate::dom::{EventType, KeyEventType, MouseEventType};
+use crate::fsm::FSM;
+use crate::r#impl::fsm::{ConcurrentAndFSM, DotVisitor, FSMImpl};
+use crate::test::fsm::press_release_fsm;
+
+fn render(fsm: &dyn FSM) -> String {
+    let mut visitor = DotVisitor::new();
+    fsm.accept_visitor(&mut visitor);
+    visitor.output().to_string()
+}
+
+#[test]
+fn renders_fsm() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        starting: pressed;
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+            pressed -> released on [EventType::Mouse(MouseEventType::Mouseup)] when "left button" (|_| Ok(true));
+            pressed -> cancelled timeout (1000);
+        }
+    };
+    assert_eq!(
+        render(&fsm),
+        "digraph fsm {
+  compound=true;
+  rankdir=LR;
+  f0_s0 [label=\"init\", shape=circle, style=filled, fillcolor=black, fontcolor=white];
+  f0_s1 [label=\"pressed\", shape=circle, penwidth=3, xlabel=\"starting\"];
+  f0_s2 [label=\"released\", shape=doublecircle];
+  f0_s3 [label=\"cancelled\", shape=doublecircle, color=red];
+  f0_s0 -> f0_s1 [label=\"mousedown\"];
+  f0_s1 -> f0_s2 [label=\"mouseup [left button]\"];
+  f0_s1 -> f0_s3 [label=\"timeout (1000 ms)\", style=dashed];
+}
+"
+    );
+}
+
+#[test]
+fn sorts_accepted_events() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended on [EventType::Mouse(MouseEventType::Mouseup), EventType::Key(KeyEventType::Keydown)] when (|_| Ok(true));
+        }
+    };
+    assert_eq!(
+        render(&fsm).contains("f0_s0 -> f0_s1 [label=\"keydown, mouseup [guard]\"];"),
+        true
+    );
+}
+
+#[test]
+fn computed_delay_not_rendered() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended timeout_with (|| 300);
+        }
+    };
+    assert_eq!(
+        render(&fsm).contains("f0_s0 -> f0_s1 [label=\"timeout\", style=dashed];"),
+        true
+    );
+}
+
+#[test]
+fn escapes_names() {
+    let mut fsm = FSMImpl::new();
+    fsm.add_std_state("a \"quoted\" state", false);
+    assert_eq!(
+        render(&fsm).contains("[label=\"a \\\"quoted\\\" state\", shape=circle];"),
+        true
+    );
+}
+
+#[test]
+fn renders_sub_fsm_as_cluster() {
+    let inner = press_release_fsm(Rc::default());
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    };
+    let dot = render(&fsm);
+    assert_eq!(
+        dot.contains("  subgraph cluster_f1 {\n    style=dashed;\n"),
+        true
+    );
+    assert_eq!(
+        dot.contains("    f1_s0 -> f1_s1 [label=\"mousedown\"];"),
+        true
+    );
+    assert_eq!(
+        dot.contains("  f0_s0 -> f1_s0 [label=\"mousedown\", lhead=cluster_f1];"),
+        true
+    );
+    assert_eq!(
+        dot.contains("  f1_s0 -> f0_s1 [ltail=cluster_f1, style=dotted];"),
+        true
+    );
+}
+
+#[test]
+fn renders_concurrent_fsm() {
+    let fsm = ConcurrentAndFSM::new(vec![
+        press_release_fsm(Rc::default()),
+        press_release_fsm(Rc::default()),
+    ]);
+    let dot = render(&fsm);
+    assert_eq!(dot.contains("  label=\"AND\";"), true);
+    assert_eq!(dot.contains("  subgraph cluster_f1 {"), true);
+    assert_eq!(dot.contains("  subgraph cluster_f2 {"), true);
+    assert_eq!(
+        dot.contains("  f0_s0 -> f2_s0 [lhead=cluster_f2, style=dotted];"),
+        true
+    );
+    assert_eq!(dot.ends_with("}\n"), true);
+}
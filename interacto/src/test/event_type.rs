@@ -0,0 +1,177 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::dom::{
+    ClipboardEventType, CompositionEventType, DragEventType, EventType, FocusEventType,
+    KeyEventType, MouseEventType, ParseEventTypeError, PointerEventType, TouchEventType,
+};
+
+fn all_event_types() -> Vec<EventType> {
+    let mut all = vec![
+        EventType::Input,
+        EventType::Scroll,
+        EventType::Change,
+        EventType::Wheel,
+    ];
+    all.extend(MouseEventType::ALL.iter().copied().map(EventType::Mouse));
+    all.extend(TouchEventType::ALL.iter().copied().map(EventType::Touch));
+    all.extend(KeyEventType::ALL.iter().copied().map(EventType::Key));
+    all.extend(
+        PointerEventType::ALL
+            .iter()
+            .copied()
+            .map(EventType::Pointer),
+    );
+    all.extend(FocusEventType::ALL.iter().copied().map(EventType::Focus));
+    all.extend(
+        CompositionEventType::ALL
+            .iter()
+            .copied()
+            .map(EventType::Composition),
+    );
+    all.extend(
+        ClipboardEventType::ALL
+            .iter()
+            .copied()
+            .map(EventType::Clipboard),
+    );
+    all.extend(DragEventType::ALL.iter().copied().map(EventType::Drag));
+    all
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        EventType::Mouse(MouseEventType::Mousedown).to_string(),
+        "mousedown"
+    );
+    assert_eq!(
+        EventType::Mouse(MouseEventType::Dblclick).to_string(),
+        "dblclick"
+    );
+    assert_eq!(
+        EventType::Mouse(MouseEventType::Contextmenu).to_string(),
+        "contextmenu"
+    );
+    assert_eq!(
+        EventType::Pointer(PointerEventType::Pointercancel).to_string(),
+        "pointercancel"
+    );
+    assert_eq!(
+        EventType::Focus(FocusEventType::Focusout).to_string(),
+        "focusout"
+    );
+    assert_eq!(
+        EventType::Composition(CompositionEventType::Compositionupdate).to_string(),
+        "compositionupdate"
+    );
+    assert_eq!(
+        EventType::Clipboard(ClipboardEventType::Paste).to_string(),
+        "paste"
+    );
+    assert_eq!(EventType::Drag(DragEventType::Drop).to_string(), "drop");
+    assert_eq!(EventType::Wheel.to_string(), "wheel");
+}
+
+#[test]
+fn from_str() {
+    assert_eq!(
+        "mousedown".parse(),
+        Ok(EventType::Mouse(MouseEventType::Mousedown))
+    );
+    assert_eq!(
+        "touchend".parse(),
+        Ok(EventType::Touch(TouchEventType::Touchend))
+    );
+    assert_eq!("keyup".parse(), Ok(EventType::Key(KeyEventType::Keyup)));
+    assert_eq!("blur".parse(), Ok(EventType::Focus(FocusEventType::Blur)));
+    assert_eq!(
+        "cut".parse(),
+        Ok(EventType::Clipboard(ClipboardEventType::Cut))
+    );
+    assert_eq!(
+        "dragstart".parse(),
+        Ok(EventType::Drag(DragEventType::Dragstart))
+    );
+    assert_eq!("change".parse(), Ok(EventType::Change));
+}
+
+#[test]
+fn from_str_sub_type() {
+    assert_eq!(MouseEventType::from_str("click"), Ok(MouseEventType::Click));
+    assert_eq!(
+        PointerEventType::from_str("pointerenter"),
+        Ok(PointerEventType::Pointerenter)
+    );
+    assert_eq!(MouseEventType::from_str("keydown").is_err(), true);
+}
+
+#[test]
+fn from_str_unknown() {
+    let err = EventType::from_str("MouseDown").unwrap_err();
+    assert_eq!(err.name(), "MouseDown");
+    assert_eq!(err.to_string(), "unknown event type 'MouseDown'");
+    assert_eq!(
+        "".parse::<EventType>()
+            .map_err(|e: ParseEventTypeError| e.name().to_string()),
+        Err(String::new())
+    );
+}
+
+#[test]
+fn round_trip() {
+    for event_type in all_event_types() {
+        assert_eq!(event_type.to_string().parse(), Ok(event_type));
+    }
+}
+
+#[test]
+fn names_are_unique() {
+    let all = all_event_types();
+    let names: HashSet<&str> = all.iter().map(|event_type| event_type.as_str()).collect();
+    assert_eq!(names.len(), all.len());
+}
+
+#[test]
+fn hash_set_of_event_types() {
+    let events = HashSet::from([
+        EventType::Pointer(PointerEventType::Pointerdown),
+        EventType::Drag(DragEventType::Dragover),
+        EventType::Pointer(PointerEventType::Pointerdown),
+    ]);
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events.contains(&EventType::Drag(DragEventType::Dragover)),
+        true
+    );
+}
+
+#[test]
+fn bubbles() {
+    assert_eq!(EventType::Mouse(MouseEventType::Click).bubbles(), true);
+    assert_eq!(EventType::Focus(FocusEventType::Focusin).bubbles(), true);
+    assert_eq!(EventType::Input.bubbles(), true);
+    assert_eq!(EventType::Focus(FocusEventType::Blur).bubbles(), false);
+    assert_eq!(
+        EventType::Mouse(MouseEventType::Mouseenter).bubbles(),
+        false
+    );
+    assert_eq!(
+        EventType::Pointer(PointerEventType::Pointerleave).bubbles(),
+        false
+    );
+    assert_eq!(EventType::Scroll.bubbles(), false);
+}
@@ -0,0 +1,505 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::{
+    Event, EventType, KeyEventType, KeyboardEvent, Modifiers, MouseEvent, MouseEventType, Touch,
+    TouchEvent, TouchEventType, WheelEvent,
+};
+use crate::fsm::{CancelFSMException, FSMHandler, SharedFSMError, StateId, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{FSMImpl, TransitionBase};
+
+pub fn press() -> EventType {
+    EventType::Mouse(MouseEventType::Mousedown)
+}
+
+pub fn release() -> EventType {
+    EventType::Mouse(MouseEventType::Mouseup)
+}
+
+pub fn key() -> EventType {
+    EventType::Key(KeyEventType::Keydown)
+}
+
+pub struct StubEvent {
+    pub event_type: EventType,
+}
+
+impl StubEvent {
+    pub fn new(event_type: EventType) -> Self {
+        Self { event_type }
+    }
+
+    pub fn press() -> Self {
+        Self::new(press())
+    }
+
+    pub fn release() -> Self {
+        Self::new(release())
+    }
+
+    pub fn key() -> Self {
+        Self::new(key())
+    }
+}
+
+impl Event for StubEvent {
+    fn event_type(&self) -> EventType {
+        self.event_type
+    }
+}
+
+pub struct StubMouseEvent {
+    pub event_type: MouseEventType,
+    pub button: i16,
+}
+
+impl Event for StubMouseEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Mouse(self.event_type)
+    }
+
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl MouseEvent for StubMouseEvent {
+    fn button(&self) -> i16 {
+        self.button
+    }
+}
+
+pub struct StubWheelEvent;
+
+impl Event for StubWheelEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Wheel
+    }
+
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        Some(self)
+    }
+
+    fn as_wheel_event(&self) -> Option<&(dyn WheelEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl MouseEvent for StubWheelEvent {
+    fn button(&self) -> i16 {
+        0
+    }
+}
+
+impl WheelEvent for StubWheelEvent {}
+
+pub struct StubTouchEvent {
+    pub event_type: TouchEventType,
+    pub touches: Vec<Touch>,
+}
+
+impl StubTouchEvent {
+    pub fn new(event_type: TouchEventType, identifier: i32) -> Self {
+        Self {
+            event_type,
+            touches: vec![Touch::new(identifier)],
+        }
+    }
+}
+
+impl Event for StubTouchEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Touch(self.event_type)
+    }
+
+    fn as_touch_event(&self) -> Option<&(dyn TouchEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl TouchEvent for StubTouchEvent {
+    fn changed_touches(&self) -> &[Touch] {
+        &self.touches
+    }
+}
+
+pub struct StubKeyEvent {
+    pub event_type: KeyEventType,
+    pub key: String,
+    pub code: String,
+    pub modifiers: Modifiers,
+}
+
+impl StubKeyEvent {
+    pub fn new(event_type: KeyEventType, key: &str, code: &str) -> Self {
+        Self {
+            event_type,
+            key: key.to_string(),
+            code: code.to_string(),
+            modifiers: Modifiers::NONE,
+        }
+    }
+}
+
+impl Event for StubKeyEvent {
+    fn event_type(&self) -> EventType {
+        EventType::Key(self.event_type)
+    }
+
+    fn as_keyboard_event(&self) -> Option<&(dyn KeyboardEvent + 'static)> {
+        Some(self)
+    }
+}
+
+impl KeyboardEvent for StubKeyEvent {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn code(&self) -> &str {
+        &self.code
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+}
+
+/// An FSM handler that records the notifications it receives.
+#[derive(Default)]
+pub struct StubFSMHandler {
+    pub calls: Vec<&'static str>,
+    pub errors: Vec<SharedFSMError>,
+    pub cancel_on_start: bool,
+    pub cancel_on_update: bool,
+    pub cancel_on_stop: bool,
+}
+
+impl StubFSMHandler {
+    pub fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self::default()))
+    }
+}
+
+impl FSMHandler for StubFSMHandler {
+    fn fsm_starts(&mut self) -> Result<(), CancelFSMException> {
+        self.calls.push("start");
+        if self.cancel_on_start {
+            Err(CancelFSMException)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn fsm_updates(&mut self) -> Result<(), CancelFSMException> {
+        self.calls.push("update");
+        if self.cancel_on_update {
+            Err(CancelFSMException)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn fsm_stops(&mut self) -> Result<(), CancelFSMException> {
+        self.calls.push("stop");
+        if self.cancel_on_stop {
+            Err(CancelFSMException)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn fsm_cancels(&mut self) {
+        self.calls.push("cancel");
+    }
+
+    fn fsm_error(&mut self, _err: &(dyn std::error::Error + Send + Sync)) {
+        self.calls.push("error");
+    }
+
+    fn fsm_shared_error(&mut self, err: &SharedFSMError) {
+        self.errors.push(err.clone());
+        self.fsm_error(err.as_ref());
+    }
+
+    fn pre_fsm_start(&mut self) {}
+
+    fn pre_fsm_update(&mut self) {}
+
+    fn pre_fsm_stop(&mut self) {}
+}
+
+pub fn transition(src: StateId, tgt: StateId, event_type: EventType) -> Box<TransitionBase> {
+    Box::new(TransitionBase::new(
+        src,
+        tgt,
+        HashSet::from([event_type]),
+        None,
+        None,
+    ))
+}
+
+/// The FSM of the tests that only need a running FSM, declared with `fsm!`:
+/// init -press-> pressed -release-> released (terminal)
+///               pressed -key-> cancelled (cancelling)
+///               pressed -timeout (100)-> cancelled
+pub fn press_release_fsm(clock: Rc<VirtualClock>) -> FSMImpl {
+    crate::fsm! {
+        clock: clock;
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> released on [release()];
+            pressed -> cancelled on [key()];
+            pressed -> cancelled timeout (100);
+        }
+    }
+}
+
+/// init -press-> pressed -release-> released (terminal)
+///                pressed -key-> cancelled (cancelling)
+struct PressRelease {
+    fsm: FSMImpl,
+    pressed: StateId,
+    released: StateId,
+}
+
+fn press_release() -> PressRelease {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    let released = fsm.add_terminal_state("released", false);
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    fsm.add_transition(transition(init, pressed, StubEvent::press().event_type));
+    fsm.add_transition(transition(
+        pressed,
+        released,
+        StubEvent::release().event_type,
+    ));
+    fsm.add_transition(transition(pressed, cancelled, StubEvent::key().event_type));
+    PressRelease {
+        fsm,
+        pressed,
+        released,
+    }
+}
+
+#[test]
+fn current_state_is_init_state_at_start() {
+    let pr = press_release();
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+    assert_eq!(pr.fsm.started(), false);
+    assert_eq!(pr.fsm.states().len(), 4);
+}
+
+#[test]
+fn starting_state_is_init_state_by_default() {
+    let pr = press_release();
+    assert_eq!(pr.fsm.starting_state().id(), pr.fsm.init_state().id());
+}
+
+#[test]
+fn event_not_accepted() {
+    let mut pr = press_release();
+    assert_eq!(pr.fsm.process(&StubEvent::release()), false);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+}
+
+#[test]
+fn accepts_does_not_process_event() {
+    let mut pr = press_release();
+    assert_eq!(pr.fsm.accepts(&StubEvent::press()), true);
+    assert_eq!(pr.fsm.accepts(&StubEvent::release()), false);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+    pr.fsm.process(&StubEvent::press());
+    assert_eq!(pr.fsm.accepts(&StubEvent::release()), true);
+}
+
+#[test]
+fn accepts_respects_guards() {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    fsm.add_transition(Box::new(TransitionBase::new(
+        init,
+        pressed,
+        HashSet::from([StubEvent::press().event_type]),
+        None,
+        Some(Box::new(|_: &dyn Event| Ok(false))),
+    )));
+    assert_eq!(fsm.accepts(&StubEvent::press()), false);
+}
+
+#[test]
+fn event_moves_to_std_state() {
+    let mut pr = press_release();
+    assert_eq!(pr.fsm.process(&StubEvent::press()), true);
+    assert_eq!(pr.fsm.current_state_id(), pr.pressed);
+    assert_eq!(pr.fsm.started(), true);
+}
+
+#[test]
+fn handler_starts_and_updates_when_leaving_init_state() {
+    let mut pr = press_release();
+    let handler = StubFSMHandler::new();
+    pr.fsm.add_handler(handler.clone());
+    pr.fsm.process(&StubEvent::press());
+    assert_eq!(handler.borrow().calls, vec!["start", "update"]);
+}
+
+#[test]
+fn terminal_state_stops_and_reinits() {
+    let mut pr = press_release();
+    let handler = StubFSMHandler::new();
+    pr.fsm.add_handler(handler.clone());
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm.process(&StubEvent::release());
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+    assert_eq!(pr.fsm.started(), false);
+}
+
+#[test]
+fn cancelling_state_cancels_and_reinits() {
+    let mut pr = press_release();
+    let handler = StubFSMHandler::new();
+    pr.fsm.add_handler(handler.clone());
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm.process(&StubEvent::key());
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "cancel"]);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+    assert_eq!(pr.fsm.started(), false);
+}
+
+#[test]
+fn custom_starting_state_delays_start() {
+    let mut pr = press_release();
+    pr.fsm.set_starting_state(pr.released);
+    let handler = StubFSMHandler::new();
+    pr.fsm.add_handler(handler.clone());
+    pr.fsm.process(&StubEvent::press());
+    assert_eq!(pr.fsm.started(), false);
+    assert_eq!(handler.borrow().calls.is_empty(), true);
+    pr.fsm.process(&StubEvent::release());
+    assert_eq!(handler.borrow().calls, vec!["start", "stop"]);
+}
+
+#[test]
+fn no_notification_when_cancelled_before_starting() {
+    let mut pr = press_release();
+    pr.fsm.set_starting_state(pr.released);
+    let handler = StubFSMHandler::new();
+    pr.fsm.add_handler(handler.clone());
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm.process(&StubEvent::key());
+    assert_eq!(handler.borrow().calls.is_empty(), true);
+}
+
+#[test]
+fn handler_cancelling_on_start_cancels_fsm() {
+    let mut pr = press_release();
+    let handler = StubFSMHandler::new();
+    handler.borrow_mut().cancel_on_start = true;
+    pr.fsm.add_handler(handler.clone());
+    assert_eq!(pr.fsm.process(&StubEvent::press()), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "cancel"]);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+}
+
+#[test]
+fn handler_cancelling_on_stop_cancels_fsm() {
+    let mut pr = press_release();
+    let handler = StubFSMHandler::new();
+    handler.borrow_mut().cancel_on_stop = true;
+    pr.fsm.add_handler(handler.clone());
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm.process(&StubEvent::release());
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "stop", "cancel"]
+    );
+}
+
+#[test]
+fn removed_handler_not_notified() {
+    let mut pr = press_release();
+    let handler = StubFSMHandler::new();
+    let dyn_handler: Rc<RefCell<dyn FSMHandler>> = handler.clone();
+    pr.fsm.add_handler(dyn_handler.clone());
+    pr.fsm.remove_handler(&dyn_handler);
+    pr.fsm.process(&StubEvent::press());
+    assert_eq!(handler.borrow().calls.is_empty(), true);
+}
+
+#[test]
+fn remaining_events_processed_after_termination() {
+    let mut pr = press_release();
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm
+        .add_remaining_event_to_process(Box::new(StubEvent::press()));
+    pr.fsm.process(&StubEvent::release());
+    assert_eq!(pr.fsm.remaining_events_count(), 0);
+    assert_eq!(pr.fsm.current_state_id(), pr.pressed);
+}
+
+#[test]
+fn reinit_keeps_remaining_events() {
+    let mut pr = press_release();
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm
+        .add_remaining_event_to_process(Box::new(StubEvent::press()));
+    pr.fsm.reinit();
+    assert_eq!(pr.fsm.remaining_events_count(), 1);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+}
+
+#[test]
+fn full_reinit_flushes_remaining_events() {
+    let mut pr = press_release();
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm
+        .add_remaining_event_to_process(Box::new(StubEvent::press()));
+    pr.fsm.full_reinit();
+    assert_eq!(pr.fsm.remaining_events_count(), 0);
+    assert_eq!(pr.fsm.started(), false);
+}
+
+#[test]
+fn cancel_flushes_remaining_events() {
+    let mut pr = press_release();
+    pr.fsm.process(&StubEvent::press());
+    pr.fsm
+        .add_remaining_event_to_process(Box::new(StubEvent::press()));
+    pr.fsm.process(&StubEvent::key());
+    assert_eq!(pr.fsm.remaining_events_count(), 0);
+    assert_eq!(pr.fsm.current_state().id(), pr.fsm.init_state().id());
+}
+
+#[test]
+#[should_panic]
+fn transition_to_init_state_rejected() {
+    let mut pr = press_release();
+    let init = pr.fsm.init_state().id();
+    pr.fsm
+        .add_transition(transition(pr.pressed, init, StubEvent::key().event_type));
+}
@@ -0,0 +1,199 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::fsm::FSM;
+use crate::r#impl::fsm::{FSMCoverage, FSMExplorer, UncoveredTransition};
+use crate::test::fsm::{press, press_release_fsm, release, StubEvent};
+
+fn uncovered(src: &str, tgt: &str, label: &str) -> UncoveredTransition {
+    UncoveredTransition {
+        src: src.to_string(),
+        tgt: tgt.to_string(),
+        label: label.to_string(),
+    }
+}
+
+#[test]
+fn instrumented_fsm_records_coverage() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let coverage = Rc::new(RefCell::new(FSMCoverage::new()));
+    fsm.instrument(coverage.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    let report = coverage.borrow().report(fsm.definition());
+    assert_eq!(report.state_count, 4);
+    assert_eq!(report.transition_count, 4);
+    assert_eq!(report.uncovered_states, vec!["cancelled".to_string()]);
+    assert_eq!(
+        report.uncovered_transitions,
+        vec![
+            uncovered("pressed", "cancelled", "keydown"),
+            uncovered("pressed", "cancelled", "timeout (100 ms)"),
+        ]
+    );
+    assert_eq!(report.is_complete(), false);
+}
+
+#[test]
+fn report_does_not_compute_timeout_delays() {
+    let computed = Rc::new(Cell::new(0));
+    let count = computed.clone();
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            ended: terminal,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> ended timeout_with (move || {
+                count.set(count.get() + 1);
+                100
+            });
+        }
+    };
+    let report = FSMCoverage::new().report(fsm.definition());
+    assert_eq!(
+        report.uncovered_transitions,
+        vec![
+            uncovered("init", "pressed", "mousedown"),
+            uncovered("pressed", "ended", "timeout"),
+        ]
+    );
+    assert_eq!(computed.get(), 0);
+}
+
+#[test]
+fn failed_state_change_not_covered() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let pressed = fsm.states()[1].id();
+    fsm.set_exit_action(pressed, Box::new(|| Err("no exit".into())));
+    let coverage = Rc::new(RefCell::new(FSMCoverage::new()));
+    fsm.instrument(coverage.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(coverage.borrow().executed_transitions().len(), 1);
+    assert_eq!(coverage.borrow().visited_states().len(), 2);
+}
+
+#[test]
+fn coverage_report_display() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let coverage = Rc::new(RefCell::new(FSMCoverage::new()));
+    fsm.instrument(coverage.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::key());
+    assert_eq!(
+        coverage.borrow().report(fsm.definition()).to_string(),
+        "states: 3/4 covered
+transitions: 2/4 covered
+state 'released' never visited
+transition 'pressed' -> 'released' (mouseup) never executed
+transition 'pressed' -> 'cancelled' (timeout (100 ms)) never executed
+"
+    );
+}
+
+#[test]
+fn instances_share_coverage() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let coverage = Rc::new(RefCell::new(FSMCoverage::new()));
+    fsm.instrument(coverage.clone());
+    let mut instance = fsm.instance();
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    instance.process(&StubEvent::press());
+    instance.process(&StubEvent::key());
+    assert_eq!(coverage.borrow().visited_states().len(), 4);
+    assert_eq!(coverage.borrow().executed_transitions().len(), 3);
+}
+
+#[test]
+fn not_instrumented_fsm() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let coverage = Rc::new(RefCell::new(FSMCoverage::new()));
+    fsm.process(&StubEvent::press());
+    assert_eq!(coverage.borrow().executed_transitions().is_empty(), true);
+}
+
+#[test]
+fn explore_covers_fsm() {
+    let fsm = press_release_fsm(Rc::default());
+    let report = FSMExplorer::explore(
+        &fsm,
+        &[
+            &StubEvent::press(),
+            &StubEvent::release(),
+            &StubEvent::key(),
+        ],
+        2,
+    );
+    assert_eq!(report.is_complete(), true);
+    assert_eq!(fsm.current_state_id(), fsm.init_state().id());
+}
+
+#[test]
+fn explore_bounded_depth() {
+    let report = FSMExplorer::explore(&press_release_fsm(Rc::default()), &[&StubEvent::press()], 1);
+    assert_eq!(
+        report.uncovered_states,
+        vec!["released".to_string(), "cancelled".to_string()]
+    );
+    assert_eq!(report.uncovered_transitions.len(), 3);
+}
+
+#[test]
+fn explore_with_partial_alphabet() {
+    let report = FSMExplorer::explore(
+        &press_release_fsm(Rc::default()),
+        &[&StubEvent::press(), &StubEvent::release()],
+        10,
+    );
+    assert_eq!(report.uncovered_states, Vec::<String>::new());
+    assert_eq!(
+        report.uncovered_transitions,
+        vec![uncovered("pressed", "cancelled", "keydown")]
+    );
+}
+
+#[test]
+fn explore_reports_guarded_and_unreachable() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            orphan: std,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> released on [release()] when "never" (|_| Ok(false));
+            orphan -> released on [release()];
+        }
+    };
+    let report = FSMExplorer::explore(&fsm, &[&StubEvent::press(), &StubEvent::release()], 5);
+    assert_eq!(
+        report.uncovered_states,
+        vec!["released".to_string(), "orphan".to_string()]
+    );
+    assert_eq!(
+        report.uncovered_transitions,
+        vec![
+            uncovered("pressed", "released", "mouseup [never]"),
+            uncovered("orphan", "released", "mouseup"),
+        ]
+    );
+}
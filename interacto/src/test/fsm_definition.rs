@@ -0,0 +1,267 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::dom::{EventType, KeyEventType};
+use crate::fsm::{StateId, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{ConcurrentAndFSM, FSMDefinition, FSMImpl};
+use crate::test::fsm::{
+    key, press, press_release_fsm, release, transition, StubEvent, StubFSMHandler,
+};
+
+#[test]
+fn add_transition_ids() {
+    let mut definition = FSMDefinition::new();
+    let init = definition.init_state().id();
+    let pressed = definition.add_std_state("pressed", false);
+    let released = definition.add_terminal_state("released", false);
+    let first = definition.add_transition(transition(init, pressed, press()));
+    let second = definition.add_transition(transition(pressed, released, release()));
+    let third = definition.add_transition(transition(pressed, released, press()));
+    assert_eq!((first.src(), first.index()), (init, 0));
+    assert_eq!((second.src(), second.index()), (pressed, 0));
+    assert_eq!((third.src(), third.index()), (pressed, 1));
+    assert_eq!(
+        definition.transition(second).map(|tr| tr.target()),
+        Some(released)
+    );
+}
+
+#[test]
+fn transitions_from() {
+    let fsm = press_release_fsm(Rc::default());
+    let pressed = StateId(1);
+    let ids: Vec<_> = fsm
+        .definition()
+        .transitions_from(pressed)
+        .map(|(id, tr)| (id.index(), tr.get_accepted_events().clone()))
+        .collect();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids[0].1.contains(&release()), true);
+    assert_eq!(fsm.definition().transitions_from(StateId(2)).count(), 0);
+}
+
+#[test]
+fn unknown_transition() {
+    let fsm = press_release_fsm(Rc::default());
+    let id = fsm
+        .definition()
+        .transitions_from(StateId(0))
+        .next()
+        .unwrap()
+        .0;
+    assert_eq!(fsm.definition().transition(id).is_some(), true);
+    let fsm2 = FSMImpl::new();
+    assert_eq!(fsm2.definition().transition(id).is_none(), true);
+}
+
+#[test]
+fn instances_share_definition() {
+    let fsm = press_release_fsm(Rc::default());
+    let instance = fsm.instance();
+    assert_eq!(Arc::ptr_eq(fsm.definition(), instance.definition()), true);
+    assert_eq!(Arc::strong_count(fsm.definition()), 2);
+}
+
+#[test]
+#[allow(clippy::arc_with_non_send_sync)]
+fn from_definition() {
+    let mut definition = FSMDefinition::new();
+    let init = definition.init_state().id();
+    let pressed = definition.add_std_state("pressed", false);
+    definition.add_transition(transition(init, pressed, press()));
+    let definition = Arc::new(definition);
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm1 = FSMImpl::from_definition(definition.clone(), clock.clone());
+    let fsm2 = FSMImpl::from_definition(definition.clone(), clock);
+    fsm1.process(&StubEvent::press());
+    assert_eq!(fsm1.current_state().name(), "pressed");
+    assert_eq!(fsm2.current_state().name(), "init");
+    assert_eq!(Arc::strong_count(&definition), 3);
+}
+
+#[test]
+fn instances_run_independently() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let mut instance = fsm.instance();
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.current_state().name(), "pressed");
+    assert_eq!(instance.current_state().name(), "init");
+    instance.process(&StubEvent::press());
+    instance.process(&StubEvent::release());
+    assert_eq!(instance.current_state().name(), "init");
+    assert_eq!(fsm.current_state().name(), "pressed");
+}
+
+#[test]
+fn instance_starts_in_init_state_without_handler() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    let mut instance = fsm.instance();
+    assert_eq!(instance.current_state().name(), "init");
+    assert_eq!(instance.started(), false);
+    instance.process(&StubEvent::press());
+    assert_eq!(handler.borrow().calls, vec!["start", "update"]);
+}
+
+#[test]
+fn instance_keeps_configuration() {
+    let mut fsm = press_release_fsm(Rc::default());
+    fsm.set_log(true);
+    fsm.set_inner(true);
+    let instance = fsm.instance();
+    assert_eq!(instance.log(), true);
+    assert_eq!(instance.is_inner(), true);
+}
+
+#[test]
+#[should_panic]
+fn shared_definition_cannot_be_modified() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let _instance = fsm.instance();
+    fsm.add_std_state("other", false);
+}
+
+#[test]
+fn definition_modifiable_once_instances_dropped() {
+    let mut fsm = press_release_fsm(Rc::default());
+    drop(fsm.instance());
+    let other = fsm.add_std_state("other", false);
+    assert_eq!(fsm.state(other).name(), "other");
+}
+
+#[test]
+fn uninstall_keeps_shared_definition() {
+    let mut fsm = press_release_fsm(Rc::default());
+    let mut instance = fsm.instance();
+    instance.uninstall();
+    assert_eq!(fsm.current_state().transitions().len(), 1);
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.current_state().name(), "pressed");
+}
+
+#[test]
+fn sub_fsm_instances() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            selected: std,
+            done: terminal,
+        }
+        transitions {
+            init -> selected sub (press_release_fsm(Rc::default()));
+            selected -> done on [key()];
+        }
+    };
+    let mut instance = fsm.instance();
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.current_sub_fsm().map(|sub| sub.started()), Some(true));
+    assert_eq!(instance.current_sub_fsm().is_none(), true);
+    instance.process(&StubEvent::press());
+    instance.process(&StubEvent::release());
+    assert_eq!(instance.current_state().name(), "selected");
+    assert_eq!(
+        fsm.current_sub_fsm()
+            .map(|sub| sub.current_state().name().to_string()),
+        Some("pressed".to_string())
+    );
+    let prototype = fsm.init_state().transitions()[0].inner_fsm().unwrap();
+    assert_eq!(prototype.current_state().name(), "init");
+}
+
+#[test]
+fn concurrent_fsm_instance() {
+    let fsm = ConcurrentAndFSM::with_secondary(
+        vec![press_release_fsm(Rc::default())],
+        vec![press_release_fsm(Rc::default())],
+    );
+    let mut instance = fsm.instance();
+    instance.process(&StubEvent::press());
+    assert_eq!(instance.started(), true);
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        Arc::ptr_eq(
+            fsm.secondary_fsms()[0].definition(),
+            instance.secondary_fsms()[0].definition()
+        ),
+        true
+    );
+}
+
+#[test]
+fn candidate_transitions() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> cancelled on [key()];
+            pressed -> released on [release()] when (|_| Ok(false));
+            pressed -> cancelled timeout (100);
+            pressed -> released on [release(), press()];
+        }
+    };
+    let pressed = StateId(1);
+    let candidates = |event_type| {
+        fsm.definition()
+            .candidate_transitions(pressed, event_type)
+            .map(|(id, _)| id.index())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(candidates(release()), vec![1, 3]);
+    assert_eq!(candidates(press()), vec![3]);
+    assert_eq!(
+        candidates(EventType::Key(KeyEventType::Keyup)),
+        Vec::<usize>::new()
+    );
+}
+
+#[test]
+fn no_candidate_transitions_from_terminal_state() {
+    let fsm = press_release_fsm(Rc::default());
+    assert_eq!(
+        fsm.definition()
+            .candidate_transitions(StateId(2), release())
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn process_tries_candidates_in_order() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            first: terminal,
+            second: terminal,
+        }
+        transitions {
+            init -> first on [press()] when (|_| Ok(false));
+            init -> second on [release()];
+            init -> second on [press()];
+        }
+    };
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.process(&StubEvent::press()), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "stop"]);
+}
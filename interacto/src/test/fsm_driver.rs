@@ -0,0 +1,312 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::executor::block_on;
+use futures::{stream, Stream};
+
+use crate::clock::Clock;
+use crate::dom::{Event, EventType, KeyEventType, MouseEventType};
+use crate::fsm::{AsyncFSMHandler, CancelFSMException, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{FSMDriver, FSMImpl, InteractionOutcome};
+use crate::test::fsm::StubEvent;
+
+/// A future that is pending once before completing,
+/// so that awaiting it suspends the awaiting future.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// An async handler that records its calls,
+/// and that cancels the interaction on the given call.
+#[derive(Default)]
+struct StubAsyncHandler {
+    calls: Vec<&'static str>,
+    cancel_on: Option<&'static str>,
+}
+
+impl StubAsyncHandler {
+    async fn call(&mut self, call: &'static str) -> Result<(), CancelFSMException> {
+        YieldNow(false).await;
+        self.calls.push(call);
+        if self.cancel_on == Some(call) {
+            Err(CancelFSMException)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl AsyncFSMHandler for StubAsyncHandler {
+    async fn fsm_starts(&mut self) -> Result<(), CancelFSMException> {
+        self.call("start").await
+    }
+
+    async fn fsm_updates(&mut self) -> Result<(), CancelFSMException> {
+        self.call("update").await
+    }
+
+    async fn fsm_stops(&mut self) -> Result<(), CancelFSMException> {
+        self.call("stop").await
+    }
+
+    async fn fsm_cancels(&mut self) {
+        self.call("cancel").await.unwrap_or_default();
+    }
+}
+
+type StubStream = stream::Iter<std::vec::IntoIter<Box<dyn Event>>>;
+type StubTimer = Pin<Box<dyn Future<Output = ()>>>;
+
+fn events(events: Vec<StubEvent>) -> StubStream {
+    stream::iter(
+        events
+            .into_iter()
+            .map(|event| Box::new(event) as Box<dyn Event>)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// A press then a release, or a cancellation on a key press or after 100 ms.
+fn click(clock: Rc<VirtualClock>) -> FSMImpl {
+    crate::fsm! {
+        clock: clock;
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+            pressed -> released on [EventType::Mouse(MouseEventType::Mouseup)];
+            pressed -> cancelled on [EventType::Key(KeyEventType::Keydown)];
+            pressed -> cancelled timeout (100);
+        }
+    }
+}
+
+/// A driver whose timers advance the virtual clock once awaited.
+fn driver(
+    events: Vec<StubEvent>,
+) -> FSMDriver<FSMImpl, StubStream, impl FnMut(Duration) -> StubTimer> {
+    let clock = Rc::new(VirtualClock::new());
+    let timer_clock = clock.clone();
+    FSMDriver::new(
+        click(clock.clone()),
+        clock,
+        self::events(events),
+        move |delay: Duration| {
+            let clock = timer_clock.clone();
+            Box::pin(async move { clock.advance(delay.as_millis() as u64) }) as StubTimer
+        },
+    )
+}
+
+#[test]
+fn interaction_terminated() {
+    let mut driver = driver(vec![StubEvent::press(), StubEvent::release()]);
+    let mut handler = StubAsyncHandler::default();
+    assert_eq!(
+        block_on(driver.next_interaction(&mut handler)),
+        Some(InteractionOutcome::Terminated)
+    );
+    assert_eq!(handler.calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn interaction_cancelled() {
+    let mut driver = driver(vec![StubEvent::press(), StubEvent::key()]);
+    let mut handler = StubAsyncHandler::default();
+    assert_eq!(
+        block_on(driver.next_interaction(&mut handler)),
+        Some(InteractionOutcome::Cancelled)
+    );
+    assert_eq!(handler.calls, vec!["start", "update", "cancel"]);
+}
+
+#[test]
+fn successive_interactions() {
+    let mut driver = driver(vec![
+        StubEvent::press(),
+        StubEvent::release(),
+        StubEvent::press(),
+        StubEvent::key(),
+        StubEvent::press(),
+    ]);
+    let mut handler = ();
+    block_on(async {
+        assert_eq!(
+            driver.next_interaction(&mut handler).await,
+            Some(InteractionOutcome::Terminated)
+        );
+        assert_eq!(
+            driver.next_interaction(&mut handler).await,
+            Some(InteractionOutcome::Cancelled)
+        );
+        assert_eq!(
+            driver.next_interaction(&mut handler).await,
+            Some(InteractionOutcome::Cancelled)
+        );
+        assert_eq!(driver.next_interaction(&mut handler).await, None);
+    });
+}
+
+#[test]
+fn stream_ended_before_interaction() {
+    let mut driver = driver(vec![StubEvent::release()]);
+    let mut handler = StubAsyncHandler::default();
+    assert_eq!(block_on(driver.next_interaction(&mut handler)), None);
+    assert_eq!(handler.calls.is_empty(), true);
+}
+
+#[test]
+fn timeout_executed_by_timer() {
+    let clock = Rc::new(VirtualClock::new());
+    let timer_clock = clock.clone();
+    let mut delays = Vec::new();
+    let mut driver = FSMDriver::new(
+        click(clock.clone()),
+        clock.clone(),
+        events(vec![StubEvent::press()]),
+        |delay: Duration| {
+            delays.push(delay);
+            timer_clock.advance(delay.as_millis() as u64);
+            future::ready(())
+        },
+    );
+    let outcome = block_on(driver.next_interaction(&mut ()));
+    assert_eq!(outcome, Some(InteractionOutcome::Cancelled));
+    assert_eq!(clock.now(), 100);
+    drop(driver);
+    assert_eq!(delays, vec![Duration::from_millis(100)]);
+}
+
+/// A stream of a single press, that panics when polled once ended.
+struct SinglePress(Option<bool>);
+
+impl Stream for SinglePress {
+    type Item = Box<dyn Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.0 {
+            None => {
+                self.0 = Some(false);
+                Poll::Ready(Some(Box::new(StubEvent::press())))
+            }
+            Some(false) => {
+                self.0 = Some(true);
+                Poll::Ready(None)
+            }
+            Some(true) => panic!("the stream ended"),
+        }
+    }
+}
+
+#[test]
+fn ended_stream_not_polled_anymore() {
+    let clock = Rc::new(VirtualClock::new());
+    let timer_clock = clock.clone();
+    let fsm = crate::fsm! {
+        clock: clock.clone();
+        states {
+            init: init,
+            pressed: std,
+            held: std,
+            done: terminal,
+        }
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+            pressed -> held timeout (100);
+            held -> done timeout (100);
+        }
+    };
+    let mut driver = FSMDriver::new(fsm, clock, SinglePress(None), |delay: Duration| {
+        timer_clock.advance(delay.as_millis() as u64);
+        future::ready(())
+    });
+    assert_eq!(
+        block_on(driver.next_interaction(&mut ())),
+        Some(InteractionOutcome::Terminated)
+    );
+    assert_eq!(block_on(driver.next_interaction(&mut ())), None);
+}
+
+#[test]
+fn event_before_timer() {
+    let mut driver = driver(vec![StubEvent::press(), StubEvent::release()]);
+    assert_eq!(
+        block_on(driver.next_interaction(&mut ())),
+        Some(InteractionOutcome::Terminated)
+    );
+}
+
+#[test]
+fn handler_cancels_on_start() {
+    let mut driver = driver(vec![StubEvent::press(), StubEvent::release()]);
+    let mut handler = StubAsyncHandler {
+        cancel_on: Some("start"),
+        ..StubAsyncHandler::default()
+    };
+    assert_eq!(
+        block_on(driver.next_interaction(&mut handler)),
+        Some(InteractionOutcome::Cancelled)
+    );
+    assert_eq!(handler.calls, vec!["start", "cancel"]);
+    assert_eq!(driver.fsm().started(), false);
+    assert_eq!(
+        driver.fsm().current_state().id(),
+        driver.fsm().init_state().id()
+    );
+}
+
+#[test]
+fn handler_cancels_on_stop() {
+    let mut driver = driver(vec![StubEvent::press(), StubEvent::release()]);
+    let mut handler = StubAsyncHandler {
+        cancel_on: Some("stop"),
+        ..StubAsyncHandler::default()
+    };
+    assert_eq!(
+        block_on(driver.next_interaction(&mut handler)),
+        Some(InteractionOutcome::Cancelled)
+    );
+    assert_eq!(handler.calls, vec!["start", "update", "stop", "cancel"]);
+}
+
+#[test]
+fn into_fsm_removes_driver() {
+    let mut driver = driver(vec![StubEvent::press()]);
+    block_on(driver.next_interaction(&mut ()));
+    let mut fsm = driver.into_fsm();
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.started(), false);
+}
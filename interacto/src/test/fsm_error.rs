@@ -0,0 +1,273 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::{Cell, RefCell};
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::fsm::{ErrorPolicy, FSMError, FSMHandler, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{ConcurrentXorFSM, FSMImpl};
+use crate::test::fsm::{key, press, release, StubEvent, StubFSMHandler};
+
+fn malformed() -> FSMError {
+    "malformed event".into()
+}
+
+#[derive(Debug)]
+struct Unreadable;
+
+impl fmt::Display for Unreadable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unreadable event")
+    }
+}
+
+impl Error for Unreadable {}
+
+/// init -press-> pressed -release-> released (terminal)
+/// The action of the release transition fails, and so does the guard of the key transition
+/// and the action of the timeout transition.
+fn failing_fsm(clock: Rc<VirtualClock>) -> FSMImpl {
+    crate::fsm! {
+        clock: clock;
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> released on [release()] action (|_| Err(malformed()));
+            pressed -> released on [key()] when (|_| Err(malformed()));
+            pressed -> released timeout (100) action (|| Err(malformed()));
+        }
+    }
+}
+
+fn setup(policy: ErrorPolicy) -> (FSMImpl, Rc<RefCell<StubFSMHandler>>, Rc<VirtualClock>) {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = failing_fsm(clock.clone());
+    fsm.set_error_policy(policy);
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    (fsm, handler, clock)
+}
+
+#[test]
+fn default_error_policy() {
+    assert_eq!(FSMImpl::new().error_policy(), ErrorPolicy::Stay);
+}
+
+#[test]
+fn failing_action_notifies_handlers() {
+    let (mut fsm, handler, _) = setup(ErrorPolicy::Stay);
+    assert_eq!(fsm.process(&StubEvent::release()), false);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "error"]);
+}
+
+#[test]
+fn failing_guard_notifies_handlers() {
+    let (mut fsm, handler, _) = setup(ErrorPolicy::Stay);
+    assert_eq!(fsm.process(&StubEvent::key()), false);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "error"]);
+}
+
+#[test]
+fn stay_policy() {
+    let (mut fsm, _, _) = setup(ErrorPolicy::Stay);
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.current_state().name(), "pressed");
+    assert_eq!(fsm.started(), true);
+}
+
+#[test]
+fn cancel_policy() {
+    let (mut fsm, handler, _) = setup(ErrorPolicy::Cancel);
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.current_state().name(), "init");
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "error", "cancel"]
+    );
+}
+
+#[test]
+fn reinit_policy() {
+    let (mut fsm, handler, _) = setup(ErrorPolicy::Reinit);
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.current_state().name(), "init");
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "error", "cancel"]
+    );
+}
+
+#[test]
+fn reinit_policy_before_start() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: terminal,
+        }
+        transitions {
+            init -> pressed on [press()] action (|_| Err(malformed()));
+        }
+    };
+    fsm.set_error_policy(ErrorPolicy::Reinit);
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    assert_eq!(handler.borrow().calls, vec!["error"]);
+}
+
+#[test]
+fn failing_timeout_action() {
+    let (mut fsm, handler, clock) = setup(ErrorPolicy::Cancel);
+    clock.advance(100);
+    assert_eq!(fsm.check_timeout(), true);
+    assert_eq!(fsm.current_state().name(), "init");
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "error", "cancel"]
+    );
+}
+
+#[test]
+fn failing_timeout_action_not_retried() {
+    let (mut fsm, handler, clock) = setup(ErrorPolicy::Stay);
+    clock.advance(100);
+    fsm.check_timeout();
+    clock.advance(100);
+    assert_eq!(fsm.check_timeout(), false);
+    assert_eq!(fsm.current_state().name(), "pressed");
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "error"]);
+}
+
+#[test]
+fn fsm_works_after_error() {
+    let executed = Rc::new(Cell::new(0));
+    let e = executed.clone();
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> released on [release()] action (move |_| {
+                e.set(e.get() + 1);
+                if e.get() == 1 {
+                    Err(malformed())
+                } else {
+                    Ok(())
+                }
+            });
+        }
+    };
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.process(&StubEvent::release()), true);
+    assert_eq!(executed.get(), 2);
+    assert_eq!(fsm.current_state().name(), "init");
+}
+
+#[test]
+fn error_policy_given_to_sub_fsm() {
+    let inner = failing_fsm(Rc::new(VirtualClock::new()));
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    };
+    fsm.set_error_policy(ErrorPolicy::Cancel);
+    fsm.process(&StubEvent::press());
+    assert_eq!(
+        fsm.current_sub_fsm().map(|sub| sub.error_policy()),
+        Some(ErrorPolicy::Cancel)
+    );
+}
+
+#[test]
+fn sub_fsm_error_notifies_parent_handlers() {
+    let inner = failing_fsm(Rc::new(VirtualClock::new()));
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    };
+    fsm.set_error_policy(ErrorPolicy::Cancel);
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.current_state().name(), "init");
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "error", "cancel"]
+    );
+}
+
+#[test]
+fn sub_fsm_error_forwarded_as_is() {
+    let inner = crate::fsm! {
+        states {
+            init: init,
+            pressed: terminal,
+        }
+        transitions {
+            init -> pressed on [press()] action (|_| Err(Box::new(Unreadable)));
+        }
+    };
+    let mut fsm = ConcurrentXorFSM::new(vec![crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    }]);
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    let errors = &handler.borrow().errors;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].downcast_ref::<Unreadable>().is_some(), true);
+}
+
+#[test]
+fn concurrent_fsm_gives_error_policy() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = ConcurrentXorFSM::new(vec![failing_fsm(clock.clone()), failing_fsm(clock)]);
+    fsm.set_error_policy(ErrorPolicy::Reinit);
+    assert_eq!(fsm.error_policy(), ErrorPolicy::Reinit);
+    let handler: Rc<RefCell<dyn FSMHandler>> = StubFSMHandler::new();
+    fsm.add_handler(handler);
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.current_fsm().is_none(), true);
+}
@@ -0,0 +1,192 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::fsm::{StateKind, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::test::fsm::{key, press, release, StubEvent, StubFSMHandler};
+
+#[test]
+fn declares_states() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        transitions {}
+    };
+    let kinds: Vec<(&str, StateKind)> = fsm.states().iter().map(|s| (s.name(), s.kind())).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("init", StateKind::Init),
+            ("pressed", StateKind::Std),
+            ("released", StateKind::Terminal),
+            ("cancelled", StateKind::Cancelling),
+        ]
+    );
+    assert_eq!(fsm.starting_state().id(), fsm.init_state().id());
+}
+
+#[test]
+fn declares_transitions() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> released on [release()];
+        }
+    };
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.process(&StubEvent::press()), true);
+    assert_eq!(fsm.current_state().name(), "pressed");
+    assert_eq!(fsm.process(&StubEvent::release()), true);
+    assert_eq!(fsm.current_state().name(), "init");
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn several_accepted_events() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended on [press(), key()];
+        }
+    };
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.process(&StubEvent::key()), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "stop"]);
+}
+
+#[test]
+fn guard_and_action() {
+    let count = Rc::new(Cell::new(0));
+    let allowed = Rc::new(Cell::new(false));
+    let (c, a) = (count.clone(), allowed.clone());
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+        }
+        transitions {
+            init -> pressed on [press()] when (move |_| Ok(a.get())) action (move |_| {
+                c.set(c.get() + 1);
+                Ok(())
+            });
+        }
+    };
+    assert_eq!(fsm.process(&StubEvent::press()), false);
+    assert_eq!(count.get(), 0);
+    allowed.set(true);
+    assert_eq!(fsm.process(&StubEvent::press()), true);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn starting_state() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+        }
+        starting: released;
+        transitions {
+            init -> pressed on [press()];
+            pressed -> released on [release()];
+        }
+    };
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    assert_eq!(fsm.starting_state().name(), "released");
+    fsm.process(&StubEvent::press());
+    assert_eq!(handler.borrow().calls.is_empty(), true);
+    fsm.process(&StubEvent::release());
+    assert_eq!(handler.borrow().calls, vec!["start", "stop"]);
+}
+
+#[test]
+fn timeout_transitions() {
+    let clock = Rc::new(VirtualClock::new());
+    let fired = Rc::new(Cell::new(false));
+    let f = fired.clone();
+    let mut fsm = crate::fsm! {
+        clock: clock.clone();
+        states {
+            init: init,
+            pressed: std,
+            held: std,
+            cancelled: cancelling,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> held timeout (100) action (move || {
+                f.set(true);
+                Ok(())
+            });
+            held -> cancelled timeout_with (|| 50);
+        }
+    };
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.next_timeout(), Some(100));
+    clock.advance(100);
+    assert_eq!(fsm.check_timeout(), true);
+    assert_eq!(fired.get(), true);
+    assert_eq!(fsm.current_state().name(), "held");
+    assert_eq!(fsm.next_timeout(), Some(150));
+    clock.advance(50);
+    assert_eq!(fsm.check_timeout(), true);
+    assert_eq!(fsm.current_state().name(), "init");
+}
+
+#[test]
+fn sub_fsm_transition() {
+    let inner = crate::fsm! {
+        states {
+            init: init,
+            keyed: terminal,
+        }
+        transitions {
+            init -> keyed on [key()];
+        }
+    };
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            ended: terminal,
+        }
+        transitions {
+            init -> pressed on [press()];
+            pressed -> ended sub (inner);
+        }
+    };
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.process(&StubEvent::key()), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
@@ -0,0 +1,229 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use crate::dom::{EventType, MouseEventType};
+use crate::fsm::FSM;
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{FSMEventQueue, FSMEventSender, FSMImpl, FSMTimer};
+use crate::test::fsm::{transition, StubEvent, StubFSMHandler, StubMouseEvent};
+
+/// An FSM that records the button of the mouse moves it processes.
+fn recording_fsm(buttons: Rc<RefCell<Vec<i16>>>) -> FSMImpl {
+    crate::fsm! {
+        states {
+            init: init,
+            moved: std,
+        }
+        transitions {
+            init -> moved on [EventType::Mouse(MouseEventType::Mousemove)];
+            moved -> moved on [EventType::Mouse(MouseEventType::Mousemove)] action (move |event| {
+                buttons.borrow_mut().push(event.as_mouse_event().unwrap().button());
+                Ok(())
+            });
+        }
+    }
+}
+
+fn post_moves(sender: &FSMEventSender, buttons: impl Iterator<Item = i16>) {
+    for button in buttons {
+        sender.post_event(Box::new(StubMouseEvent {
+            event_type: MouseEventType::Mousemove,
+            button,
+        }));
+    }
+}
+
+#[test]
+fn sender_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<FSMEventSender>();
+}
+
+#[test]
+fn nothing_to_drain() {
+    let queue = FSMEventQueue::new();
+    let mut fsm = recording_fsm(Rc::default());
+    assert_eq!(queue.drain(&mut fsm), 0);
+}
+
+#[test]
+fn events_processed_when_drained() {
+    let queue = FSMEventQueue::new();
+    let mut fsm = FSMImpl::new();
+    let handler = StubFSMHandler::new();
+    let pressed = fsm.add_std_state("pressed", false);
+    let released = fsm.add_terminal_state("released", false);
+    let init = fsm.init_state().id();
+    fsm.add_transition(transition(
+        init,
+        pressed,
+        EventType::Mouse(MouseEventType::Mousedown),
+    ));
+    fsm.add_transition(transition(
+        pressed,
+        released,
+        EventType::Mouse(MouseEventType::Mouseup),
+    ));
+    fsm.add_handler(handler.clone());
+    let sender = queue.sender();
+    thread::spawn(move || {
+        sender.post_event(Box::new(StubEvent::press()));
+        sender.post_event(Box::new(StubEvent::release()));
+    })
+    .join()
+    .unwrap();
+    assert_eq!(handler.borrow().calls.is_empty(), true);
+    assert_eq!(queue.drain(&mut fsm), 2);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn events_of_a_thread_processed_in_posting_order() {
+    let queue = FSMEventQueue::new();
+    let buttons = Rc::new(RefCell::new(Vec::new()));
+    let mut fsm = recording_fsm(buttons.clone());
+    let sender = queue.sender();
+    thread::spawn(move || post_moves(&sender, 0..50))
+        .join()
+        .unwrap();
+    queue.drain(&mut fsm);
+    assert_eq!(*buttons.borrow(), (1..50).collect::<Vec<_>>());
+}
+
+#[test]
+fn events_of_several_threads_keep_their_order() {
+    let queue = FSMEventQueue::new();
+    let buttons = Rc::new(RefCell::new(Vec::new()));
+    let mut fsm = recording_fsm(buttons.clone());
+    queue.sender().post_event(Box::new(StubMouseEvent {
+        event_type: MouseEventType::Mousemove,
+        button: -1,
+    }));
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            let sender = queue.sender();
+            thread::spawn(move || post_moves(&sender, t * 100..t * 100 + 50))
+        })
+        .collect();
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    assert_eq!(queue.drain(&mut fsm), 201);
+    let buttons = buttons.borrow();
+    assert_eq!(buttons.len(), 200);
+    for t in 0..4 {
+        let of_thread: Vec<i16> = buttons.iter().copied().filter(|b| b / 100 == t).collect();
+        assert_eq!(of_thread, (t * 100..t * 100 + 50).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn early_timeout_ignored() {
+    let clock = Rc::new(VirtualClock::new());
+    let queue = FSMEventQueue::new();
+    let mut fsm = crate::fsm! {
+        clock: clock.clone();
+        states {
+            init: init,
+            pressed: std,
+            held: terminal,
+        }
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+            pressed -> held timeout (100);
+        }
+    };
+    fsm.process(&StubEvent::press());
+    clock.advance(50);
+    queue.sender().post_timeout();
+    assert_eq!(queue.drain(&mut fsm), 1);
+    assert_eq!(fsm.current_state().name(), "pressed");
+    clock.advance(50);
+    queue.sender().post_timeout();
+    queue.drain(&mut fsm);
+    assert_eq!(fsm.current_state().name(), "init");
+}
+
+#[test]
+fn timeout_posted_by_timer_thread() {
+    let queue = FSMEventQueue::new();
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            held: terminal,
+        }
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+            pressed -> held timeout (5);
+        }
+    };
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::press());
+    let timer = FSMTimer::new(queue.sender());
+    timer.schedule(Duration::from_millis(10));
+    assert_eq!(queue.wait_and_drain(&mut fsm, Duration::from_secs(5)), 1);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn timer_posts_timeouts_in_deadline_order() {
+    let queue = FSMEventQueue::new();
+    let timer = FSMTimer::new(queue.sender());
+    timer.schedule(Duration::from_millis(20));
+    timer.schedule(Duration::from_millis(5));
+    let mut fsm = recording_fsm(Rc::default());
+    assert_eq!(queue.wait_and_drain(&mut fsm, Duration::from_secs(5)), 1);
+    assert_eq!(queue.wait_and_drain(&mut fsm, Duration::from_secs(5)), 1);
+    assert_eq!(timer.schedule(Duration::from_millis(1)), true);
+}
+
+#[test]
+fn messages_posted_while_draining_left_for_next_drain() {
+    let queue = FSMEventQueue::new();
+    let sender = queue.sender();
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            moved: std,
+        }
+        transitions {
+            init -> moved on [EventType::Mouse(MouseEventType::Mousemove)];
+            moved -> moved on [EventType::Mouse(MouseEventType::Mousemove)] action (move |_| {
+                post_moves(&sender, 0..1);
+                Ok(())
+            });
+        }
+    };
+    post_moves(&queue.sender(), 0..2);
+    assert_eq!(queue.drain(&mut fsm), 2);
+    assert_eq!(queue.drain(&mut fsm), 1);
+}
+
+#[test]
+fn wait_without_message() {
+    let queue = FSMEventQueue::new();
+    let mut fsm = recording_fsm(Rc::default());
+    assert_eq!(queue.wait_and_drain(&mut fsm, Duration::from_millis(1)), 0);
+}
+
+#[test]
+fn post_after_queue_dropped() {
+    let sender = FSMEventQueue::new().sender();
+    assert_eq!(sender.post_event(Box::new(StubEvent::press())), false);
+    assert_eq!(sender.post_timeout(), false);
+}
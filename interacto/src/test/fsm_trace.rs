@@ -0,0 +1,230 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::dom::EventType;
+use crate::fsm::{ErrorPolicy, FSMTrace, FSMTraceSubscriber, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::ConcurrentAndFSM;
+use crate::test::fsm::{key, press, press_release_fsm, release, StubEvent};
+
+fn transition(
+    previous_state: &str,
+    new_state: &str,
+    event_type: Option<EventType>,
+    timestamp: u64,
+) -> FSMTrace {
+    FSMTrace::Transition {
+        previous_state: previous_state.to_string(),
+        new_state: new_state.to_string(),
+        event_type,
+        timestamp,
+    }
+}
+
+/// Subscribes a recorder of the trace records to the FSM.
+fn record(fsm: &mut dyn FSM) -> Rc<RefCell<Vec<FSMTrace>>> {
+    let traces = Rc::new(RefCell::new(Vec::new()));
+    let recorded = traces.clone();
+    fsm.subscribe(Rc::new(RefCell::new(move |trace: &FSMTrace| {
+        recorded.borrow_mut().push(trace.clone());
+    })));
+    traces
+}
+
+#[test]
+fn traces_execution() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = press_release_fsm(clock.clone());
+    let traces = record(&mut fsm);
+    clock.set(10);
+    fsm.process(&StubEvent::press());
+    clock.set(30);
+    fsm.process(&StubEvent::release());
+    assert_eq!(
+        *traces.borrow(),
+        vec![
+            FSMTrace::Start { timestamp: 10 },
+            transition("init", "pressed", Some(press()), 10),
+            transition("pressed", "released", Some(release()), 30),
+            FSMTrace::Terminate { timestamp: 30 },
+        ]
+    );
+}
+
+#[test]
+fn traces_timeout_and_cancellation() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = press_release_fsm(clock.clone());
+    let traces = record(&mut fsm);
+    fsm.process(&StubEvent::press());
+    clock.advance(100);
+    fsm.check_timeout();
+    assert_eq!(
+        traces.borrow()[2..],
+        [
+            transition("pressed", "cancelled", None, 100),
+            FSMTrace::Cancel { timestamp: 100 },
+        ]
+    );
+}
+
+#[test]
+fn failed_state_change_not_traced() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = press_release_fsm(clock);
+    let pressed = fsm.states()[1].id();
+    fsm.set_entry_action(pressed, Box::new(|| Err("no entry".into())));
+    fsm.set_error_policy(ErrorPolicy::Cancel);
+    let traces = record(&mut fsm);
+    fsm.process(&StubEvent::press());
+    assert_eq!(
+        *traces.borrow(),
+        vec![
+            FSMTrace::Start { timestamp: 0 },
+            FSMTrace::Cancel { timestamp: 0 }
+        ]
+    );
+}
+
+#[test]
+fn no_cancel_traced_before_start() {
+    let mut fsm = crate::fsm! {
+        clock: Rc::new(VirtualClock::new());
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        starting: pressed;
+        transitions {
+            init -> pressed on [press()];
+            init -> cancelled on [key()];
+            pressed -> released on [release()];
+        }
+    };
+    let traces = record(&mut fsm);
+    fsm.process(&StubEvent::key());
+    assert_eq!(fsm.started(), false);
+    assert_eq!(
+        *traces.borrow(),
+        vec![transition("init", "cancelled", Some(key()), 0)]
+    );
+}
+
+#[test]
+fn nothing_traced_on_unprocessed_events() {
+    let mut fsm = press_release_fsm(Rc::new(VirtualClock::new()));
+    let traces = record(&mut fsm);
+    fsm.process(&StubEvent::key());
+    assert_eq!(traces.borrow().is_empty(), true);
+}
+
+#[test]
+fn unsubscribe() {
+    let mut fsm = press_release_fsm(Rc::new(VirtualClock::new()));
+    let count = Rc::new(RefCell::new(0));
+    let c = count.clone();
+    let subscriber: Rc<RefCell<dyn FSMTraceSubscriber>> =
+        Rc::new(RefCell::new(move |_: &FSMTrace| *c.borrow_mut() += 1));
+    fsm.subscribe(subscriber.clone());
+    fsm.process(&StubEvent::press());
+    fsm.unsubscribe(&subscriber);
+    fsm.process(&StubEvent::release());
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn trace_stream() {
+    let mut fsm = press_release_fsm(Rc::new(VirtualClock::new()));
+    let (sender, receiver) = mpsc::channel();
+    fsm.subscribe(Rc::new(RefCell::new(move |trace: &FSMTrace| {
+        let _ = sender.send(trace.clone());
+    })));
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(receiver.try_iter().count(), 4);
+}
+
+#[test]
+fn traces_sub_fsm_transition() {
+    let clock = Rc::new(VirtualClock::new());
+    let inner = crate::fsm! {
+        states {
+            init: init,
+            keyed: terminal,
+        }
+        transitions {
+            init -> keyed on [key()];
+        }
+    };
+    let mut fsm = crate::fsm! {
+        clock: clock;
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    };
+    let traces = record(&mut fsm);
+    fsm.process(&StubEvent::key());
+    assert_eq!(
+        *traces.borrow(),
+        vec![
+            FSMTrace::Start { timestamp: 0 },
+            transition("init", "ended", None, 0),
+            FSMTrace::Terminate { timestamp: 0 },
+        ]
+    );
+}
+
+#[test]
+fn traces_concurrent_fsm() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = ConcurrentAndFSM::new(vec![press_release_fsm(clock.clone())]);
+    fsm.set_clock(clock.clone());
+    let traces = record(&mut fsm);
+    clock.set(5);
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(
+        *traces.borrow(),
+        vec![
+            FSMTrace::Start { timestamp: 5 },
+            FSMTrace::Terminate { timestamp: 5 }
+        ]
+    );
+}
+
+#[test]
+fn trace_display() {
+    assert_eq!(
+        transition("init", "pressed", Some(press()), 10).to_string(),
+        "[10 ms] init -> pressed on Mouse(Mousedown)"
+    );
+    assert_eq!(
+        transition("pressed", "cancelled", None, 20).to_string(),
+        "[20 ms] pressed -> cancelled"
+    );
+    assert_eq!(
+        FSMTrace::Cancel { timestamp: 3 }.to_string(),
+        "[3 ms] cancel"
+    );
+    assert_eq!(FSMTrace::Cancel { timestamp: 3 }.timestamp(), 3);
+}
@@ -0,0 +1,260 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::MouseEventType;
+use crate::fsm::{StateId, FSM};
+use crate::r#impl::fsm::{
+    ConcurrentAndFSM, FSMDefect, FSMImpl, FSMValidator, MouseTransition, TransitionBase,
+    TypedTransition,
+};
+use crate::test::fsm::{
+    key, press, press_release_fsm, release, transition, StubEvent, StubMouseEvent,
+};
+
+#[test]
+fn valid_fsm() {
+    assert_eq!(
+        FSMValidator::validate(&press_release_fsm(Rc::default())),
+        Ok(())
+    );
+}
+
+#[test]
+fn unreachable_state() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+        }
+        transitions {
+            init -> released on [release()];
+            pressed -> released on [release()];
+        }
+    };
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::UnreachableState("pressed".to_string())])
+    );
+}
+
+#[test]
+fn no_path_to_end() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+        }
+        transitions {
+            init -> pressed on [press()];
+            init -> released on [release()];
+        }
+    };
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::NoPathToEnd("pressed".to_string())])
+    );
+}
+
+#[test]
+fn fsm_without_end() {
+    let fsm = FSMImpl::new();
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::NoPathToEnd("init".to_string())])
+    );
+}
+
+#[test]
+fn no_accepted_event() {
+    let mut fsm = FSMImpl::new();
+    let ended = fsm.add_terminal_state("ended", false);
+    fsm.add_transition(transition(StateId(0), ended, release()));
+    fsm.add_transition(Box::new(TransitionBase::new(
+        StateId(0),
+        ended,
+        HashSet::new(),
+        None,
+        None,
+    )));
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::NoAcceptedEvent {
+            src: "init".to_string(),
+            tgt: "ended".to_string()
+        }])
+    );
+}
+
+#[test]
+fn ambiguous_transitions() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: terminal,
+            keyed: terminal,
+        }
+        transitions {
+            init -> pressed on [press(), key()];
+            init -> keyed on [key()];
+        }
+    };
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::AmbiguousTransitions {
+            state: "init".to_string(),
+            event_type: key()
+        }])
+    );
+}
+
+#[test]
+fn guarded_transitions_are_not_ambiguous() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: terminal,
+            other: terminal,
+        }
+        transitions {
+            init -> pressed on [press()] when (|_| Ok(true));
+            init -> other on [press()];
+        }
+    };
+    assert_eq!(FSMValidator::validate(&fsm), Ok(()));
+}
+
+#[test]
+fn labelled_unguarded_transitions_are_ambiguous() {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_terminal_state("pressed", false);
+    let other = fsm.add_terminal_state("other", false);
+    for tgt in [pressed, other] {
+        fsm.add_transition(Box::new(
+            TransitionBase::new(init, tgt, HashSet::from([press()]), None, None)
+                .with_guard_label("far"),
+        ));
+    }
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::AmbiguousTransitions {
+            state: "init".to_string(),
+            event_type: press()
+        }])
+    );
+}
+
+#[test]
+fn typed_transitions_are_not_ambiguous() {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_terminal_state("pressed", false);
+    let other = fsm.add_terminal_state("other", false);
+    fsm.add_transition(Box::new(TypedTransition::<StubEvent>::new(
+        init,
+        pressed,
+        HashSet::from([press()]),
+        None,
+        None,
+    )));
+    fsm.add_transition(Box::new(TypedTransition::<StubMouseEvent>::new(
+        init,
+        other,
+        HashSet::from([press()]),
+        None,
+        None,
+    )));
+    assert_eq!(FSMValidator::validate(&fsm), Ok(()));
+}
+
+#[test]
+fn filtered_transitions_are_not_ambiguous() {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let left = fsm.add_terminal_state("left", false);
+    let right = fsm.add_terminal_state("right", false);
+    for (tgt, button) in [(left, 0), (right, 2)] {
+        fsm.add_transition(Box::new(
+            MouseTransition::new(init, tgt, [MouseEventType::Mousedown], None, None)
+                .with_button(button),
+        ));
+    }
+    assert_eq!(FSMValidator::validate(&fsm), Ok(()));
+}
+
+#[test]
+fn validates_sub_fsms() {
+    let mut inner = press_release_fsm(Rc::default());
+    inner.add_std_state("lost", false);
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    };
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![
+            FSMDefect::UnreachableState("lost".to_string()),
+            FSMDefect::NoPathToEnd("lost".to_string())
+        ])
+    );
+}
+
+#[test]
+fn validates_concurrent_fsms() {
+    let fsm = ConcurrentAndFSM::new(vec![press_release_fsm(Rc::default()), FSMImpl::new()]);
+    assert_eq!(
+        FSMValidator::validate(&fsm),
+        Err(vec![FSMDefect::NoPathToEnd("init".to_string())])
+    );
+}
+
+#[test]
+fn visitor_collects_defects() {
+    let mut validator = FSMValidator::new();
+    FSMImpl::new().accept_visitor(&mut validator);
+    press_release_fsm(Rc::default()).accept_visitor(&mut validator);
+    assert_eq!(validator.defects().len(), 1);
+}
+
+#[test]
+fn defect_messages() {
+    assert_eq!(
+        FSMDefect::AmbiguousTransitions {
+            state: "init".to_string(),
+            event_type: press()
+        }
+        .to_string(),
+        "several unguarded transitions leave state 'init' on Mouse(Mousedown)"
+    );
+    assert_eq!(
+        FSMDefect::UnreachableState("s".to_string()).to_string(),
+        "state 's' is unreachable"
+    );
+}
+
+#[test]
+#[should_panic]
+fn starting_state_must_be_in_fsm() {
+    let mut fsm = FSMImpl::new();
+    fsm.set_starting_state(StateId(1));
+}
@@ -0,0 +1,816 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::binding::{Binding, VisitorBinding};
+use crate::checker::{RuleName, Severity};
+use crate::command::{Cmd, Command, CustomCmd};
+use crate::dom::{
+    Event, EventType, HeadlessTree, HeadlessUI, InteractionOptions, KeyEventType,
+    KeyboardEventImpl, MouseEventImpl, MouseEventType, Node, NodeId, NodeTree, Point, PointerData,
+    Rect,
+};
+use crate::fsm::FSM;
+use crate::interaction::{Interaction, InteractionData, VisitorInteraction};
+use crate::linearhistory::LinearHistoryImpl;
+use crate::r#impl::fsm::FSMImpl;
+use crate::test::fsm::{press, press_release_fsm, release, StubFSMHandler};
+use crate::undoble::Undoable;
+use crate::undohistory::{UndoHistory, UndoHistoryBase};
+
+fn mouse_move() -> EventType {
+    EventType::Mouse(MouseEventType::Mousemove)
+}
+
+fn mouse(event_type: MouseEventType, x: f64, y: f64) -> MouseEventImpl {
+    MouseEventImpl::new(event_type, PointerData::new(Point::new(x, y)))
+}
+
+/// A window with a toolbar made of two buttons, above a canvas that contains a shape.
+struct Editor {
+    ui: HeadlessUI,
+    window: NodeId,
+    add_button: NodeId,
+    clear_button: NodeId,
+    canvas: NodeId,
+    shape: NodeId,
+}
+
+impl Editor {
+    fn new() -> Self {
+        let mut tree = HeadlessTree::new("window", Rect::new(0.0, 0.0, 800.0, 600.0));
+        let window = tree.root();
+        let toolbar = tree
+            .append_child(window, "toolbar", Rect::new(0.0, 0.0, 800.0, 40.0))
+            .unwrap();
+        let add_button = tree
+            .append_child(toolbar, "button", Rect::new(0.0, 0.0, 40.0, 40.0))
+            .unwrap();
+        let clear_button = tree
+            .append_child(toolbar, "button", Rect::new(40.0, 0.0, 40.0, 40.0))
+            .unwrap();
+        let canvas = tree
+            .append_child(window, "canvas", Rect::new(0.0, 40.0, 800.0, 560.0))
+            .unwrap();
+        let shape = tree
+            .append_child(canvas, "rect", Rect::new(100.0, 100.0, 50.0, 50.0))
+            .unwrap();
+        tree.get_mut(add_button).unwrap().set_attribute("id", "add");
+        tree.get_mut(clear_button)
+            .unwrap()
+            .set_attribute("id", "clear");
+        Self {
+            ui: HeadlessUI::new(tree),
+            window,
+            add_button,
+            clear_button,
+            canvas,
+            shape,
+        }
+    }
+
+    fn click_at(&mut self, x: f64, y: f64) {
+        self.ui
+            .fire_at(Point::new(x, y), &mouse(MouseEventType::Mousedown, x, y));
+        self.ui
+            .fire_at(Point::new(x, y), &mouse(MouseEventType::Mouseup, x, y));
+    }
+}
+
+/// The FSM of a button click: a press then a release.
+fn click_fsm() -> Rc<RefCell<dyn FSM>> {
+    Rc::new(RefCell::new(press_release_fsm(Rc::default())))
+}
+
+/// Increments a counter.
+struct Increment {
+    counter: Rc<Cell<i32>>,
+}
+
+impl CustomCmd for Increment {
+    fn execution(&mut self) {
+        self.counter.set(self.counter.get() + 1);
+    }
+}
+
+impl Undoable for Increment {
+    fn undo(&mut self) {
+        self.counter.set(self.counter.get() - 1);
+    }
+
+    fn redo(&mut self) {
+        self.execution();
+    }
+
+    fn get_undo_name(&self) -> String {
+        "increment".to_string()
+    }
+}
+
+/// Moves a node of the UI by the distance the pointer travelled.
+struct MoveNode {
+    bounds: Rc<Cell<Rect>>,
+    positions: Rc<RefCell<Vec<Point>>>,
+    from: Rect,
+    to: Rect,
+}
+
+impl MoveNode {
+    fn new(bounds: &Rc<Cell<Rect>>, positions: &Rc<RefCell<Vec<Point>>>) -> Self {
+        Self {
+            bounds: bounds.clone(),
+            positions: positions.clone(),
+            from: bounds.get(),
+            to: bounds.get(),
+        }
+    }
+}
+
+impl CustomCmd for MoveNode {
+    fn create_memento(&mut self) {
+        let points = self.positions.borrow();
+        let (first, last) = (points[0], points[points.len() - 1]);
+        self.from = self.bounds.get();
+        self.to = Rect::new(
+            self.from.x + last.x - first.x,
+            self.from.y + last.y - first.y,
+            self.from.width,
+            self.from.height,
+        );
+    }
+
+    fn execution(&mut self) {
+        self.bounds.set(self.to);
+    }
+}
+
+impl Undoable for MoveNode {
+    fn undo(&mut self) {
+        self.bounds.set(self.from);
+    }
+
+    fn redo(&mut self) {
+        self.execution();
+    }
+
+    fn get_undo_name(&self) -> String {
+        "move".to_string()
+    }
+}
+
+/// The data of the interactions of these tests: the commands read what they need themselves.
+struct NoData;
+
+impl InteractionData for NoData {}
+
+/// A user interaction run by its own FSM, whose notifications it records for its binding.
+struct FSMInteraction {
+    fsm: FSMImpl,
+    notifications: Rc<RefCell<StubFSMHandler>>,
+    data: NoData,
+    nodes: HashSet<()>,
+    stop_immediate_propagation: bool,
+    prevent_default: bool,
+    activated: bool,
+}
+
+impl FSMInteraction {
+    fn new(mut fsm: FSMImpl) -> Self {
+        let notifications = StubFSMHandler::new();
+        fsm.add_handler(notifications.clone());
+        Self {
+            fsm,
+            notifications,
+            data: NoData,
+            nodes: HashSet::new(),
+            stop_immediate_propagation: false,
+            prevent_default: false,
+            activated: true,
+        }
+    }
+
+    /// Makes the FSM process the event. Returns whether it did, and what the FSM notified.
+    fn process(&mut self, event: &dyn Event) -> (bool, Vec<&'static str>) {
+        let processed = self.activated && self.fsm.process(event);
+        let notifications = std::mem::take(&mut self.notifications.borrow_mut().calls);
+        (processed, notifications)
+    }
+}
+
+impl Interaction<NoData> for FSMInteraction {
+    fn set_stop_immediate_propagation(&mut self, stop: bool) {
+        self.stop_immediate_propagation = stop;
+    }
+
+    fn set_prevent_default(&mut self, prevent: bool) {
+        self.prevent_default = prevent;
+    }
+
+    fn fsm(&self) -> &dyn FSM {
+        &self.fsm
+    }
+
+    fn data(&self) -> &NoData {
+        &self.data
+    }
+
+    fn name(&self) -> &str {
+        "fsm interaction"
+    }
+
+    fn registered_nodes(&self) -> &HashSet<()> {
+        &self.nodes
+    }
+
+    fn dynamic_registered_nodes(&self) -> &HashSet<()> {
+        &self.nodes
+    }
+
+    fn is_running(&self) -> bool {
+        self.activated && self.fsm.started()
+    }
+
+    fn is_activated(&self) -> bool {
+        self.activated
+    }
+
+    fn set_activated(&mut self, activated: bool) {
+        self.activated = activated;
+        if !activated {
+            self.fsm.full_reinit();
+        }
+    }
+
+    fn log(&mut self, log: bool) {
+        self.fsm.set_log(log);
+    }
+
+    // The headless UI registers the interactions to its nodes (see `FSMBinding::register`).
+    fn register_to_nodes(&mut self, _widgets: &[()]) {}
+
+    fn register_to_node_children(&mut self, _element_to_observe: &dyn Node) {}
+
+    fn set_throttle_timeout(&mut self, _timeout: u64) {}
+
+    fn full_reinit(&mut self) {
+        self.fsm.full_reinit();
+    }
+
+    fn reinit(&mut self) {
+        self.fsm.reinit();
+    }
+
+    fn reinit_data(&mut self) {}
+
+    fn uninstall(&mut self) {
+        self.fsm.uninstall();
+    }
+
+    fn accept_visitor(&self, _visitor: &dyn VisitorInteraction) {}
+}
+
+/// A binding: produces a command when its interaction starts, executes it and stores it
+/// in the undo history when the interaction ends, and cancels it when the interaction is cancelled.
+struct FSMBinding<C: Command + Undoable + 'static> {
+    interaction: FSMInteraction,
+    produce: Box<dyn Fn() -> C>,
+    command: Option<C>,
+    history: Rc<RefCell<LinearHistoryImpl>>,
+    linter_rules: HashMap<RuleName, Severity>,
+    times_ended: usize,
+    times_cancelled: usize,
+    uninstalled: Cell<bool>,
+}
+
+impl<C: Command + Undoable + 'static> FSMBinding<C> {
+    fn new(
+        fsm: FSMImpl,
+        history: &Rc<RefCell<LinearHistoryImpl>>,
+        produce: impl Fn() -> C + 'static,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            interaction: FSMInteraction::new(fsm),
+            produce: Box::new(produce),
+            command: None,
+            history: history.clone(),
+            linter_rules: HashMap::new(),
+            times_ended: 0,
+            times_cancelled: 0,
+            uninstalled: Cell::new(false),
+        }))
+    }
+
+    /// Makes the binding handle the events dispatched to the node (and its descendants),
+    /// during the capture phase if asked, and alter their propagation as its interaction asks.
+    fn register(binding: &Rc<RefCell<Self>>, ui: &mut HeadlessUI, node: NodeId, capture: bool) {
+        let options = {
+            let binding = binding.borrow();
+            InteractionOptions {
+                capture,
+                stop_immediate_propagation: binding.interaction.stop_immediate_propagation,
+                prevent_default: binding.interaction.prevent_default,
+            }
+        };
+        let process = {
+            let binding = binding.clone();
+            move |event: &dyn Event| binding.borrow_mut().process(event)
+        };
+        ui.register(node, binding.borrow().interaction.fsm(), options, process);
+    }
+
+    fn process(&mut self, event: &dyn Event) -> bool {
+        if self.uninstalled.get() {
+            return false;
+        }
+        let (processed, notifications) = self.interaction.process(event);
+        for notification in notifications {
+            match notification {
+                "start" => self.command = Some((self.produce)()),
+                "stop" => {
+                    if let Some(mut cmd) = self.command.take() {
+                        if cmd.execute() {
+                            cmd.done();
+                            self.history.borrow_mut().add(Box::new(cmd));
+                        }
+                    }
+                    self.times_ended += 1;
+                }
+                "cancel" => {
+                    if let Some(mut cmd) = self.command.take() {
+                        cmd.cancel();
+                    }
+                    self.times_cancelled += 1;
+                }
+                _ => {}
+            }
+        }
+        processed
+    }
+}
+
+impl<C: Command + Undoable + 'static> Binding<C, FSMInteraction, (), NoData> for FSMBinding<C> {
+    fn name(&self) -> &str {
+        "fsm binding"
+    }
+
+    fn log_usage(&self) -> bool {
+        false
+    }
+
+    fn log_binding(&self) -> bool {
+        false
+    }
+
+    fn log_cmd(&self) -> bool {
+        false
+    }
+
+    fn accumulator(&self) -> &() {
+        &()
+    }
+
+    fn interaction(&self) -> &FSMInteraction {
+        &self.interaction
+    }
+
+    fn command(&self) -> Option<&C> {
+        self.command.as_ref()
+    }
+
+    fn linter_rules(&self) -> &HashMap<RuleName, Severity> {
+        &self.linter_rules
+    }
+
+    fn activated(&self) -> bool {
+        self.interaction.is_activated() && !self.uninstalled.get()
+    }
+
+    fn running(&self) -> bool {
+        self.activated() && self.interaction.is_running()
+    }
+
+    fn continuous_cmd_execution(&self) -> bool {
+        false
+    }
+
+    fn times_ended(&self) -> usize {
+        self.times_ended
+    }
+
+    fn times_cancelled(&self) -> usize {
+        self.times_cancelled
+    }
+
+    fn is_when_defined(&self) -> bool {
+        false
+    }
+
+    fn uninstall_binding(&self) {
+        self.uninstalled.set(true);
+    }
+
+    fn accept_visitor(&self, visitor: &dyn VisitorBinding<C, FSMInteraction, (), NoData>) {
+        visitor.visit_binding(self);
+    }
+}
+
+/// A binding that increments the counter on each click on the nodes it is registered to.
+fn increment_binding(
+    history: &Rc<RefCell<LinearHistoryImpl>>,
+    counter: &Rc<Cell<i32>>,
+) -> Rc<RefCell<FSMBinding<Cmd<Increment>>>> {
+    let counter = counter.clone();
+    FSMBinding::new(press_release_fsm(Rc::default()), history, move || {
+        Cmd::new(Increment {
+            counter: counter.clone(),
+        })
+    })
+}
+
+#[test]
+fn rect() {
+    let rect = Rect::new(10.0, 20.0, 30.0, 40.0);
+    assert_eq!(rect.contains(Point::new(10.0, 20.0)), true);
+    assert_eq!(rect.contains(Point::new(39.9, 59.9)), true);
+    assert_eq!(rect.contains(Point::new(40.0, 30.0)), false);
+    assert_eq!(rect.contains(Point::new(5.0, 30.0)), false);
+    assert_eq!(rect.center(), Point::new(25.0, 40.0));
+}
+
+#[test]
+fn tree_structure() {
+    let editor = Editor::new();
+    let tree = &editor.ui.tree;
+    assert_eq!(tree.len(), 6);
+    assert_eq!(tree.get(editor.window).unwrap().tag(), "window");
+    assert_eq!(
+        tree.find_by_tag("button"),
+        vec![editor.add_button, editor.clear_button]
+    );
+    assert_eq!(
+        tree.find_by_attribute("id", "clear"),
+        Some(editor.clear_button)
+    );
+    assert_eq!(tree.find_by_attribute("id", "undo"), None);
+    assert_eq!(
+        tree.propagation_path(editor.shape),
+        vec![editor.shape, editor.canvas, editor.window]
+    );
+}
+
+#[test]
+fn attributes() {
+    let mut editor = Editor::new();
+    let node = editor.ui.tree.get_mut(editor.shape).unwrap();
+    node.set_attribute("fill", "red");
+    node.set_attribute("class", "selected");
+    assert_eq!(node.attribute("fill"), Some("red"));
+    assert_eq!(
+        node.attributes().collect::<Vec<_>>(),
+        vec![("class", "selected"), ("fill", "red")]
+    );
+    assert_eq!(node.remove_attribute("fill"), Some("red".to_string()));
+    assert_eq!(node.attribute("fill"), None);
+}
+
+#[test]
+fn append_child_to_unknown_node() {
+    let mut tree = HeadlessTree::new("window", Rect::default());
+    assert_eq!(
+        tree.append_child(NodeId(42), "button", Rect::default()),
+        None
+    );
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn remove_subtree() {
+    let mut editor = Editor::new();
+    let tree = &mut editor.ui.tree;
+    assert_eq!(
+        tree.remove(editor.canvas),
+        vec![editor.canvas, editor.shape]
+    );
+    assert_eq!(tree.get(editor.shape).is_none(), true);
+    assert_eq!(tree.descendants(editor.window).len(), 4);
+    assert_eq!(tree.remove(editor.window).is_empty(), true);
+    assert_eq!(tree.remove(editor.canvas).is_empty(), true);
+}
+
+#[test]
+fn hit_test() {
+    let editor = Editor::new();
+    let tree = &editor.ui.tree;
+    assert_eq!(
+        tree.hit_test(Point::new(50.0, 10.0)),
+        Some(editor.clear_button)
+    );
+    assert_eq!(tree.hit_test(Point::new(120.0, 130.0)), Some(editor.shape));
+    assert_eq!(tree.hit_test(Point::new(300.0, 300.0)), Some(editor.canvas));
+    assert_eq!(
+        tree.hit_test(Point::new(300.0, 10.0)),
+        tree.find_by_tag("toolbar").first().copied()
+    );
+    assert_eq!(tree.hit_test(Point::new(900.0, 10.0)), None);
+}
+
+#[test]
+fn hit_test_last_sibling_on_top() {
+    let mut tree = HeadlessTree::new("window", Rect::new(0.0, 0.0, 100.0, 100.0));
+    let below = tree
+        .append_child(tree.root(), "rect", Rect::new(0.0, 0.0, 50.0, 50.0))
+        .unwrap();
+    let above = tree
+        .append_child(tree.root(), "rect", Rect::new(25.0, 25.0, 50.0, 50.0))
+        .unwrap();
+    assert_eq!(tree.hit_test(Point::new(30.0, 30.0)), Some(above));
+    assert_eq!(tree.hit_test(Point::new(10.0, 10.0)), Some(below));
+}
+
+#[test]
+fn register_fsm_listens_to_accepted_events() {
+    let mut editor = Editor::new();
+    let listeners = editor.ui.register_fsm(
+        editor.add_button,
+        click_fsm(),
+        InteractionOptions::default(),
+    );
+    // Mouse down and up, and the key press that cancels the click.
+    assert_eq!(listeners.len(), 3);
+}
+
+#[test]
+fn register_shares_process_between_event_types() {
+    let mut editor = Editor::new();
+    let handler = StubFSMHandler::new();
+    let mut fsm = press_release_fsm(Rc::default());
+    fsm.add_handler(handler.clone());
+    let listened = fsm.instance();
+    // The FSM is owned by the closure: the press and the release must reach the same FSM.
+    editor.ui.register(
+        editor.add_button,
+        &listened,
+        InteractionOptions::default(),
+        move |event: &dyn Event| fsm.process(event),
+    );
+
+    editor.click_at(20.0, 20.0);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn button_binding_produces_undoable_commands() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let binding = increment_binding(&history, &counter);
+    FSMBinding::register(&binding, &mut editor.ui, editor.add_button, false);
+
+    editor.click_at(20.0, 20.0);
+    editor.click_at(20.0, 20.0);
+    editor.click_at(60.0, 20.0);
+    assert_eq!(counter.get(), 2);
+    assert_eq!(binding.borrow().times_ended(), 2);
+    assert_eq!(history.borrow().get_undo().len(), 2);
+
+    history.borrow_mut().undo();
+    assert_eq!(counter.get(), 1);
+    history.borrow_mut().redo();
+    assert_eq!(counter.get(), 2);
+}
+
+#[test]
+fn binding_cancels_its_command() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let binding = increment_binding(&history, &counter);
+    FSMBinding::register(&binding, &mut editor.ui, editor.add_button, false);
+
+    editor.ui.fire(
+        editor.add_button,
+        &mouse(MouseEventType::Mousedown, 20.0, 20.0),
+    );
+    assert_eq!(binding.borrow().running(), true);
+    assert_eq!(binding.borrow().command().is_some(), true);
+
+    editor.ui.fire(
+        editor.add_button,
+        &KeyboardEventImpl::new(KeyEventType::Keydown, "Escape", "Escape"),
+    );
+    assert_eq!(binding.borrow().running(), false);
+    assert_eq!(binding.borrow().command().is_none(), true);
+    assert_eq!(binding.borrow().times_cancelled(), 1);
+    assert_eq!(binding.borrow().times_ended(), 0);
+    assert_eq!(counter.get(), 0);
+    assert_eq!(history.borrow().get_undo().is_empty(), true);
+}
+
+#[test]
+fn uninstalled_binding_ignores_events() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let binding = increment_binding(&history, &counter);
+    FSMBinding::register(&binding, &mut editor.ui, editor.add_button, false);
+
+    binding.borrow().uninstall_binding();
+    editor.click_at(20.0, 20.0);
+    assert_eq!(binding.borrow().activated(), false);
+    assert_eq!(binding.borrow().times_ended(), 0);
+    assert_eq!(counter.get(), 0);
+}
+
+#[test]
+fn binding_on_several_nodes() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let binding = increment_binding(&history, &counter);
+    for button in editor.ui.tree.find_by_tag("button") {
+        FSMBinding::register(&binding, &mut editor.ui, button, false);
+    }
+    editor.click_at(20.0, 20.0);
+    editor.click_at(60.0, 20.0);
+    editor.click_at(300.0, 300.0);
+    assert_eq!(counter.get(), 2);
+}
+
+#[test]
+fn drag_binding_moves_node() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let initial = editor.ui.tree.get(editor.shape).unwrap().bounds();
+    let bounds = Rc::new(Cell::new(initial));
+    let shape_bounds = bounds.clone();
+    let positions: Rc<RefCell<Vec<Point>>> = Rc::default();
+    let record = |positions: &Rc<RefCell<Vec<Point>>>| {
+        let positions = positions.clone();
+        move |event: &dyn Event| {
+            let position = event
+                .as_mouse_event()
+                .map(|e| e.position())
+                .unwrap_or_default();
+            positions.borrow_mut().push(position);
+            Ok(())
+        }
+    };
+    let on_press = record(&positions);
+    let on_move = record(&positions);
+    let on_move_again = record(&positions);
+    let on_release = record(&positions);
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            dragged: std,
+            released: terminal,
+        }
+        transitions {
+            init -> pressed on [press()] action (on_press);
+            pressed -> dragged on [mouse_move()] action (on_move);
+            dragged -> dragged on [mouse_move()] action (on_move_again);
+            dragged -> released on [release()] action (on_release);
+        }
+    };
+    let binding = FSMBinding::new(fsm, &history, move || {
+        Cmd::new(MoveNode::new(&shape_bounds, &positions))
+    });
+    FSMBinding::register(&binding, &mut editor.ui, editor.shape, false);
+
+    editor.ui.fire_at(
+        Point::new(110.0, 110.0),
+        &mouse(MouseEventType::Mousedown, 110.0, 110.0),
+    );
+    editor.ui.fire_at(
+        Point::new(130.0, 120.0),
+        &mouse(MouseEventType::Mousemove, 130.0, 120.0),
+    );
+    editor.ui.fire_at(
+        Point::new(140.0, 125.0),
+        &mouse(MouseEventType::Mousemove, 140.0, 125.0),
+    );
+    editor.ui.fire_at(
+        Point::new(140.0, 125.0),
+        &mouse(MouseEventType::Mouseup, 140.0, 125.0),
+    );
+    assert_eq!(bounds.get(), Rect::new(130.0, 115.0, 50.0, 50.0));
+
+    history.borrow_mut().undo();
+    assert_eq!(bounds.get(), initial);
+}
+
+#[test]
+fn stop_immediate_propagation() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let first = increment_binding(&history, &counter);
+    let second = increment_binding(&history, &counter);
+    let on_window = increment_binding(&history, &counter);
+    first
+        .borrow_mut()
+        .interaction
+        .set_stop_immediate_propagation(true);
+    FSMBinding::register(&first, &mut editor.ui, editor.add_button, false);
+    FSMBinding::register(&second, &mut editor.ui, editor.add_button, false);
+    FSMBinding::register(&on_window, &mut editor.ui, editor.window, false);
+
+    editor.click_at(20.0, 20.0);
+    assert_eq!(counter.get(), 1);
+    assert_eq!(first.borrow().times_ended(), 1);
+    assert_eq!(second.borrow().times_ended(), 0);
+    assert_eq!(on_window.borrow().times_ended(), 0);
+}
+
+#[test]
+fn events_not_processed_propagate() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let on_button = increment_binding(&history, &counter);
+    let on_window = increment_binding(&history, &counter);
+    on_button
+        .borrow_mut()
+        .interaction
+        .set_stop_immediate_propagation(true);
+    FSMBinding::register(&on_button, &mut editor.ui, editor.add_button, false);
+    FSMBinding::register(&on_window, &mut editor.ui, editor.window, false);
+
+    // A release alone is not processed by the FSM of the button, so it reaches the window.
+    let outcome = editor.ui.fire(
+        editor.add_button,
+        &mouse(MouseEventType::Mouseup, 20.0, 20.0),
+    );
+    assert_eq!(outcome.propagation_stopped, false);
+    assert_eq!(outcome.listeners_called, 2);
+}
+
+#[test]
+fn prevent_default() {
+    let mut editor = Editor::new();
+    let options = InteractionOptions {
+        prevent_default: true,
+        ..InteractionOptions::default()
+    };
+    editor.ui.register_fsm(editor.canvas, click_fsm(), options);
+    let press = mouse(MouseEventType::Mousedown, 300.0, 300.0);
+    let outcome = editor.ui.fire_at(Point::new(300.0, 300.0), &press).unwrap();
+    assert_eq!(outcome.default_prevented, true);
+    assert_eq!(outcome.propagation_stopped, false);
+    let outcome = editor.ui.fire(editor.add_button, &press);
+    assert_eq!(outcome.default_prevented, false);
+}
+
+#[test]
+fn capture_interaction_runs_first() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let on_canvas = increment_binding(&history, &counter);
+    let on_shape = increment_binding(&history, &counter);
+    on_canvas
+        .borrow_mut()
+        .interaction
+        .set_stop_immediate_propagation(true);
+    FSMBinding::register(&on_canvas, &mut editor.ui, editor.canvas, true);
+    FSMBinding::register(&on_shape, &mut editor.ui, editor.shape, false);
+
+    editor.click_at(120.0, 120.0);
+    assert_eq!(counter.get(), 1);
+    assert_eq!(on_shape.borrow().times_ended(), 0);
+}
+
+#[test]
+fn removed_node_loses_its_interactions() {
+    let mut editor = Editor::new();
+    let history = Rc::new(RefCell::new(LinearHistoryImpl::new()));
+    let counter = Rc::new(Cell::new(0));
+    let binding = increment_binding(&history, &counter);
+    FSMBinding::register(&binding, &mut editor.ui, editor.shape, false);
+
+    assert_eq!(
+        editor.ui.remove(editor.canvas),
+        vec![editor.canvas, editor.shape]
+    );
+    let outcome = editor.ui.fire(
+        editor.shape,
+        &mouse(MouseEventType::Mousedown, 120.0, 120.0),
+    );
+    assert_eq!(outcome.listeners_called, 0);
+    editor.click_at(120.0, 120.0);
+    assert_eq!(counter.get(), 0);
+}
@@ -0,0 +1,112 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::{EventType, KeyEventType, Modifiers};
+use crate::fsm::{StateId, Transition};
+use crate::r#impl::fsm::KeyTransition;
+use crate::test::fsm::{StubEvent, StubKeyEvent};
+
+fn transition() -> KeyTransition {
+    KeyTransition::new(StateId(0), StateId(1), [KeyEventType::Keydown], None, None)
+}
+
+fn key_a() -> StubKeyEvent {
+    StubKeyEvent::new(KeyEventType::Keydown, "a", "KeyA")
+}
+
+#[test]
+fn accepted_events() {
+    assert_eq!(
+        *transition().get_accepted_events(),
+        HashSet::from([EventType::Key(KeyEventType::Keydown)])
+    );
+}
+
+#[test]
+fn accepts_key_events_of_its_types() {
+    let tr = transition();
+    assert_eq!(tr.accept(&key_a()), true);
+    assert_eq!(
+        tr.accept(&StubKeyEvent::new(KeyEventType::Keyup, "a", "KeyA")),
+        false
+    );
+    assert_eq!(tr.accept(&StubEvent::key()), false);
+}
+
+#[test]
+fn key() {
+    let tr = transition().with_key("a");
+    assert_eq!(tr.accept(&key_a()), true);
+    assert_eq!(
+        tr.accept(&StubKeyEvent::new(KeyEventType::Keydown, "b", "KeyB")),
+        false
+    );
+}
+
+#[test]
+fn code() {
+    let tr = transition().with_code("KeyA");
+    assert_eq!(
+        tr.accept(&StubKeyEvent::new(KeyEventType::Keydown, "A", "KeyA")),
+        true
+    );
+    assert_eq!(
+        tr.accept(&StubKeyEvent::new(KeyEventType::Keydown, "q", "KeyQ")),
+        false
+    );
+}
+
+#[test]
+fn modifiers() {
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+    let tr = transition().with_key("z").with_modifiers(ctrl);
+    let mut event = StubKeyEvent::new(KeyEventType::Keydown, "z", "KeyZ");
+    assert_eq!(tr.execute(&event).unwrap(), None);
+    event.modifiers = ctrl;
+    assert_eq!(tr.execute(&event).unwrap(), Some(StateId(1)));
+    event.modifiers.shift = true;
+    assert_eq!(tr.execute(&event).unwrap(), None);
+}
+
+#[test]
+fn guard_label() {
+    assert_eq!(
+        transition().with_key("a").with_code("KeyA").guard_label(),
+        Some("key \"a\" && code \"KeyA\"")
+    );
+}
+
+#[test]
+fn action_gets_key_events() {
+    let key = Rc::new(RefCell::new(String::new()));
+    let k = key.clone();
+    let tr = KeyTransition::new(
+        StateId(0),
+        StateId(1),
+        [KeyEventType::Keydown],
+        Some(Box::new(move |e| {
+            *k.borrow_mut() = e.key().to_string();
+            Ok(())
+        })),
+        None,
+    );
+    tr.execute(&key_a()).unwrap();
+    assert_eq!(*key.borrow(), "a");
+}
@@ -0,0 +1,150 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::linearhistory::LinearHistoryImpl;
+use crate::undoble::Undoable;
+use crate::undohistory::{UndoHistory, UndoHistoryBase};
+
+/// An undoable object that logs its undo and redo calls.
+struct StubUndoable {
+    name: String,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl Undoable for StubUndoable {
+    fn undo(&mut self) {
+        self.log.borrow_mut().push(format!("undo {}", self.name));
+    }
+
+    fn redo(&mut self) {
+        self.log.borrow_mut().push(format!("redo {}", self.name));
+    }
+
+    fn get_undo_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+fn history_with(names: &[&str]) -> (LinearHistoryImpl, Rc<RefCell<Vec<String>>>) {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut history = LinearHistoryImpl::new();
+    for name in names {
+        history.add(Box::new(StubUndoable {
+            name: name.to_string(),
+            log: log.clone(),
+        }));
+    }
+    (history, log)
+}
+
+fn names(undoables: &[Box<dyn Undoable>]) -> Vec<String> {
+    undoables.iter().map(|u| u.get_undo_name()).collect()
+}
+
+#[test]
+fn empty_history() {
+    let history = LinearHistoryImpl::new();
+    assert_eq!(history.get_last_undo().is_none(), true);
+    assert_eq!(history.get_last_redo().is_none(), true);
+    assert_eq!(history.get_size_max(), 100);
+}
+
+#[test]
+fn undo_redo_on_empty_history() {
+    let (mut history, log) = history_with(&[]);
+    history.undo();
+    history.redo();
+    assert_eq!(log.borrow().is_empty(), true);
+}
+
+#[test]
+fn add() {
+    let (history, _) = history_with(&["a", "b"]);
+    assert_eq!(names(history.get_undo()), vec!["a", "b"]);
+    assert_eq!(history.get_last_undo().unwrap().get_undo_name(), "b");
+}
+
+#[test]
+fn undo() {
+    let (mut history, log) = history_with(&["a", "b"]);
+    history.undo();
+    assert_eq!(*log.borrow(), vec!["undo b"]);
+    assert_eq!(names(history.get_undo()), vec!["a"]);
+    assert_eq!(history.get_last_redo().unwrap().get_undo_name(), "b");
+}
+
+#[test]
+fn redo() {
+    let (mut history, log) = history_with(&["a", "b"]);
+    history.undo();
+    history.undo();
+    history.redo();
+    assert_eq!(*log.borrow(), vec!["undo b", "undo a", "redo a"]);
+    assert_eq!(names(history.get_undo()), vec!["a"]);
+    assert_eq!(names(history.get_redo()), vec!["b"]);
+}
+
+#[test]
+fn add_clears_redos() {
+    let (mut history, log) = history_with(&["a"]);
+    history.undo();
+    history.add(Box::new(StubUndoable {
+        name: "b".to_string(),
+        log,
+    }));
+    assert_eq!(history.get_redo().is_empty(), true);
+}
+
+#[test]
+fn clear() {
+    let (mut history, _) = history_with(&["a", "b"]);
+    history.undo();
+    history.clear();
+    assert_eq!(history.get_undo().is_empty(), true);
+    assert_eq!(history.get_redo().is_empty(), true);
+}
+
+#[test]
+fn add_removes_oldest_when_full() {
+    let (mut history, log) = history_with(&[]);
+    history.set_size_max(2);
+    for name in ["a", "b", "c"] {
+        history.add(Box::new(StubUndoable {
+            name: name.to_string(),
+            log: log.clone(),
+        }));
+    }
+    assert_eq!(names(history.get_undo()), vec!["b", "c"]);
+}
+
+#[test]
+fn set_size_max_removes_oldest() {
+    let (mut history, _) = history_with(&["a", "b", "c"]);
+    history.set_size_max(1);
+    assert_eq!(history.get_size_max(), 1);
+    assert_eq!(names(history.get_undo()), vec!["c"]);
+}
+
+#[test]
+fn size_max_zero_stores_nothing() {
+    let (mut history, log) = history_with(&["a"]);
+    history.set_size_max(0);
+    history.add(Box::new(StubUndoable {
+        name: "b".to_string(),
+        log,
+    }));
+    assert_eq!(history.get_undo().is_empty(), true);
+}
@@ -0,0 +1,134 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::rc::Rc;
+
+use crate::dom::{EventType, MouseEventType};
+use crate::fsm::FSM;
+use crate::r#impl::fsm::{ConcurrentXorFSM, FSMImpl, MermaidVisitor};
+use crate::test::fsm::press_release_fsm;
+
+fn render(fsm: &dyn FSM) -> String {
+    let mut visitor = MermaidVisitor::new();
+    fsm.accept_visitor(&mut visitor);
+    visitor.output().to_string()
+}
+
+#[test]
+fn renders_fsm() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: std,
+            released: terminal,
+            cancelled: cancelling,
+        }
+        starting: pressed;
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)];
+            pressed -> released on [EventType::Mouse(MouseEventType::Mouseup)] when "left button" (|_| Ok(true));
+            pressed -> cancelled timeout (1000);
+        }
+    };
+    assert_eq!(
+        render(&fsm),
+        "stateDiagram-v2
+    classDef starting stroke-width:3px,font-weight:bold
+    classDef cancelling fill:#f99
+    state \"init\" as f0_s0
+    [*] --> f0_s0
+    state \"pressed\" as f0_s1
+    class f0_s1 starting
+    state \"released\" as f0_s2
+    f0_s2 --> [*]
+    state \"cancelled\" as f0_s3
+    class f0_s3 cancelling
+    f0_s3 --> [*]
+    f0_s0 --> f0_s1 : mousedown
+    f0_s1 --> f0_s2 : mouseup [left button]
+    f0_s1 --> f0_s3 : timeout (1000 ms)
+"
+    );
+}
+
+#[test]
+fn computed_delay_not_rendered() {
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended timeout_with (|| 300);
+        }
+    };
+    assert_eq!(render(&fsm).contains("f0_s0 --> f0_s1 : timeout\n"), true);
+}
+
+#[test]
+fn escapes_names() {
+    let mut fsm = FSMImpl::new();
+    fsm.add_std_state("a \"quoted\" state", false);
+    assert_eq!(
+        render(&fsm).contains("state \"a #quot;quoted#quot; state\" as f0_s1"),
+        true
+    );
+}
+
+#[test]
+fn renders_sub_fsm_as_composite_state() {
+    let inner = press_release_fsm(Rc::default());
+    let fsm = crate::fsm! {
+        states {
+            init: init,
+            ended: terminal,
+        }
+        transitions {
+            init -> ended sub (inner);
+        }
+    };
+    let mermaid = render(&fsm);
+    assert_eq!(
+        mermaid.contains("    f0_s0 --> f1 : mousedown\n    f1 --> f0_s1\n"),
+        true
+    );
+    assert_eq!(
+        mermaid.contains("    state \"sub-FSM\" as f1 {\n        state \"init\" as f1_s0\n"),
+        true
+    );
+    assert_eq!(
+        mermaid.contains("        f1_s1 --> f1_s3 : timeout (100 ms)\n    }\n"),
+        true
+    );
+}
+
+#[test]
+fn renders_concurrent_fsm() {
+    let fsm = ConcurrentXorFSM::new(vec![
+        press_release_fsm(Rc::default()),
+        press_release_fsm(Rc::default()),
+    ]);
+    let mermaid = render(&fsm);
+    assert_eq!(
+        mermaid.contains("    [*] --> f0\n    state \"XOR\" as f0 {\n"),
+        true
+    );
+    assert_eq!(
+        mermaid.contains("        f1_s1 --> f1_s3 : timeout (100 ms)\n        --\n"),
+        true
+    );
+    assert_eq!(
+        mermaid.ends_with("        f2_s1 --> f2_s3 : timeout (100 ms)\n    }\n"),
+        true
+    );
+}
@@ -11,6 +11,39 @@
  * You should have received a copy of the GNU General Public License
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
+// The tests compare booleans with `assert_eq!` to name the expected value.
+#![allow(clippy::bool_assert_comparison)]
+
 mod command;
 mod anoncmd;
 mod undo;
+mod fsm;
+mod timeout_transition;
+mod concurrent_and_fsm;
+mod concurrent_xor_fsm;
+mod sub_fsm_transition;
+mod fsm_macro;
+mod dot_visitor;
+mod mermaid_visitor;
+mod fsm_validator;
+mod fsm_trace;
+mod dom;
+mod typed_transition;
+mod mouse_transition;
+mod touch_transition;
+mod key_transition;
+mod wheel_transition;
+mod scroll_transition;
+mod change_transition;
+mod input_transition;
+mod fsm_error;
+mod fsm_definition;
+mod statechart;
+mod fsm_coverage;
+mod testing;
+mod linearhistory;
+mod fsm_queue;
+mod fsm_driver;
+mod event_type;
+mod dispatcher;
+mod headless;
@@ -0,0 +1,115 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::{EventType, MouseEventType};
+use crate::fsm::{StateId, Transition};
+use crate::r#impl::fsm::MouseTransition;
+use crate::test::fsm::{StubEvent, StubMouseEvent};
+
+fn press(button: i16) -> StubMouseEvent {
+    StubMouseEvent {
+        event_type: MouseEventType::Mousedown,
+        button,
+    }
+}
+
+fn transition() -> MouseTransition {
+    MouseTransition::new(
+        StateId(0),
+        StateId(1),
+        [MouseEventType::Mousedown, MouseEventType::Mousemove],
+        None,
+        None,
+    )
+}
+
+#[test]
+fn accepted_events() {
+    assert_eq!(
+        *transition().get_accepted_events(),
+        HashSet::from([
+            EventType::Mouse(MouseEventType::Mousedown),
+            EventType::Mouse(MouseEventType::Mousemove)
+        ])
+    );
+}
+
+#[test]
+fn accepts_mouse_events_of_its_types() {
+    let tr = transition();
+    assert_eq!(tr.accept(&press(0)), true);
+    assert_eq!(
+        tr.accept(&StubMouseEvent {
+            event_type: MouseEventType::Mouseup,
+            button: 0
+        }),
+        false
+    );
+}
+
+#[test]
+fn does_not_accept_other_events() {
+    assert_eq!(transition().accept(&StubEvent::press()), false);
+}
+
+#[test]
+fn button() {
+    let tr = transition().with_button(2);
+    assert_eq!(tr.accept(&press(0)), false);
+    assert_eq!(tr.execute(&press(0)).unwrap(), None);
+    assert_eq!(tr.accept(&press(2)), true);
+    assert_eq!(tr.execute(&press(2)).unwrap(), Some(StateId(1)));
+}
+
+#[test]
+fn action_and_guard_get_mouse_events() {
+    let button = Rc::new(Cell::new(-1));
+    let b = button.clone();
+    let tr = MouseTransition::new(
+        StateId(0),
+        StateId(1),
+        [MouseEventType::Mousedown],
+        Some(Box::new(move |e| {
+            b.set(e.button());
+            Ok(())
+        })),
+        Some(Box::new(|e| Ok(e.button() > 0))),
+    );
+    assert_eq!(tr.execute(&press(0)).unwrap(), None);
+    assert_eq!(tr.execute(&press(1)).unwrap(), Some(StateId(1)));
+    assert_eq!(button.get(), 1);
+}
+
+#[test]
+fn filter_is_not_a_guard() {
+    assert_eq!(transition().has_filter(), false);
+    assert_eq!(transition().with_button(1).has_filter(), true);
+    assert_eq!(transition().with_button(1).is_guarded(), false);
+}
+
+#[test]
+fn guard_label() {
+    assert_eq!(transition().guard_label(), None);
+    assert_eq!(transition().with_button(1).guard_label(), Some("button 1"));
+    assert_eq!(
+        transition()
+            .with_button(1)
+            .with_guard_label("far")
+            .guard_label(),
+        Some("button 1 && far")
+    );
+}
@@ -0,0 +1,51 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::EventType;
+use crate::fsm::{StateId, Transition};
+use crate::r#impl::fsm::ScrollTransition;
+use crate::test::fsm::StubEvent;
+
+#[test]
+fn accepted_events() {
+    let tr = ScrollTransition::new(StateId(0), StateId(1), None, None);
+    assert_eq!(
+        *tr.get_accepted_events(),
+        HashSet::from([EventType::Scroll])
+    );
+}
+
+#[test]
+fn execute() {
+    let executed = Rc::new(Cell::new(false));
+    let e = executed.clone();
+    let tr = ScrollTransition::new(
+        StateId(0),
+        StateId(1),
+        Some(Box::new(move |_| {
+            e.set(true);
+            Ok(())
+        })),
+        None,
+    );
+    assert_eq!(tr.execute(&StubEvent::key()).unwrap(), None);
+    assert_eq!(
+        tr.execute(&StubEvent::new(EventType::Scroll)).unwrap(),
+        Some(StateId(1))
+    );
+    assert_eq!(executed.get(), true);
+}
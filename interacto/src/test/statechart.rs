@@ -0,0 +1,449 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::dom::{EventType, MouseEventType};
+use crate::fsm::{ErrorPolicy, StateId, StateKind, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{
+    DotVisitor, FSMDefect, FSMDefinition, FSMImpl, FSMValidator, MermaidVisitor, TimeoutTransition,
+};
+use crate::test::fsm::{key, press, release, transition, StubEvent, StubFSMHandler};
+
+fn mouse_move() -> EventType {
+    EventType::Mouse(MouseEventType::Mousemove)
+}
+
+/// The states of a canvas tool:
+/// init -press-> tool { idle -move-> hover -press-> dragging -release-> hover }
+/// tool -key-> paused -press-> history of tool
+/// tool -release-> done (terminal), from idle only
+struct Tool {
+    fsm: FSMImpl,
+    tool: StateId,
+    idle: StateId,
+    hover: StateId,
+    dragging: StateId,
+    paused: StateId,
+    log: Rc<RefCell<Vec<String>>>,
+    clock: Rc<VirtualClock>,
+}
+
+fn tool() -> Tool {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = FSMImpl::with_clock(clock.clone());
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let tool = fsm.add_composite_state("tool", false);
+    let idle = fsm.add_std_state("idle", false);
+    let hover = fsm.add_std_state("hover", false);
+    let dragging = fsm.add_std_state("dragging", false);
+    let paused = fsm.add_std_state("paused", false);
+    let done = fsm.add_terminal_state("done", false);
+    fsm.add_child(tool, idle);
+    fsm.add_child(tool, hover);
+    fsm.add_child(tool, dragging);
+    let history = fsm.add_shallow_history_state("H", tool);
+    for (state, name) in [(tool, "tool"), (idle, "idle"), (hover, "hover")] {
+        let entry_log = log.clone();
+        fsm.set_entry_action(
+            state,
+            Box::new(move || {
+                entry_log.borrow_mut().push(format!("enter {name}"));
+                Ok(())
+            }),
+        );
+        let exit_log = log.clone();
+        fsm.set_exit_action(
+            state,
+            Box::new(move || {
+                exit_log.borrow_mut().push(format!("exit {name}"));
+                Ok(())
+            }),
+        );
+    }
+    fsm.add_transition(transition(StateId(0), tool, press()));
+    fsm.add_transition(transition(idle, hover, mouse_move()));
+    fsm.add_transition(transition(hover, dragging, press()));
+    fsm.add_transition(transition(dragging, hover, release()));
+    fsm.add_transition(transition(idle, done, release()));
+    fsm.add_transition(transition(tool, paused, key()));
+    fsm.add_transition(transition(paused, history, press()));
+    Tool {
+        fsm,
+        tool,
+        idle,
+        hover,
+        dragging,
+        paused,
+        log,
+        clock,
+    }
+}
+
+fn logged(tool: &Tool) -> Vec<String> {
+    tool.log.borrow_mut().drain(..).collect()
+}
+
+#[test]
+fn composite_state_kind() {
+    let tool = tool();
+    assert_eq!(tool.fsm.state(tool.tool).kind(), StateKind::Composite);
+    assert_eq!(tool.fsm.definition().parent(tool.idle), Some(tool.tool));
+    assert_eq!(tool.fsm.definition().parent(tool.tool), None);
+}
+
+#[test]
+fn entering_composite_enters_initial_child() {
+    let mut tool = tool();
+    tool.fsm.process(&StubEvent::press());
+    assert_eq!(tool.fsm.current_state_id(), tool.idle);
+    assert_eq!(logged(&tool), vec!["enter tool", "enter idle"]);
+}
+
+#[test]
+fn set_initial_child() {
+    let mut tool = tool();
+    tool.fsm.set_initial_child(tool.tool, tool.hover);
+    tool.fsm.process(&StubEvent::press());
+    assert_eq!(tool.fsm.current_state_id(), tool.hover);
+}
+
+#[test]
+fn transition_inside_composite_does_not_leave_it() {
+    let mut tool = tool();
+    tool.fsm.process(&StubEvent::press());
+    logged(&tool);
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(tool.fsm.current_state_id(), tool.hover);
+    assert_eq!(logged(&tool), vec!["exit idle", "enter hover"]);
+}
+
+#[test]
+fn transition_of_composite_from_child() {
+    let mut tool = tool();
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    logged(&tool);
+    assert_eq!(tool.fsm.process(&StubEvent::key()), true);
+    assert_eq!(tool.fsm.current_state_id(), tool.paused);
+    assert_eq!(logged(&tool), vec!["exit hover", "exit tool"]);
+}
+
+#[test]
+fn child_transition_has_priority() {
+    let mut tool = tool();
+    tool.fsm
+        .add_transition(transition(tool.tool, tool.paused, mouse_move()));
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(tool.fsm.current_state_id(), tool.hover);
+}
+
+#[test]
+fn self_transition_of_composite_enters_it_again() {
+    let mut tool = tool();
+    tool.fsm
+        .add_transition(transition(tool.tool, tool.tool, mouse_move()));
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    tool.fsm.process(&StubEvent::press());
+    logged(&tool);
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(tool.fsm.current_state_id(), tool.idle);
+    assert_eq!(logged(&tool), vec!["exit tool", "enter tool", "enter idle"]);
+}
+
+#[test]
+fn terminal_child_terminates() {
+    let mut tool = tool();
+    let handler = StubFSMHandler::new();
+    tool.fsm.add_handler(handler.clone());
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::release());
+    assert_eq!(tool.fsm.current_state_id(), StateId(0));
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn shallow_history() {
+    let mut tool = tool();
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::key());
+    logged(&tool);
+    tool.fsm.process(&StubEvent::press());
+    assert_eq!(tool.fsm.current_state_id(), tool.dragging);
+    assert_eq!(logged(&tool), vec!["enter tool"]);
+}
+
+#[test]
+fn history_without_past_enters_composite() {
+    let mut tool = tool();
+    tool.fsm
+        .add_transition(transition(StateId(0), tool.paused, key()));
+    tool.fsm.process(&StubEvent::key());
+    tool.fsm.process(&StubEvent::press());
+    assert_eq!(tool.fsm.current_state_id(), tool.idle);
+}
+
+#[test]
+fn history_cleared_on_reinit() {
+    let mut tool = tool();
+    tool.fsm
+        .add_transition(transition(StateId(0), tool.paused, key()));
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    tool.fsm.process(&StubEvent::key());
+    tool.fsm.reinit();
+    tool.fsm.process(&StubEvent::key());
+    tool.fsm.process(&StubEvent::press());
+    assert_eq!(tool.fsm.current_state_id(), tool.idle);
+}
+
+/// init -press-> outer { inner { a -move-> b }, c }
+/// outer -key-> out; out -press-> H (outer); out -release-> H* (outer)
+fn nested() -> (FSMImpl, [StateId; 3]) {
+    let mut fsm = FSMImpl::new();
+    let outer = fsm.add_composite_state("outer", false);
+    let inner = fsm.add_composite_state("inner", false);
+    let a = fsm.add_std_state("a", false);
+    let b = fsm.add_std_state("b", false);
+    let c = fsm.add_std_state("c", false);
+    let out = fsm.add_std_state("out", false);
+    fsm.add_child(outer, inner);
+    fsm.add_child(outer, c);
+    fsm.add_child(inner, a);
+    fsm.add_child(inner, b);
+    let shallow = fsm.add_shallow_history_state("H", outer);
+    let deep = fsm.add_deep_history_state("H*", outer);
+    fsm.add_transition(transition(StateId(0), outer, press()));
+    fsm.add_transition(transition(a, b, mouse_move()));
+    fsm.add_transition(transition(outer, out, key()));
+    fsm.add_transition(transition(out, shallow, press()));
+    fsm.add_transition(transition(out, deep, release()));
+    (fsm, [a, b, c])
+}
+
+#[test]
+fn shallow_history_enters_initial_children_of_nested_composite() {
+    let (mut fsm, [a, b, _]) = nested();
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(fsm.current_state_id(), b);
+    fsm.process(&StubEvent::key());
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.current_state_id(), a);
+}
+
+#[test]
+fn deep_history() {
+    let (mut fsm, [_, b, _]) = nested();
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::new(mouse_move()));
+    fsm.process(&StubEvent::key());
+    fsm.process(&StubEvent::release());
+    assert_eq!(fsm.current_state_id(), b);
+}
+
+#[test]
+fn composite_starting_state() {
+    let mut tool = tool();
+    tool.fsm.set_starting_state(tool.tool);
+    let handler = StubFSMHandler::new();
+    tool.fsm.add_handler(handler.clone());
+    tool.fsm.process(&StubEvent::press());
+    assert_eq!(tool.fsm.started(), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update"]);
+}
+
+#[test]
+fn failing_exit_action_stays() {
+    let mut tool = tool();
+    tool.fsm
+        .set_exit_action(tool.idle, Box::new(|| Err("no exit".into())));
+    let handler = StubFSMHandler::new();
+    tool.fsm.add_handler(handler.clone());
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(tool.fsm.current_state_id(), tool.idle);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "error"]);
+}
+
+#[test]
+fn failing_entry_action_cancels() {
+    let mut tool = tool();
+    tool.fsm
+        .set_entry_action(tool.hover, Box::new(|| Err("no entry".into())));
+    tool.fsm.set_error_policy(ErrorPolicy::Cancel);
+    let handler = StubFSMHandler::new();
+    tool.fsm.add_handler(handler.clone());
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(tool.fsm.current_state_id(), StateId(0));
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "error", "cancel"]
+    );
+}
+
+#[test]
+fn failing_entry_action_with_stay_policy_enters_state() {
+    let mut tool = tool();
+    tool.fsm
+        .set_entry_action(tool.hover, Box::new(|| Err("no entry".into())));
+    tool.fsm.process(&StubEvent::press());
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    assert_eq!(tool.fsm.current_state_id(), tool.hover);
+}
+
+#[test]
+fn timeout_transition_of_composite() {
+    let mut tool = tool();
+    tool.fsm
+        .add_transition(Box::new(TimeoutTransition::with_delay(
+            tool.tool,
+            tool.paused,
+            100,
+            None,
+        )));
+    tool.fsm.process(&StubEvent::press());
+    tool.clock.advance(60);
+    tool.fsm.process(&StubEvent::new(mouse_move()));
+    tool.clock.advance(60);
+    assert_eq!(tool.fsm.check_timeout(), false);
+    tool.clock.advance(40);
+    assert_eq!(tool.fsm.check_timeout(), true);
+    assert_eq!(tool.fsm.current_state_id(), tool.paused);
+    assert_eq!(
+        logged(&tool).ends_with(&["exit hover".to_string(), "exit tool".to_string()]),
+        true
+    );
+}
+
+#[test]
+#[should_panic]
+fn child_of_two_composites() {
+    let mut definition = FSMDefinition::new();
+    let first = definition.add_composite_state("first", false);
+    let second = definition.add_composite_state("second", false);
+    let child = definition.add_std_state("child", false);
+    definition.add_child(first, child);
+    definition.add_child(second, child);
+}
+
+#[test]
+#[should_panic]
+fn composite_cannot_contain_itself() {
+    let mut definition = FSMDefinition::new();
+    let outer = definition.add_composite_state("outer", false);
+    let inner = definition.add_composite_state("inner", false);
+    definition.add_child(outer, inner);
+    definition.add_child(inner, outer);
+}
+
+#[test]
+#[should_panic]
+fn child_of_std_state() {
+    let mut definition = FSMDefinition::new();
+    let state = definition.add_std_state("state", false);
+    let child = definition.add_std_state("child", false);
+    definition.add_child(state, child);
+}
+
+#[test]
+fn common_ancestor() {
+    let mut definition = FSMDefinition::new();
+    let outer = definition.add_composite_state("outer", false);
+    let inner = definition.add_composite_state("inner", false);
+    let a = definition.add_std_state("a", false);
+    let b = definition.add_std_state("b", false);
+    definition.add_child(outer, inner);
+    definition.add_child(inner, a);
+    definition.add_child(outer, b);
+    assert_eq!(definition.common_ancestor(a, b), Some(outer));
+    assert_eq!(definition.common_ancestor(a, inner), Some(outer));
+    assert_eq!(definition.common_ancestor(outer, a), None);
+    assert_eq!(
+        definition.ancestors(a).collect::<Vec<_>>(),
+        vec![inner, outer]
+    );
+}
+
+#[test]
+fn validates_statechart() {
+    assert_eq!(FSMValidator::validate(&tool().fsm), Ok(()));
+}
+
+#[test]
+fn validator_reports_unreachable_child() {
+    let mut tool = tool();
+    let child = tool.fsm.add_std_state("unreachable", false);
+    tool.fsm.add_child(tool.tool, child);
+    assert_eq!(
+        FSMValidator::validate(&tool.fsm),
+        Err(vec![FSMDefect::UnreachableState("unreachable".to_string())])
+    );
+}
+
+#[test]
+fn dot_renders_composite_as_cluster() {
+    let tool = tool();
+    let mut visitor = DotVisitor::new();
+    tool.fsm.accept_visitor(&mut visitor);
+    let output = visitor.output();
+    assert_eq!(
+        output.contains(
+            "  subgraph cluster_f0_s1 {
+    label=\"tool\";
+    f0_s1 [label=\"tool\", shape=point];
+    f0_s2 [label=\"idle\", shape=circle];
+"
+        ),
+        true
+    );
+    assert_eq!(
+        output.contains("f0_s7 [label=\"H\", shape=circle, style=dashed];"),
+        true
+    );
+    assert_eq!(
+        output.contains("f0_s0 -> f0_s1 [label=\"mousedown\", lhead=cluster_f0_s1];"),
+        true
+    );
+    assert_eq!(
+        output.contains("f0_s1 -> f0_s5 [label=\"keydown\", ltail=cluster_f0_s1];"),
+        true
+    );
+}
+
+#[test]
+fn mermaid_renders_composite_state() {
+    let tool = tool();
+    let mut visitor = MermaidVisitor::new();
+    tool.fsm.accept_visitor(&mut visitor);
+    assert_eq!(
+        visitor.output().contains(
+            "    state \"tool\" as f0_s1 {
+        [*] --> f0_s2
+        state \"idle\" as f0_s2
+        state \"hover\" as f0_s3
+        state \"dragging\" as f0_s4
+        state \"H\" as f0_s7
+    }
+"
+        ),
+        true
+    );
+}
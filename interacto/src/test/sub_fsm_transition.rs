@@ -0,0 +1,246 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::Event;
+use crate::fsm::{StateId, Transition, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{
+    ConcurrentAndFSM, FSMImpl, SubFSMTransition, TimeoutTransition, TransitionBase,
+};
+
+use super::fsm::{transition, StubEvent, StubFSMHandler};
+
+/// init -press-> pressed -release-> released (terminal)
+///               pressed -key-> cancelled (cancelling)
+fn press_release() -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    let released = fsm.add_terminal_state("released", false);
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    fsm.add_transition(transition(init, pressed, StubEvent::press().event_type));
+    fsm.add_transition(transition(
+        pressed,
+        released,
+        StubEvent::release().event_type,
+    ));
+    fsm.add_transition(transition(pressed, cancelled, StubEvent::key().event_type));
+    fsm
+}
+
+/// init -[press-release]-> selected -key-> done (terminal)
+struct SelectThenKey {
+    fsm: FSMImpl,
+    selected: StateId,
+}
+
+fn select_then_key() -> SelectThenKey {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let selected = fsm.add_std_state("selected", false);
+    let done = fsm.add_terminal_state("done", false);
+    fsm.add_transition(Box::new(SubFSMTransition::new(
+        init,
+        selected,
+        Box::new(press_release()),
+    )));
+    fsm.add_transition(transition(selected, done, StubEvent::key().event_type));
+    SelectThenKey { fsm, selected }
+}
+
+#[test]
+fn sub_fsm_is_inner() {
+    let tr = SubFSMTransition::new(StateId(0), StateId(1), Box::new(press_release()));
+    assert_eq!(tr.inner_fsm().map(|fsm| fsm.is_inner()), Some(true));
+}
+
+#[test]
+fn accepts_events_of_sub_fsm_init_state() {
+    let tr = SubFSMTransition::new(StateId(0), StateId(1), Box::new(press_release()));
+    assert_eq!(
+        tr.get_accepted_events(),
+        &HashSet::from([StubEvent::press().event_type])
+    );
+    assert_eq!(tr.accept(&StubEvent::press()), true);
+    assert_eq!(tr.accept(&StubEvent::release()), false);
+}
+
+#[test]
+fn accepts_events_of_concurrent_sub_fsm() {
+    let tr = SubFSMTransition::new(
+        StateId(0),
+        StateId(1),
+        Box::new(ConcurrentAndFSM::new(vec![press_release()])),
+    );
+    assert_eq!(
+        tr.get_accepted_events(),
+        &HashSet::from([StubEvent::press().event_type])
+    );
+    assert_eq!(tr.accept(&StubEvent::press()), true);
+}
+
+#[test]
+fn rejects_events_refused_by_sub_fsm_guards() {
+    let mut guarded = FSMImpl::new();
+    let init = guarded.init_state().id();
+    let pressed = guarded.add_terminal_state("pressed", false);
+    guarded.add_transition(Box::new(TransitionBase::new(
+        init,
+        pressed,
+        HashSet::from([StubEvent::press().event_type]),
+        None,
+        Some(Box::new(|_: &dyn Event| Ok(false))),
+    )));
+    let tr = SubFSMTransition::new(StateId(0), StateId(1), Box::new(guarded));
+    assert_eq!(tr.accept(&StubEvent::press()), false);
+}
+
+#[test]
+fn sub_fsm_rejecting_event_keeps_parent_timeout() {
+    let clock = Rc::new(VirtualClock::new());
+    // The guard holds when the parent checks the sub-FSM transition, not when the sub-FSM runs.
+    let evaluations = Rc::new(Cell::new(0));
+    let mut guarded = FSMImpl::new();
+    let init = guarded.init_state().id();
+    let pressed = guarded.add_terminal_state("pressed", false);
+    guarded.add_transition(Box::new(TransitionBase::new(
+        init,
+        pressed,
+        HashSet::from([StubEvent::press().event_type]),
+        None,
+        Some(Box::new(move |_: &dyn Event| {
+            evaluations.set(evaluations.get() + 1);
+            Ok(evaluations.get() == 1)
+        })),
+    )));
+    let mut fsm = FSMImpl::with_clock(clock.clone());
+    let init = fsm.init_state().id();
+    let ready = fsm.add_std_state("ready", false);
+    let done = fsm.add_terminal_state("done", false);
+    fsm.add_transition(transition(init, ready, StubEvent::key().event_type));
+    fsm.add_transition(Box::new(SubFSMTransition::new(
+        ready,
+        done,
+        Box::new(guarded),
+    )));
+    fsm.add_transition(Box::new(TimeoutTransition::with_delay(
+        ready, done, 100, None,
+    )));
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::key());
+    assert_eq!(fsm.process(&StubEvent::press()), false);
+    assert_eq!(fsm.current_sub_fsm().is_none(), true);
+    assert_eq!(fsm.next_timeout(), Some(100));
+    clock.advance(100);
+    assert_eq!(fsm.check_timeout(), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn entering_sub_fsm() {
+    let mut stk = select_then_key();
+    assert_eq!(stk.fsm.process(&StubEvent::press()), true);
+    assert_eq!(
+        stk.fsm.current_sub_fsm().map(|fsm| fsm.started()),
+        Some(true)
+    );
+    assert_eq!(stk.fsm.started(), true);
+    assert_eq!(stk.fsm.current_state().id(), stk.fsm.init_state().id());
+}
+
+#[test]
+fn events_given_to_sub_fsm() {
+    let mut stk = select_then_key();
+    stk.fsm.process(&StubEvent::press());
+    assert_eq!(stk.fsm.process(&StubEvent::key()), true);
+    assert_eq!(stk.fsm.current_sub_fsm().is_none(), true);
+    assert_eq!(stk.fsm.started(), false);
+}
+
+#[test]
+fn sub_fsm_termination_enters_target_state() {
+    let mut stk = select_then_key();
+    let handler = StubFSMHandler::new();
+    stk.fsm.add_handler(handler.clone());
+    stk.fsm.process(&StubEvent::press());
+    stk.fsm.process(&StubEvent::release());
+    assert_eq!(stk.fsm.current_sub_fsm().is_none(), true);
+    assert_eq!(stk.fsm.current_state_id(), stk.selected);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "update"]);
+}
+
+#[test]
+fn parent_terminates_after_sub_fsm() {
+    let mut stk = select_then_key();
+    let handler = StubFSMHandler::new();
+    stk.fsm.add_handler(handler.clone());
+    stk.fsm.process(&StubEvent::press());
+    stk.fsm.process(&StubEvent::release());
+    stk.fsm.process(&StubEvent::key());
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "update", "stop"]
+    );
+}
+
+#[test]
+fn sub_fsm_cancellation_cancels_parent() {
+    let mut stk = select_then_key();
+    let handler = StubFSMHandler::new();
+    stk.fsm.add_handler(handler.clone());
+    stk.fsm.process(&StubEvent::press());
+    stk.fsm.process(&StubEvent::key());
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "cancel"]);
+    assert_eq!(stk.fsm.current_state().id(), stk.fsm.init_state().id());
+}
+
+#[test]
+fn parent_does_not_see_sub_fsm_start_as_its_own() {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let ready = fsm.add_std_state("ready", false);
+    let done = fsm.add_terminal_state("done", false);
+    fsm.add_transition(transition(init, ready, StubEvent::key().event_type));
+    fsm.add_transition(Box::new(SubFSMTransition::new(
+        ready,
+        done,
+        Box::new(press_release()),
+    )));
+    let handler = StubFSMHandler::new();
+    fsm.add_handler(handler.clone());
+    fsm.process(&StubEvent::key());
+    fsm.process(&StubEvent::press());
+    fsm.process(&StubEvent::release());
+    assert_eq!(
+        handler.borrow().calls,
+        vec!["start", "update", "update", "stop"]
+    );
+}
+
+#[test]
+fn parent_reinit_reinits_sub_fsm() {
+    let mut stk = select_then_key();
+    stk.fsm.process(&StubEvent::press());
+    stk.fsm.reinit();
+    assert_eq!(stk.fsm.current_sub_fsm().is_none(), true);
+    stk.fsm.process(&StubEvent::press());
+    assert_eq!(
+        stk.fsm.current_sub_fsm().map(|fsm| fsm.started()),
+        Some(true)
+    );
+}
@@ -0,0 +1,330 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::prelude::*;
+
+use crate::dom::{
+    Event, EventType, KeyEventType, KeyboardEvent, Modifiers, MouseEventType, TouchEvent,
+    TouchEventType,
+};
+use crate::fsm::FSM;
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{
+    ConcurrentAndFSM, ConcurrentXorFSM, FSMImpl, KeyTransition, MouseTransition, SubFSMTransition,
+    TimeoutTransition, TouchTransition,
+};
+use crate::test::fsm::StubFSMHandler;
+use crate::testing::strategies::{event_sequence, key_sequence, pointer_sequence, touch_sequence};
+use crate::testing::{check_fsm_invariants, gesture_events, EventSequence, Gesture, SampleEvent};
+
+/// Checks the timestamps are monotone, and that key presses and touches are paired.
+fn well_formed(events: &[SampleEvent]) -> bool {
+    let mut keys = HashSet::new();
+    let mut touches = HashSet::new();
+    let monotone = events
+        .windows(2)
+        .all(|pair| pair[0].timestamp() <= pair[1].timestamp());
+    let paired = events.iter().all(|event| match event {
+        SampleEvent::Key { event_type, .. } => match event_type {
+            KeyEventType::Keydown => keys.insert(event.code().to_string()),
+            KeyEventType::Keyup => keys.remove(event.code()),
+        },
+        SampleEvent::Touch { event_type, .. } => {
+            let id = event.changed_touches()[0].identifier;
+            match event_type {
+                TouchEventType::Touchstart => touches.insert(id),
+                TouchEventType::Touchmove => touches.contains(&id),
+                TouchEventType::Touchend => touches.remove(&id),
+            }
+        }
+        _ => true,
+    });
+    monotone && paired && keys.is_empty() && touches.is_empty()
+}
+
+/// A drag-and-drop started by a move, cancelled by Escape or by a release without move.
+/// A long press (500 ms) also cancels it.
+fn dnd(clock: Rc<VirtualClock>) -> FSMImpl {
+    let mut fsm = FSMImpl::with_clock(clock);
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    let dragged = fsm.add_std_state("dragged", true);
+    let released = fsm.add_terminal_state("released", false);
+    let cancelled = fsm.add_cancelling_state("cancelled");
+    let mouse = |src, tgt, event_type| {
+        Box::new(MouseTransition::new(src, tgt, [event_type], None, None).with_button(0))
+    };
+    fsm.add_transition(mouse(init, pressed, MouseEventType::Mousedown));
+    fsm.add_transition(mouse(pressed, cancelled, MouseEventType::Mouseup));
+    fsm.add_transition(mouse(pressed, dragged, MouseEventType::Mousemove));
+    fsm.add_transition(mouse(dragged, dragged, MouseEventType::Mousemove));
+    fsm.add_transition(mouse(dragged, released, MouseEventType::Mouseup));
+    fsm.add_transition(Box::new(
+        KeyTransition::new(dragged, cancelled, [KeyEventType::Keydown], None, None)
+            .with_key("Escape"),
+    ));
+    fsm.add_transition(Box::new(TimeoutTransition::with_delay(
+        pressed, cancelled, 500, None,
+    )));
+    fsm
+}
+
+/// Keys typed until Enter is released, or until 300 ms without typing.
+fn keys_typed(clock: Rc<VirtualClock>) -> FSMImpl {
+    let mut fsm = FSMImpl::with_clock(clock);
+    let init = fsm.init_state().id();
+    let typed = fsm.add_std_state("typed", false);
+    let ended = fsm.add_terminal_state("ended", false);
+    let key_up = |src, tgt| KeyTransition::new(src, tgt, [KeyEventType::Keyup], None, None);
+    fsm.add_transition(Box::new(key_up(init, typed)));
+    fsm.add_transition(Box::new(key_up(typed, ended).with_key("Enter")));
+    fsm.add_transition(Box::new(key_up(typed, typed)));
+    fsm.add_transition(Box::new(TimeoutTransition::with_delay(
+        typed, ended, 300, None,
+    )));
+    fsm
+}
+
+/// The press-move-release of the touch point that has the given identifier.
+fn touch_dnd(identifier: i32) -> FSMImpl {
+    let mut fsm = FSMImpl::new();
+    let init = fsm.init_state().id();
+    let touched = fsm.add_std_state("touched", false);
+    let moved = fsm.add_std_state("moved", false);
+    let released = fsm.add_terminal_state("released", false);
+    let transition = |src, tgt, event_type| {
+        Box::new(TouchTransition::new(src, tgt, [event_type], None, None).with_touch_id(identifier))
+    };
+    fsm.add_transition(transition(init, touched, TouchEventType::Touchstart));
+    fsm.add_transition(transition(touched, moved, TouchEventType::Touchmove));
+    fsm.add_transition(transition(moved, moved, TouchEventType::Touchmove));
+    fsm.add_transition(transition(moved, released, TouchEventType::Touchend));
+    fsm.add_transition(transition(touched, released, TouchEventType::Touchend));
+    fsm
+}
+
+/// A click, or else a tap.
+fn click_or_tap() -> ConcurrentXorFSM<FSMImpl> {
+    let mut click = FSMImpl::new();
+    let init = click.init_state().id();
+    let clicked = click.add_terminal_state("clicked", false);
+    click.add_transition(Box::new(MouseTransition::new(
+        init,
+        clicked,
+        [MouseEventType::Click],
+        None,
+        None,
+    )));
+    ConcurrentXorFSM::new(vec![click, touch_dnd(0)])
+}
+
+/// A DnD then a key press.
+fn dnd_then_key(clock: Rc<VirtualClock>) -> FSMImpl {
+    let mut fsm = FSMImpl::with_clock(clock.clone());
+    let init = fsm.init_state().id();
+    let dropped = fsm.add_std_state("dropped", false);
+    let done = fsm.add_terminal_state("done", false);
+    fsm.add_transition(Box::new(SubFSMTransition::new(
+        init,
+        dropped,
+        Box::new(dnd(clock)),
+    )));
+    fsm.add_transition(Box::new(KeyTransition::new(
+        dropped,
+        done,
+        [KeyEventType::Keydown],
+        None,
+        None,
+    )));
+    fsm
+}
+
+#[test]
+fn pointer_gesture_events() {
+    let events = gesture_events(&[(
+        Gesture::Pointer {
+            button: 0,
+            moves: 1,
+        },
+        10,
+    )]);
+    let types: Vec<(EventType, u64)> = events
+        .iter()
+        .map(|event| (event.event_type(), event.timestamp()))
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            (EventType::Mouse(MouseEventType::Mousedown), 10),
+            (EventType::Mouse(MouseEventType::Mousemove), 20),
+            (EventType::Mouse(MouseEventType::Mouseup), 30),
+            (EventType::Mouse(MouseEventType::Click), 40),
+        ]
+    );
+}
+
+#[test]
+fn key_gesture_events_carry_modifiers() {
+    let events = gesture_events(&[(Gesture::Keys(vec![4, 0]), 5)]);
+    let keys: Vec<(&str, KeyEventType, Modifiers)> = events
+        .iter()
+        .map(|event| match event {
+            SampleEvent::Key {
+                event_type,
+                key,
+                modifiers,
+                ..
+            } => (*key, *event_type, *modifiers),
+            _ => unreachable!(),
+        })
+        .collect();
+    let shift = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+    assert_eq!(
+        keys,
+        vec![
+            ("Shift", KeyEventType::Keydown, shift),
+            ("a", KeyEventType::Keydown, shift),
+            ("a", KeyEventType::Keyup, shift),
+            ("Shift", KeyEventType::Keyup, Modifiers::NONE),
+        ]
+    );
+}
+
+#[test]
+fn key_gesture_presses_keys_once() {
+    let events = gesture_events(&[(Gesture::Keys(vec![1, 1]), 5)]);
+    assert_eq!(events.len(), 2);
+    assert_eq!(well_formed(&events), true);
+}
+
+#[test]
+fn touch_identifiers_not_reused() {
+    let touches = Gesture::Touches { count: 2, moves: 0 };
+    let events = gesture_events(&[(touches.clone(), 1), (touches, 1)]);
+    let ids: HashSet<i32> = events
+        .iter()
+        .map(|event| event.changed_touches()[0].identifier)
+        .collect();
+    assert_eq!(ids, HashSet::from([0, 1, 2, 3]));
+}
+
+#[test]
+fn arbitrary_event_sequence() {
+    let data: Vec<u8> = (0..=255).rev().cycle().take(1024).collect();
+    let sequence = EventSequence::arbitrary(&mut Unstructured::new(&data)).unwrap();
+    assert_eq!(sequence.0.is_empty(), false);
+    assert_eq!(well_formed(&sequence.0), true);
+}
+
+#[test]
+fn panicking_action_reported() {
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: terminal,
+        }
+        transitions {
+            init -> pressed on [EventType::Mouse(MouseEventType::Mousedown)] action (|_| panic!("boom"));
+        }
+    };
+    let events = gesture_events(&[(
+        Gesture::Pointer {
+            button: 0,
+            moves: 0,
+        },
+        1,
+    )]);
+    let violation = check_fsm_invariants(&mut fsm, &events, None).unwrap_err();
+    assert_eq!(violation.event, 0);
+    assert_eq!(violation.message.ends_with("panicked: boom"), true);
+}
+
+#[test]
+fn invariants_hold_with_cancelling_handler() {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = dnd(clock.clone());
+    let handler = StubFSMHandler::new();
+    handler.borrow_mut().cancel_on_update = true;
+    fsm.add_handler(handler);
+    let events = gesture_events(&[(
+        Gesture::Pointer {
+            button: 0,
+            moves: 3,
+        },
+        10,
+    )]);
+    assert_eq!(
+        check_fsm_invariants(&mut fsm, &events, Some(&clock)),
+        Ok(())
+    );
+}
+
+proptest! {
+    #[test]
+    fn prop_generated_sequences_well_formed(events in event_sequence(12)) {
+        prop_assert!(well_formed(&events));
+    }
+
+    #[test]
+    fn prop_arbitrary_sequences_well_formed(data in prop::collection::vec(any::<u8>(), 0..512)) {
+        let sequence = EventSequence::arbitrary(&mut Unstructured::new(&data));
+        prop_assert!(sequence.map_or(true, |sequence| well_formed(&sequence.0)));
+    }
+
+    #[test]
+    fn prop_dnd_invariants(events in pointer_sequence(8)) {
+        let clock = Rc::new(VirtualClock::new());
+        let mut fsm = dnd(clock.clone());
+        prop_assert_eq!(check_fsm_invariants(&mut fsm, &events, Some(&clock)), Ok(()));
+    }
+
+    #[test]
+    fn prop_dnd_invariants_with_other_events(events in event_sequence(8)) {
+        let clock = Rc::new(VirtualClock::new());
+        let mut fsm = dnd(clock.clone());
+        prop_assert_eq!(check_fsm_invariants(&mut fsm, &events, Some(&clock)), Ok(()));
+    }
+
+    #[test]
+    fn prop_keys_typed_invariants(events in key_sequence(8)) {
+        let clock = Rc::new(VirtualClock::new());
+        let mut fsm = keys_typed(clock.clone());
+        prop_assert_eq!(check_fsm_invariants(&mut fsm, &events, Some(&clock)), Ok(()));
+    }
+
+    #[test]
+    fn prop_multi_touch_invariants(events in touch_sequence(6)) {
+        let mut fsm = ConcurrentAndFSM::new(vec![touch_dnd(0), touch_dnd(1), touch_dnd(2)]);
+        prop_assert_eq!(check_fsm_invariants(&mut fsm, &events, None), Ok(()));
+    }
+
+    #[test]
+    fn prop_click_or_tap_invariants(events in event_sequence(8)) {
+        let mut fsm = click_or_tap();
+        prop_assert_eq!(check_fsm_invariants(&mut fsm, &events, None), Ok(()));
+    }
+
+    #[test]
+    fn prop_sub_fsm_invariants(events in event_sequence(8)) {
+        let clock = Rc::new(VirtualClock::new());
+        let mut fsm = dnd_then_key(clock.clone());
+        prop_assert_eq!(check_fsm_invariants(&mut fsm, &events, Some(&clock)), Ok(()));
+    }
+}
@@ -0,0 +1,201 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::clock::Clock;
+use crate::dom::Event;
+use crate::fsm::{FSMError, StateId, FSM};
+use crate::r#impl::clock::VirtualClock;
+use crate::r#impl::fsm::{FSMImpl, TimeoutDelay, TimeoutTransition};
+
+use super::fsm::{press, transition, StubEvent, StubFSMHandler};
+
+/// init -press-> pressed -release-> released (terminal)
+///               pressed -timeout-> held (terminal)
+struct LongPress {
+    fsm: FSMImpl,
+    clock: Rc<VirtualClock>,
+    pressed: StateId,
+    executed: Rc<Cell<bool>>,
+}
+
+fn long_press(delay: TimeoutDelay) -> LongPress {
+    let clock = Rc::new(VirtualClock::new());
+    let mut fsm = FSMImpl::with_clock(clock.clone());
+    let init = fsm.init_state().id();
+    let pressed = fsm.add_std_state("pressed", false);
+    let released = fsm.add_cancelling_state("released");
+    let held = fsm.add_terminal_state("held", false);
+    let executed = Rc::new(Cell::new(false));
+    let exec = executed.clone();
+    fsm.add_transition(transition(init, pressed, StubEvent::press().event_type));
+    fsm.add_transition(transition(
+        pressed,
+        released,
+        StubEvent::release().event_type,
+    ));
+    fsm.add_transition(Box::new(TimeoutTransition::new(
+        pressed,
+        held,
+        delay,
+        Some(Box::new(move || {
+            exec.set(true);
+            Ok(())
+        })),
+    )));
+    LongPress {
+        fsm,
+        clock,
+        pressed,
+        executed,
+    }
+}
+
+fn long_press_1s() -> LongPress {
+    long_press(Box::new(|| 1000))
+}
+
+#[test]
+fn no_timeout_in_init_state() {
+    let lp = long_press_1s();
+    assert_eq!(lp.fsm.next_timeout(), None);
+}
+
+#[test]
+fn timeout_started_when_entering_state() {
+    let mut lp = long_press_1s();
+    lp.clock.set(500);
+    lp.fsm.process(&StubEvent::press());
+    assert_eq!(lp.fsm.next_timeout(), Some(1500));
+}
+
+#[test]
+fn timeout_not_executed_before_delay() {
+    let mut lp = long_press_1s();
+    lp.fsm.process(&StubEvent::press());
+    lp.clock.advance(999);
+    assert_eq!(lp.fsm.check_timeout(), false);
+    assert_eq!(lp.fsm.current_state_id(), lp.pressed);
+    assert_eq!(lp.executed.get(), false);
+}
+
+#[test]
+fn timeout_executed_after_delay() {
+    let mut lp = long_press_1s();
+    let handler = StubFSMHandler::new();
+    lp.fsm.add_handler(handler.clone());
+    lp.fsm.process(&StubEvent::press());
+    lp.clock.advance(1000);
+    assert_eq!(lp.fsm.check_timeout(), true);
+    assert_eq!(lp.executed.get(), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+    assert_eq!(lp.fsm.next_timeout(), None);
+}
+
+#[test]
+fn on_timeout_executes_without_checking_clock() {
+    let mut lp = long_press_1s();
+    lp.fsm.process(&StubEvent::press());
+    lp.fsm.on_timeout();
+    assert_eq!(lp.executed.get(), true);
+    assert_eq!(lp.fsm.current_state().id(), lp.fsm.init_state().id());
+}
+
+#[test]
+fn leaving_state_stops_timeout() {
+    let mut lp = long_press_1s();
+    lp.fsm.process(&StubEvent::press());
+    lp.fsm.process(&StubEvent::release());
+    assert_eq!(lp.fsm.next_timeout(), None);
+    lp.clock.advance(2000);
+    assert_eq!(lp.fsm.check_timeout(), false);
+    assert_eq!(lp.executed.get(), false);
+}
+
+#[test]
+fn elapsed_timeout_processed_before_event() {
+    let mut lp = long_press_1s();
+    let handler = StubFSMHandler::new();
+    lp.fsm.add_handler(handler.clone());
+    lp.fsm.process(&StubEvent::press());
+    lp.clock.advance(1500);
+    lp.fsm.process(&StubEvent::release());
+    assert_eq!(lp.executed.get(), true);
+    assert_eq!(handler.borrow().calls, vec!["start", "update", "stop"]);
+}
+
+#[test]
+fn delay_computed_when_entering_state() {
+    let delay = Rc::new(Cell::new(100));
+    let d = delay.clone();
+    let mut lp = long_press(Box::new(move || d.get()));
+    delay.set(300);
+    lp.fsm.process(&StubEvent::press());
+    assert_eq!(lp.fsm.next_timeout(), Some(300));
+}
+
+/// Counts the presses of a multi-click.
+fn count_press(clicks: &Rc<Cell<u64>>) -> impl Fn(&dyn Event) -> Result<(), FSMError> {
+    let clicks = clicks.clone();
+    move |_| {
+        clicks.set(clicks.get() + 1);
+        Ok(())
+    }
+}
+
+/// Each press of a multi-click gives more time for the next one.
+#[test]
+fn delay_computed_from_data_updated_by_actions() {
+    let clock = Rc::new(VirtualClock::new());
+    let clicks = Rc::new(Cell::new(0));
+    let delay = clicks.clone();
+    let mut fsm = crate::fsm! {
+        clock: clock.clone();
+        states {
+            init: init,
+            pressed: std,
+            ended: terminal,
+        }
+        transitions {
+            init -> pressed on [press()] action (count_press(&clicks));
+            pressed -> pressed on [press()] action (count_press(&clicks));
+            pressed -> ended timeout_with (move || 100 * delay.get());
+        }
+    };
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.next_timeout(), Some(100));
+    clock.advance(50);
+    fsm.process(&StubEvent::press());
+    assert_eq!(fsm.next_timeout(), Some(250));
+    clock.advance(200);
+    assert_eq!(fsm.check_timeout(), true);
+    assert_eq!(fsm.started(), false);
+}
+
+#[test]
+fn virtual_clock_saturates() {
+    let clock = VirtualClock::new();
+    clock.advance(10);
+    clock.advance(u64::MAX);
+    assert_eq!(clock.now(), u64::MAX);
+}
+
+#[test]
+fn reinit_stops_timeout() {
+    let mut lp = long_press_1s();
+    lp.fsm.process(&StubEvent::press());
+    lp.fsm.reinit();
+    assert_eq!(lp.fsm.next_timeout(), None);
+}
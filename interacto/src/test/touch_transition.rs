@@ -0,0 +1,91 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::{EventType, Touch, TouchEventType};
+use crate::fsm::{StateId, Transition};
+use crate::r#impl::fsm::TouchTransition;
+use crate::test::fsm::{StubEvent, StubTouchEvent};
+
+fn transition() -> TouchTransition {
+    TouchTransition::new(
+        StateId(0),
+        StateId(1),
+        [TouchEventType::Touchstart],
+        None,
+        None,
+    )
+}
+
+#[test]
+fn accepted_events() {
+    assert_eq!(
+        *transition().get_accepted_events(),
+        HashSet::from([EventType::Touch(TouchEventType::Touchstart)])
+    );
+}
+
+#[test]
+fn accepts_touch_events_of_its_types() {
+    let tr = transition();
+    assert_eq!(
+        tr.accept(&StubTouchEvent::new(TouchEventType::Touchstart, 1)),
+        true
+    );
+    assert_eq!(
+        tr.accept(&StubTouchEvent::new(TouchEventType::Touchmove, 1)),
+        false
+    );
+    assert_eq!(
+        tr.accept(&StubEvent::new(EventType::Touch(
+            TouchEventType::Touchstart
+        ))),
+        false
+    );
+}
+
+#[test]
+fn touch_id() {
+    let tr = transition().with_touch_id(3);
+    assert_eq!(
+        tr.execute(&StubTouchEvent::new(TouchEventType::Touchstart, 1))
+            .unwrap(),
+        None
+    );
+    let mut event = StubTouchEvent::new(TouchEventType::Touchstart, 1);
+    event.touches.push(Touch::new(3));
+    assert_eq!(tr.execute(&event).unwrap(), Some(StateId(1)));
+    assert_eq!(tr.guard_label(), Some("touch 3"));
+}
+
+#[test]
+fn action_gets_touch_events() {
+    let id = Rc::new(Cell::new(0));
+    let i = id.clone();
+    let tr = TouchTransition::new(
+        StateId(0),
+        StateId(1),
+        [TouchEventType::Touchstart],
+        Some(Box::new(move |e| {
+            i.set(e.changed_touches()[0].identifier);
+            Ok(())
+        })),
+        None,
+    );
+    tr.execute(&StubTouchEvent::new(TouchEventType::Touchstart, 5))
+        .unwrap();
+    assert_eq!(id.get(), 5);
+}
@@ -0,0 +1,152 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::dom::{Event, EventType};
+use crate::fsm::{StateId, Transition, FSM};
+use crate::r#impl::fsm::{FSMValidator, TypedAction, TypedGuard, TypedTransition};
+use crate::test::fsm::{press, release, StubEvent};
+
+struct PointerStub {
+    x: i32,
+}
+
+impl Event for PointerStub {
+    fn event_type(&self) -> EventType {
+        press()
+    }
+}
+
+fn typed(
+    action: Option<TypedAction<PointerStub>>,
+    guard: Option<TypedGuard<PointerStub>>,
+) -> TypedTransition<PointerStub> {
+    TypedTransition::new(
+        StateId(0),
+        StateId(1),
+        HashSet::from([press()]),
+        action,
+        guard,
+    )
+}
+
+#[test]
+fn accepts_events_of_its_type() {
+    let tr = typed(None, None);
+    assert_eq!(tr.accept(&PointerStub { x: 0 }), true);
+}
+
+#[test]
+fn does_not_accept_events_of_other_types() {
+    let tr = typed(None, None);
+    assert_eq!(tr.accept(&StubEvent::press()), false);
+}
+
+#[test]
+fn does_not_accept_other_event_types() {
+    let tr = TypedTransition::<PointerStub>::new(
+        StateId(0),
+        StateId(1),
+        HashSet::from([release()]),
+        None,
+        None,
+    );
+    assert_eq!(tr.execute(&PointerStub { x: 0 }).unwrap(), None);
+}
+
+#[test]
+fn action_gets_typed_event() {
+    let x = Rc::new(Cell::new(0));
+    let x2 = x.clone();
+    let tr = typed(
+        Some(Box::new(move |e| {
+            x2.set(e.x);
+            Ok(())
+        })),
+        None,
+    );
+    assert_eq!(
+        tr.execute(&PointerStub { x: 42 }).unwrap(),
+        Some(StateId(1))
+    );
+    assert_eq!(x.get(), 42);
+}
+
+#[test]
+fn no_action_on_other_types() {
+    let called = Rc::new(Cell::new(false));
+    let c = called.clone();
+    let tr = typed(
+        Some(Box::new(move |_| {
+            c.set(true);
+            Ok(())
+        })),
+        None,
+    );
+    assert_eq!(tr.execute(&StubEvent::press()).unwrap(), None);
+    assert_eq!(called.get(), false);
+}
+
+#[test]
+fn guard_gets_typed_event() {
+    let tr = typed(None, Some(Box::new(|e| Ok(e.x > 10))));
+    assert_eq!(tr.guard(&PointerStub { x: 11 }).unwrap(), true);
+    assert_eq!(tr.guard(&StubEvent::press()).unwrap(), false);
+    assert_eq!(tr.execute(&PointerStub { x: 5 }).unwrap(), None);
+    assert_eq!(
+        tr.execute(&PointerStub { x: 15 }).unwrap(),
+        Some(StateId(1))
+    );
+}
+
+#[test]
+fn guard_label() {
+    assert_eq!(typed(None, None).guard_label(), None);
+    assert_eq!(
+        typed(None, Some(Box::new(|_| Ok(true)))).guard_label(),
+        Some("guard")
+    );
+    assert_eq!(
+        typed(None, Some(Box::new(|_| Ok(true))))
+            .with_guard_label("far")
+            .guard_label(),
+        Some("far")
+    );
+}
+
+#[test]
+fn typed_transitions_in_macro() {
+    let x = Rc::new(Cell::new(0));
+    let x2 = x.clone();
+    let mut fsm = crate::fsm! {
+        states {
+            init: init,
+            pressed: terminal,
+        }
+        transitions {
+            init -> pressed on<PointerStub> [press()] when "right side" (|e: &PointerStub| Ok(e.x > 0))
+                action (move |e: &PointerStub| {
+                    x2.set(e.x);
+                    Ok(())
+                });
+        }
+    };
+    assert_eq!(FSMValidator::validate(&fsm), Ok(()));
+    assert_eq!(fsm.process(&StubEvent::press()), false);
+    assert_eq!(fsm.process(&PointerStub { x: -1 }), false);
+    assert_eq!(fsm.process(&PointerStub { x: 7 }), true);
+    assert_eq!(x.get(), 7);
+}
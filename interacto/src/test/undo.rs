@@ -12,18 +12,18 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{command::CustomCmd, undo::Undo, undoble::Undoable, undohistory::UndoHistoryBase};
+use crate::{command::{Command, CustomCmd}, undo::Undo, undoble::Undoable, undohistory::UndoHistoryBase};
 use mockall::mock;
 
 mock! {
     FakeHistory {}
     impl UndoHistoryBase for FakeHistory {
-        fn undo(&self);
-        fn redo(&self);
-        fn clear(&self);
-        fn add(&self, undoable: Box<dyn Undoable>);
+        fn undo(&mut self);
+        fn redo(&mut self);
+        fn clear(&mut self);
+        fn add(&mut self, undoable: Box<dyn Undoable>);
         fn get_last_undo(&self) -> Option<&'static dyn Undoable>;
-        fn get_last_redo(&self) -> Option<Box<dyn Undoable + 'static>>;
+        fn get_last_redo(&self) -> Option<&'static dyn Undoable>;
     }
 }
 mock! {
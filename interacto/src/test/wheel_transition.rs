@@ -0,0 +1,44 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::HashSet;
+
+use crate::dom::{EventType, MouseEventType};
+use crate::fsm::{StateId, Transition};
+use crate::r#impl::fsm::WheelTransition;
+use crate::test::fsm::{StubEvent, StubMouseEvent, StubWheelEvent};
+
+#[test]
+fn accepted_events() {
+    let tr = WheelTransition::new(StateId(0), StateId(1), None, None);
+    assert_eq!(*tr.get_accepted_events(), HashSet::from([EventType::Wheel]));
+}
+
+#[test]
+fn accepts_wheel_events() {
+    let tr = WheelTransition::new(
+        StateId(0),
+        StateId(1),
+        None,
+        Some(Box::new(|e| Ok(e.button() == 0))),
+    );
+    assert_eq!(tr.accept(&StubEvent::new(EventType::Wheel)), false);
+    assert_eq!(
+        tr.accept(&StubMouseEvent {
+            event_type: MouseEventType::Mousedown,
+            button: 0
+        }),
+        false
+    );
+    assert_eq!(tr.execute(&StubWheelEvent).unwrap(), Some(StateId(1)));
+}
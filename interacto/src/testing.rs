@@ -0,0 +1,531 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Support for property-based testing and fuzzing of user interactions.
+//! This module generates well-formed sequences of events (see `Gesture`),
+//! either from `proptest` strategies (feature `proptest`) or from unstructured data
+//! through `arbitrary` (feature `arbitrary`), and checks the invariants of the FSMs
+//! that process them (see `check_fsm_invariants`).
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use crate::clock::Clock;
+use crate::dom::{
+    Event, EventType, KeyEventType, KeyboardEvent, Modifiers, MouseEvent, MouseEventType, Touch,
+    TouchEvent, TouchEventType, WheelEvent,
+};
+use crate::fsm::{CancelFSMException, FSMHandler, FSM};
+use crate::r#impl::clock::VirtualClock;
+
+/// The keys the generated key events use, given by their key and code values.
+/// The last two are modifier keys.
+pub const SAMPLE_KEYS: [(&str, &str); 6] = [
+    ("a", "KeyA"),
+    ("b", "KeyB"),
+    ("Enter", "Enter"),
+    ("Escape", "Escape"),
+    ("Shift", "ShiftLeft"),
+    ("Control", "ControlLeft"),
+];
+
+/// A generated event. It carries the time (in ms) at which it occurs.
+/// # Category
+/// Testing
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleEvent {
+    Mouse {
+        event_type: MouseEventType,
+        button: i16,
+        timestamp: u64,
+    },
+    Key {
+        event_type: KeyEventType,
+        key: &'static str,
+        code: &'static str,
+        modifiers: Modifiers,
+        timestamp: u64,
+    },
+    Touch {
+        event_type: TouchEventType,
+        touches: Vec<Touch>,
+        timestamp: u64,
+    },
+    Wheel {
+        timestamp: u64,
+    },
+    /// An input, scroll or change event.
+    Other {
+        event_type: EventType,
+        timestamp: u64,
+    },
+}
+
+impl Event for SampleEvent {
+    fn event_type(&self) -> EventType {
+        match self {
+            Self::Mouse { event_type, .. } => EventType::Mouse(*event_type),
+            Self::Key { event_type, .. } => EventType::Key(*event_type),
+            Self::Touch { event_type, .. } => EventType::Touch(*event_type),
+            Self::Wheel { .. } => EventType::Wheel,
+            Self::Other { event_type, .. } => *event_type,
+        }
+    }
+
+    fn timestamp(&self) -> u64 {
+        match self {
+            Self::Mouse { timestamp, .. }
+            | Self::Key { timestamp, .. }
+            | Self::Touch { timestamp, .. }
+            | Self::Wheel { timestamp }
+            | Self::Other { timestamp, .. } => *timestamp,
+        }
+    }
+
+    fn as_mouse_event(&self) -> Option<&(dyn MouseEvent + 'static)> {
+        match self {
+            Self::Mouse { .. } | Self::Wheel { .. } => Some(self),
+            _ => None,
+        }
+    }
+
+    fn as_touch_event(&self) -> Option<&(dyn TouchEvent + 'static)> {
+        match self {
+            Self::Touch { .. } => Some(self),
+            _ => None,
+        }
+    }
+
+    fn as_keyboard_event(&self) -> Option<&(dyn KeyboardEvent + 'static)> {
+        match self {
+            Self::Key { .. } => Some(self),
+            _ => None,
+        }
+    }
+
+    fn as_wheel_event(&self) -> Option<&(dyn WheelEvent + 'static)> {
+        match self {
+            Self::Wheel { .. } => Some(self),
+            _ => None,
+        }
+    }
+}
+
+/// Only meaningful for mouse and wheel events.
+impl MouseEvent for SampleEvent {
+    fn button(&self) -> i16 {
+        match self {
+            Self::Mouse { button, .. } => *button,
+            _ => 0,
+        }
+    }
+}
+
+/// Only meaningful for key events.
+impl KeyboardEvent for SampleEvent {
+    fn key(&self) -> &str {
+        match self {
+            Self::Key { key, .. } => key,
+            _ => "",
+        }
+    }
+
+    fn code(&self) -> &str {
+        match self {
+            Self::Key { code, .. } => code,
+            _ => "",
+        }
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        match self {
+            Self::Key { modifiers, .. } => *modifiers,
+            _ => Modifiers::NONE,
+        }
+    }
+}
+
+/// Only meaningful for touch events.
+impl TouchEvent for SampleEvent {
+    fn changed_touches(&self) -> &[Touch] {
+        match self {
+            Self::Touch { touches, .. } => touches,
+            _ => &[],
+        }
+    }
+}
+
+impl WheelEvent for SampleEvent {}
+
+/// A user action, that produces a well-formed sequence of events (see `gesture_events`).
+/// # Category
+/// Testing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gesture {
+    /// A button pressed, the pointer moved `moves` times, then the button released.
+    /// The release is followed by a click (or by an auxclick for the other buttons than the main one).
+    Pointer { button: i16, moves: usize },
+    /// Keys (given by their positions in `SAMPLE_KEYS`) pressed in order, then released in reverse order.
+    /// The key events carry the modifier keys pressed so far.
+    Keys(Vec<usize>),
+    /// `count` touch points that touch the surface in order, move `moves` times each (in turn),
+    /// then leave the surface in order.
+    Touches { count: usize, moves: usize },
+    /// A wheel rotation.
+    Wheel,
+    /// An input, scroll or change event.
+    Other(EventType),
+}
+
+/// The events of the given gestures, each one being paired with the delay (in ms) that precedes
+/// each of its events. Timestamps thus never decrease, and start from 0.
+/// Touch identifiers are never reused, and a key is pressed at most once per gesture.
+pub fn gesture_events(gestures: &[(Gesture, u64)]) -> Vec<SampleEvent> {
+    let mut events = Vec::new();
+    let mut timestamp = 0u64;
+    let mut next_touch = 0;
+    for (gesture, delay) in gestures {
+        let mut next = || {
+            timestamp = timestamp.saturating_add(*delay);
+            timestamp
+        };
+        match gesture {
+            Gesture::Pointer { button, moves } => {
+                let button = *button;
+                let mut mouse = |event_type| SampleEvent::Mouse {
+                    event_type,
+                    button,
+                    timestamp: next(),
+                };
+                events.push(mouse(MouseEventType::Mousedown));
+                for _ in 0..*moves {
+                    events.push(mouse(MouseEventType::Mousemove));
+                }
+                events.push(mouse(MouseEventType::Mouseup));
+                events.push(mouse(if button == 0 {
+                    MouseEventType::Click
+                } else {
+                    MouseEventType::Auxclick
+                }));
+            }
+            Gesture::Keys(keys) => {
+                let mut seen = HashSet::new();
+                let keys: Vec<usize> = keys
+                    .iter()
+                    .map(|key| key % SAMPLE_KEYS.len())
+                    .filter(|key| seen.insert(*key))
+                    .collect();
+                let mut modifiers = Modifiers::NONE;
+                let released = keys.iter().rev().map(|key| (KeyEventType::Keyup, *key));
+                for (event_type, index) in keys
+                    .iter()
+                    .map(|key| (KeyEventType::Keydown, *key))
+                    .chain(released)
+                {
+                    let (key, code) = SAMPLE_KEYS[index];
+                    let pressed = event_type == KeyEventType::Keydown;
+                    match key {
+                        "Shift" => modifiers.shift = pressed,
+                        "Control" => modifiers.ctrl = pressed,
+                        _ => {}
+                    }
+                    events.push(SampleEvent::Key {
+                        event_type,
+                        key,
+                        code,
+                        modifiers,
+                        timestamp: next(),
+                    });
+                }
+            }
+            Gesture::Touches { count, moves } => {
+                let ids: Vec<i32> = (next_touch..next_touch + *count as i32).collect();
+                next_touch += *count as i32;
+                let mut touch = |event_type, identifier| SampleEvent::Touch {
+                    event_type,
+                    touches: vec![Touch::new(identifier)],
+                    timestamp: next(),
+                };
+                events.extend(ids.iter().map(|id| touch(TouchEventType::Touchstart, *id)));
+                for _ in 0..*moves {
+                    events.extend(ids.iter().map(|id| touch(TouchEventType::Touchmove, *id)));
+                }
+                events.extend(ids.iter().map(|id| touch(TouchEventType::Touchend, *id)));
+            }
+            Gesture::Wheel => events.push(SampleEvent::Wheel { timestamp: next() }),
+            Gesture::Other(event_type) => events.push(SampleEvent::Other {
+                event_type: *event_type,
+                timestamp: next(),
+            }),
+        }
+    }
+    events
+}
+
+/// `proptest` strategies that generate gestures and well-formed event sequences.
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategies {
+    use proptest::prelude::*;
+
+    use super::{gesture_events, Gesture, SampleEvent, SAMPLE_KEYS};
+    use crate::dom::EventType;
+
+    /// The maximal delay (in ms) between two generated events.
+    pub const MAX_DELAY: u64 = 500;
+
+    pub fn pointer_gesture() -> impl Strategy<Value = Gesture> {
+        (0i16..=2, 0usize..=8).prop_map(|(button, moves)| Gesture::Pointer { button, moves })
+    }
+
+    pub fn key_gesture() -> impl Strategy<Value = Gesture> {
+        prop::sample::subsequence((0..SAMPLE_KEYS.len()).collect::<Vec<_>>(), 1..=3)
+            .prop_shuffle()
+            .prop_map(Gesture::Keys)
+    }
+
+    pub fn touch_gesture() -> impl Strategy<Value = Gesture> {
+        (1usize..=3, 0usize..=4).prop_map(|(count, moves)| Gesture::Touches { count, moves })
+    }
+
+    pub fn gesture() -> impl Strategy<Value = Gesture> {
+        prop_oneof![
+            pointer_gesture(),
+            key_gesture(),
+            touch_gesture(),
+            Just(Gesture::Wheel),
+            prop_oneof![
+                Just(EventType::Input),
+                Just(EventType::Scroll),
+                Just(EventType::Change),
+            ]
+            .prop_map(Gesture::Other),
+        ]
+    }
+
+    /// The events of at most `max_gestures` gestures generated by the given strategy.
+    pub fn events_of(
+        gesture: impl Strategy<Value = Gesture>,
+        max_gestures: usize,
+    ) -> impl Strategy<Value = Vec<SampleEvent>> {
+        prop::collection::vec((gesture, 0..=MAX_DELAY), 0..=max_gestures)
+            .prop_map(|gestures| gesture_events(&gestures))
+    }
+
+    /// Pointer events: presses, moves and releases, with monotone timestamps.
+    pub fn pointer_sequence(max_gestures: usize) -> impl Strategy<Value = Vec<SampleEvent>> {
+        events_of(pointer_gesture(), max_gestures)
+    }
+
+    /// Key events, each key press being paired with a release.
+    pub fn key_sequence(max_gestures: usize) -> impl Strategy<Value = Vec<SampleEvent>> {
+        events_of(key_gesture(), max_gestures)
+    }
+
+    /// Touch events, each touch point starting, moving then ending.
+    pub fn touch_sequence(max_gestures: usize) -> impl Strategy<Value = Vec<SampleEvent>> {
+        events_of(touch_gesture(), max_gestures)
+    }
+
+    /// Events of all the kinds of gestures.
+    pub fn event_sequence(max_gestures: usize) -> impl Strategy<Value = Vec<SampleEvent>> {
+        events_of(gesture(), max_gestures)
+    }
+}
+
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for Gesture {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=4)? {
+            0 => Self::Pointer {
+                button: u.int_in_range(0..=2)?,
+                moves: u.int_in_range(0..=8)?,
+            },
+            1 => {
+                let count = u.int_in_range(1..=3)?;
+                let keys = (0..count)
+                    .map(|_| u.choose_index(SAMPLE_KEYS.len()))
+                    .collect::<arbitrary::Result<_>>()?;
+                Self::Keys(keys)
+            }
+            2 => Self::Touches {
+                count: u.int_in_range(1..=3)?,
+                moves: u.int_in_range(0..=4)?,
+            },
+            3 => Self::Wheel,
+            _ => {
+                Self::Other(*u.choose(&[EventType::Input, EventType::Scroll, EventType::Change])?)
+            }
+        })
+    }
+}
+
+/// A well-formed sequence of events (see `gesture_events`), that fuzzers can generate.
+/// # Category
+/// Testing
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSequence(pub Vec<SampleEvent>);
+
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for EventSequence {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut gestures = Vec::new();
+        u.arbitrary_loop(None, Some(32), |u| {
+            gestures.push((u.arbitrary()?, u.int_in_range(0..=500)?));
+            Ok(std::ops::ControlFlow::Continue(()))
+        })?;
+        Ok(Self(gesture_events(&gestures)))
+    }
+}
+
+/// An invariant of an FSM that does not hold, as detected by `check_fsm_invariants`.
+/// # Category
+/// Testing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// The position of the event whose processing broke the invariant.
+    pub event: usize,
+    pub message: String,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "event #{}: {}", self.event, self.message)
+    }
+}
+
+impl Error for InvariantViolation {}
+
+/// Follows the notifications of an FSM and checks that they are balanced.
+#[derive(Default)]
+struct LifecycleChecker {
+    started: bool,
+    /// True: the FSM stopped or cancelled while processing the current event.
+    ended: bool,
+    violation: Option<String>,
+}
+
+impl LifecycleChecker {
+    fn expect_started(&mut self, notification: &str) {
+        if !self.started && self.violation.is_none() {
+            self.violation = Some(format!("{notification} while not started"));
+        }
+    }
+
+    fn end(&mut self, notification: &str) {
+        self.expect_started(notification);
+        self.started = false;
+        self.ended = true;
+    }
+}
+
+impl FSMHandler for LifecycleChecker {
+    fn fsm_starts(&mut self) -> Result<(), CancelFSMException> {
+        if self.started && self.violation.is_none() {
+            self.violation = Some("started while already started".to_string());
+        }
+        self.started = true;
+        Ok(())
+    }
+
+    fn fsm_updates(&mut self) -> Result<(), CancelFSMException> {
+        self.expect_started("updated");
+        Ok(())
+    }
+
+    fn fsm_stops(&mut self) -> Result<(), CancelFSMException> {
+        self.end("stopped");
+        Ok(())
+    }
+
+    fn fsm_cancels(&mut self) {
+        self.end("cancelled");
+    }
+
+    fn fsm_error(&mut self, _err: &(dyn Error + Send + Sync)) {}
+
+    fn pre_fsm_start(&mut self) {}
+
+    fn pre_fsm_update(&mut self) {}
+
+    fn pre_fsm_stop(&mut self) {}
+}
+
+/// Makes the given FSM process the given events, and checks after each event that:
+/// - `FSM::process` did not panic;
+/// - the handlers of the FSM received balanced notifications: a start, updates,
+///   then a stop or a cancellation, and `FSM::started` agrees with them;
+/// - the FSM went back to its initial state when it stopped or cancelled.
+///
+/// If a virtual clock is given (the clock of the FSM), it is set to the time of each event
+/// (relatively to its current time) before the event is processed, so that timeout transitions run.
+/// The FSM must not have events waiting for the end of its current execution
+/// (see `FSM::add_remaining_event_to_process`).
+/// # Errors
+/// The first invariant that does not hold. The FSM must not be used after a panic.
+pub fn check_fsm_invariants(
+    fsm: &mut dyn FSM,
+    events: &[SampleEvent],
+    clock: Option<&VirtualClock>,
+) -> Result<(), InvariantViolation> {
+    let checker = Rc::new(RefCell::new(LifecycleChecker {
+        started: fsm.started(),
+        ..LifecycleChecker::default()
+    }));
+    let handler: Rc<RefCell<dyn FSMHandler>> = checker.clone();
+    fsm.add_handler(handler.clone());
+    let origin = clock.map(|clock| clock.now());
+    let res = events.iter().enumerate().try_for_each(|(index, event)| {
+        if let (Some(clock), Some(origin)) = (clock, origin) {
+            clock.set(origin.saturating_add(event.timestamp()));
+        }
+        checker.borrow_mut().ended = false;
+        let violation = |message: String| InvariantViolation {
+            event: index,
+            message,
+        };
+        if let Err(cause) = panic::catch_unwind(AssertUnwindSafe(|| fsm.process(event))) {
+            let message = cause
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| cause.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            return Err(violation(format!(
+                "processing {event:?} panicked: {message}"
+            )));
+        }
+        let checker = checker.borrow();
+        if let Some(message) = &checker.violation {
+            return Err(violation(message.clone()));
+        }
+        if checker.started != fsm.started() {
+            return Err(violation(format!(
+                "the FSM is {}started while its handlers were notified otherwise",
+                if fsm.started() { "" } else { "not " }
+            )));
+        }
+        if checker.ended && !fsm.started() && fsm.current_state().id() != fsm.init_state().id() {
+            return Err(violation(format!(
+                "the FSM is in state '{}' after having stopped or cancelled",
+                fsm.current_state().name()
+            )));
+        }
+        Ok(())
+    });
+    fsm.remove_handler(&handler);
+    res
+}
@@ -12,6 +12,8 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use alloc::boxed::Box;
+
 use crate::{undohistory::UndoHistoryBase, command::CustomCmd};
 
 pub struct Undo {
@@ -12,6 +12,8 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use alloc::string::String;
+
 /**
  * An interface for undoable objects.
  */
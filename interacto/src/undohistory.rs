@@ -12,6 +12,8 @@
  * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use alloc::boxed::Box;
+
 use crate::undoble::Undoable;
 
 /**
@@ -21,23 +23,23 @@ pub trait UndoHistoryBase {
     /**
      * Undoes the last undoable object.
      */
-    fn undo(&self);
+    fn undo(&mut self);
 
     /**
      * Redoes the last undoable object.
      */
-    fn redo(&self);
+    fn redo(&mut self);
 
     /**
      * Removes all the undoable objects of the collector.
      */
-    fn clear(&self);
+    fn clear(&mut self);
 
     /**
      * Adds an undoable object to the collector.
      * @param undoable - The undoable object to add.
      */
-    fn add(&self, undoable: Box<dyn Undoable>);
+    fn add(&mut self, undoable: Box<dyn Undoable>);
 
     /**
      * @returns The last undoable object or undefined if there is no last object.
@@ -47,7 +49,7 @@ pub trait UndoHistoryBase {
     /**
      * @returns The last redoable object or undefined if there is no last object.
      */
-    fn get_last_redo(&self) -> Option<Box<dyn Undoable>>;
+    fn get_last_redo(&self) -> Option<&dyn Undoable>;
 
     // /**
     //  * @returns The last undoable object name or undefined if there is no last object.
@@ -88,12 +90,12 @@ pub trait UndoHistory: UndoHistoryBase {
     /**
      * @returns The stack of saved undoable objects.
      */
-    fn get_undo(&self) -> Vec<Box<dyn Undoable>>;
+    fn get_undo(&self) -> &[Box<dyn Undoable>];
 
     /**
      * @returns The stack of saved redoable objects
      */
-    fn get_redo(&self) -> Vec<Box<dyn Undoable>>;
+    fn get_redo(&self) -> &[Box<dyn Undoable>];
 
     /**
      * @returns The max number of saved undoable objects.
@@ -101,7 +103,9 @@ pub trait UndoHistory: UndoHistoryBase {
     fn get_size_max(&self) -> u64;
 
     /**
-     * @param max - The max number of saved undoable objects. Must be great than 0.
+     * @param max - The max number of saved undoable objects.
+     * The oldest undoable objects that exceed this number are removed.
+     * If 0, the history stores nothing.
      */
     fn set_size_max(&mut self, max: u64);
 }
@@ -0,0 +1,63 @@
+/*
+ * This file is part of Interacto.
+ * Interacto is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * Interacto is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License
+ * along with Interacto.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Checks that the FSMs, commands and undo build without the standard library.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Checks the library without its default features, for the given target (or the host).
+fn check_without_std(target: Option<&str>) {
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["check", "--lib", "--no-default-features"])
+        .arg("--target-dir")
+        .arg(concat!(env!("CARGO_TARGET_TMPDIR"), "/no_std"));
+    if let Some(target) = target {
+        cargo.args(["--target", target]);
+    }
+    let output = cargo.output().expect("cargo must run");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn builds_without_default_features() {
+    check_without_std(None);
+}
+
+/// The host has a standard library, so only a target without one shows that
+/// nothing uses it. The check needs the target to be installed (as in CI).
+#[test]
+#[ignore = "needs the thumbv7em-none-eabihf target; run with --ignored"]
+fn builds_for_target_without_std() {
+    const TARGET: &str = "thumbv7em-none-eabihf";
+    let sysroot = Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .output()
+        .expect("rustc must run");
+    let sysroot = String::from_utf8_lossy(&sysroot.stdout);
+    assert!(
+        Path::new(sysroot.trim())
+            .join("lib/rustlib")
+            .join(TARGET)
+            .exists(),
+        "the {TARGET} target is not installed: rustup target add {TARGET}"
+    );
+    check_without_std(Some(TARGET));
+}